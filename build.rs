@@ -0,0 +1,125 @@
+// Expose the git revision and build time to `/version` and embed the
+// vendored browser libraries.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Vendored third-party files, see `contrib/fetch-assets.sh`.
+const ASSET_DIR: &str = "assets/vendor";
+
+/// Files the console pages load, see `dashboard::assets`.
+const REQUIRED_ASSETS: &[&str] = &[
+    "novnc/core/rfb.js",
+    "xterm/lib/xterm.js",
+    "xterm/css/xterm.css",
+];
+
+/// Set to build without the vendored files anyway, e.g. offline; the
+/// console pages then only say that their client is missing.
+const WITHOUT_ASSETS: &str = "RUST_MANAGER_WITHOUT_ASSETS";
+
+fn collect(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// Write `assets.rs` – a table of `(path, contents)` for everything below
+/// `ASSET_DIR` – to OUT_DIR for `dashboard::assets`.
+fn embed_assets() {
+    let root = Path::new(ASSET_DIR);
+    let mut files = Vec::new();
+    collect(root, &mut files);
+    files.sort();
+    let missing: Vec<&str> = REQUIRED_ASSETS
+        .iter()
+        .copied()
+        .filter(|name| !root.join(name).is_file())
+        .collect();
+    if !missing.is_empty() {
+        let message = format!(
+            "{} lacks {} – run contrib/fetch-assets.sh, or set {}=1 to build \
+             without working console pages",
+            ASSET_DIR,
+            missing.join(", "),
+            WITHOUT_ASSETS
+        );
+        if std::env::var_os(WITHOUT_ASSETS).is_none() {
+            panic!("{}", message);
+        }
+        println!("cargo:warning={}", message);
+    }
+    let mut table = String::from("&[\n");
+    for file in files {
+        let name = file
+            .strip_prefix(root)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+        let absolute = std::fs::canonicalize(&file).unwrap();
+        table.push_str(&format!(
+            "    ({:?}, include_bytes!({:?}) as &[u8]),\n",
+            name, absolute
+        ));
+    }
+    table.push(']');
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("assets.rs");
+    std::fs::write(out, table).unwrap();
+}
+
+/// Trimmed output of a successful `git` command.
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Files that change with every new commit: HEAD only moves when switching
+/// branches, the branch's ref file on each commit, and `packed-refs` holds
+/// the branch after `git gc`. Missing files would rerun the script on every
+/// build, so only existing ones are returned.
+fn git_watch_paths() -> Vec<String> {
+    let mut names = vec!["HEAD".to_string(), "packed-refs".to_string()];
+    names.extend(git(&["symbolic-ref", "-q", "HEAD"]));
+    names
+        .iter()
+        .filter_map(|name| git(&["rev-parse", "--git-path", name]))
+        .filter(|path| Path::new(path).exists())
+        .collect()
+}
+
+fn main() {
+    let git_hash = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_else(|| "unknown".into());
+
+    // Honour SOURCE_DATE_EPOCH for reproducible builds
+    let build_time = std::env::var("SOURCE_DATE_EPOCH").unwrap_or_else(|_| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+            .to_string()
+    });
+
+    println!("cargo:rustc-env=RUST_MANAGER_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=RUST_MANAGER_BUILD_TIME={}", build_time);
+    for path in git_watch_paths() {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    embed_assets();
+    println!("cargo:rerun-if-changed=assets");
+    println!("cargo:rerun-if-env-changed={}", WITHOUT_ASSETS);
+}
@@ -0,0 +1,28 @@
+#!/bin/sh
+# Vendor the browser libraries of the console pages into assets/vendor,
+# from where build.rs embeds them into the binary. npm checks every
+# tarball against the registry's integrity hash. Commit the result.
+set -eu
+
+NOVNC_VERSION=1.4.0
+XTERM_VERSION=5.5.0
+
+cd "$(dirname "$0")/.."
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+(cd "$tmp" && npm pack --silent "@novnc/novnc@$NOVNC_VERSION" "@xterm/xterm@$XTERM_VERSION" >/dev/null)
+mkdir "$tmp/novnc" "$tmp/xterm"
+tar -xzf "$tmp/novnc-novnc-$NOVNC_VERSION.tgz" -C "$tmp/novnc"
+tar -xzf "$tmp/xterm-xterm-$XTERM_VERSION.tgz" -C "$tmp/xterm"
+
+rm -rf assets/vendor/novnc assets/vendor/xterm
+mkdir -p assets/vendor/novnc assets/vendor/xterm/lib assets/vendor/xterm/css
+# rfb.js imports the other core modules and pako from ../vendor
+cp -r "$tmp/novnc/package/core" "$tmp/novnc/package/vendor" assets/vendor/novnc/
+cp "$tmp"/novnc/package/docs/LICENSE* assets/vendor/novnc/
+cp "$tmp/xterm/package/lib/xterm.js" assets/vendor/xterm/lib/
+cp "$tmp/xterm/package/css/xterm.css" assets/vendor/xterm/css/
+cp "$tmp/xterm/package/LICENSE" assets/vendor/xterm/
+
+echo "noVNC $NOVNC_VERSION and xterm.js $XTERM_VERSION are in assets/vendor"
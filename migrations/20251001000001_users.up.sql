@@ -0,0 +1,7 @@
+-- Users that may log in to rust-manager.  `IF NOT EXISTS` keeps databases
+-- created before migrations were introduced working.
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL
+);
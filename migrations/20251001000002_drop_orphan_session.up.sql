@@ -0,0 +1,3 @@
+-- `session` was created at startup but never used; axum_session keeps its
+-- data in `sessions_table`.
+DROP TABLE IF EXISTS session;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS backup_retention;
+DROP INDEX IF EXISTS backups_domain;
+DROP TABLE IF EXISTS backups;
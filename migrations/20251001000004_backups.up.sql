@@ -0,0 +1,27 @@
+-- Push-mode backups started through rust-manager. Every backup creates a
+-- checkpoint that the next incremental backup of the domain is based on.
+CREATE TABLE backups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    domain_uuid TEXT NOT NULL,
+    domain_name TEXT NOT NULL,
+    kind TEXT NOT NULL,                 -- full | incremental
+    checkpoint TEXT NOT NULL,
+    parent_checkpoint TEXT,
+    target_dir TEXT NOT NULL,
+    files TEXT NOT NULL DEFAULT '',     -- one image path per line
+    status TEXT NOT NULL,               -- running | completed | failed | cancelled
+    bytes_total INTEGER,
+    bytes_processed INTEGER,
+    error TEXT,
+    started_by TEXT NOT NULL,
+    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TEXT
+);
+
+CREATE INDEX backups_domain ON backups (domain_uuid, id);
+
+-- How many full backups (with their incrementals) to keep per domain.
+CREATE TABLE backup_retention (
+    domain_uuid TEXT PRIMARY KEY,
+    keep_full INTEGER NOT NULL
+);
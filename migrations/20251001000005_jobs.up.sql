@@ -0,0 +1,27 @@
+-- Recurring API calls run by the built-in scheduler on behalf of `owner`.
+CREATE TABLE jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    schedule TEXT NOT NULL,             -- cron expression, server local time
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    body TEXT,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    next_run INTEGER,                   -- unix seconds
+    last_run TEXT,
+    last_status INTEGER,                -- HTTP status of the last run
+    last_outcome TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE job_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id INTEGER NOT NULL,
+    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TEXT,
+    status INTEGER,
+    outcome TEXT
+);
+
+CREATE INDEX job_runs_job ON job_runs (job_id, id);
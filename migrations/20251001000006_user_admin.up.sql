@@ -0,0 +1,4 @@
+-- Administrators may do things ordinary users may not, such as setting
+-- secret values.  The oldest account (the seeded `admin`) becomes one.
+ALTER TABLE users ADD COLUMN admin INTEGER NOT NULL DEFAULT 0;
+UPDATE users SET admin = 1 WHERE id = (SELECT MIN(id) FROM users);
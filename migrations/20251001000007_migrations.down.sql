@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS migrations_domain;
+DROP TABLE IF EXISTS migrations;
@@ -0,0 +1,19 @@
+-- Domain migrations to registered hosts started through rust-manager.
+CREATE TABLE migrations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    domain_uuid TEXT NOT NULL,
+    domain_name TEXT NOT NULL,
+    destination TEXT NOT NULL,          -- registered host name
+    mode TEXT NOT NULL,                 -- live | suspended | offline
+    options TEXT NOT NULL DEFAULT '',   -- space separated, e.g. "p2p copy-storage=all"
+    status TEXT NOT NULL,               -- running | completed | failed | cancelled | interrupted
+    bytes_total INTEGER,
+    bytes_processed INTEGER,
+    post_copy INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_by TEXT NOT NULL,
+    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TEXT
+);
+
+CREATE INDEX migrations_domain ON migrations (domain_uuid, id);
@@ -0,0 +1,8 @@
+-- Domains marked as templates that new domains are cloned from.
+CREATE TABLE templates (
+    domain_uuid TEXT PRIMARY KEY,
+    domain_name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,707 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use virt::{domain::Domain, error::Error, storage_vol::StorageVol, sys};
+
+use super::console::uri_host;
+use super::domains::lookup as lookup_domain;
+use super::pools::lookup as lookup_pool;
+use super::{ApiResult, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape};
+use crate::backups::{self, Backup, NewBackup};
+use crate::{AppState, audit, auth::CurrentUser};
+
+/// How often a running backup job is asked for progress.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Audit actor for backups removed by a retention policy.
+const RETENTION_ACTOR: &str = "retention";
+
+// ---------------------------------------------------------------------
+// Checkpoint information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct CheckpointInfo {
+    pub name: String,
+    pub parent: Option<String>,
+    pub description: String,
+    /// Creation time as seconds since the unix epoch
+    pub created: i64,
+    /// Disks tracked by a dirty bitmap
+    pub disks: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCheckpoint {
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+pub struct StartBackup {
+    /// Only copy blocks changed since the last completed backup
+    #[serde(default)]
+    incremental: bool,
+    /// Absolute directory the disk images are written to
+    #[serde(default)]
+    target_dir: Option<String>,
+    /// Alternatively a directory based storage pool
+    #[serde(default)]
+    pool: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Retention {
+    /// Full backups to keep; `null` keeps everything
+    pub keep_full: Option<i64>,
+}
+
+/// Owned `virDomainCheckpointPtr` – the virt crate has no checkpoint API.
+struct Checkpoint(sys::virDomainCheckpointPtr);
+
+impl Drop for Checkpoint {
+    fn drop(&mut self) {
+        unsafe { sys::virDomainCheckpointFree(self.0) };
+    }
+}
+
+impl Checkpoint {
+    fn list(dom: &Domain) -> Result<Vec<Checkpoint>, Error> {
+        let mut list: *mut sys::virDomainCheckpointPtr = std::ptr::null_mut();
+        let size = unsafe { sys::virDomainListAllCheckpoints(dom.as_ptr(), &mut list, 0) };
+        if size == -1 {
+            return Err(Error::last_error());
+        }
+        // SAFETY: libvirt returned `size` checkpoints which we now own; the
+        // array itself is freed here, each checkpoint when it is dropped.
+        let mut out = Vec::with_capacity(size as usize);
+        unsafe {
+            for i in 0..size as isize {
+                out.push(Checkpoint(*list.offset(i)));
+            }
+            if !list.is_null() {
+                libc::free(list as *mut libc::c_void);
+            }
+        }
+        Ok(out)
+    }
+
+    fn lookup(dom: &Domain, name: &str) -> Result<Checkpoint, Error> {
+        let name = CString::new(name).unwrap_or_default();
+        let ptr = unsafe { sys::virDomainCheckpointLookupByName(dom.as_ptr(), name.as_ptr(), 0) };
+        if ptr.is_null() {
+            return Err(Error::last_error());
+        }
+        Ok(Checkpoint(ptr))
+    }
+
+    fn create(dom: &Domain, xml: &str) -> Result<Checkpoint, Error> {
+        let xml = CString::new(xml).unwrap_or_default();
+        let ptr = unsafe { sys::virDomainCheckpointCreateXML(dom.as_ptr(), xml.as_ptr(), 0) };
+        if ptr.is_null() {
+            return Err(Error::last_error());
+        }
+        Ok(Checkpoint(ptr))
+    }
+
+    fn xml(&self) -> Result<String, Error> {
+        let xml = unsafe { sys::virDomainCheckpointGetXMLDesc(self.0, 0) };
+        if xml.is_null() {
+            return Err(Error::last_error());
+        }
+        let out = unsafe { CStr::from_ptr(xml) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { libc::free(xml as *mut libc::c_void) };
+        Ok(out)
+    }
+
+    /// Remove the checkpoint; its bitmap is merged into the parent.
+    fn delete(&self) -> Result<(), Error> {
+        if unsafe { sys::virDomainCheckpointDelete(self.0, 0) } == -1 {
+            return Err(Error::last_error());
+        }
+        Ok(())
+    }
+}
+
+fn parse_checkpoint_xml(xml: &str) -> Result<CheckpointInfo, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let child = |name: &str| root.children().find(|n| n.has_tag_name(name));
+    let text = |name: &str| {
+        child(name)
+            .and_then(|n| n.text())
+            .unwrap_or_default()
+            .to_string()
+    };
+    Ok(CheckpointInfo {
+        name: text("name"),
+        parent: child("parent")
+            .and_then(|p| p.children().find(|n| n.has_tag_name("name")))
+            .and_then(|n| n.text())
+            .map(str::to_string),
+        description: text("description"),
+        created: text("creationTime").parse().unwrap_or(0),
+        disks: child("disks")
+            .map(|disks| {
+                disks
+                    .children()
+                    .filter(|d| d.attribute("checkpoint") == Some("bitmap"))
+                    .filter_map(|d| d.attribute("name"))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+fn checkpoint_info(checkpoint: &Checkpoint) -> ApiResult<CheckpointInfo> {
+    let xml = checkpoint.xml().map_err(libvirt_error)?;
+    parse_checkpoint_xml(&xml).map_err(internal_error)
+}
+
+/// Target names (`vda`, `sdb`, …) of the domain's disks, CD-ROMs excluded.
+fn disk_targets(dom: &Domain) -> ApiResult<Vec<String>> {
+    let xml = dom.get_xml_desc(0).map_err(libvirt_error)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(internal_error)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name("disk") && n.attribute("device").unwrap_or("disk") == "disk")
+        .filter_map(|disk| {
+            disk.children()
+                .find(|n| n.has_tag_name("target"))?
+                .attribute("dev")
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+/// `target/path` of a storage pool.
+fn pool_target_path(conn: &Libvirt, name: &str) -> ApiResult<String> {
+    let xml = lookup_pool(conn, name)?
+        .get_xml_desc(0)
+        .map_err(libvirt_error)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(internal_error)?;
+    doc.descendants()
+        .find(|n| n.has_tag_name("target"))
+        .and_then(|t| t.children().find(|n| n.has_tag_name("path")))
+        .and_then(|p| p.text())
+        .map(str::to_string)
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("pool {} has no target path", name),
+        ))
+}
+
+fn begin_backup(dom: &Domain, backup_xml: &str, checkpoint_xml: &str) -> Result<(), Error> {
+    let backup_xml = CString::new(backup_xml).unwrap_or_default();
+    let checkpoint_xml = CString::new(checkpoint_xml).unwrap_or_default();
+    let ret = unsafe {
+        sys::virDomainBackupBegin(
+            dom.as_ptr(),
+            backup_xml.as_ptr(),
+            checkpoint_xml.as_ptr(),
+            0,
+        )
+    };
+    if ret == -1 {
+        return Err(Error::last_error());
+    }
+    Ok(())
+}
+
+/// State of the backup job of a domain.
+enum JobPoll {
+    Running {
+        processed: Option<u64>,
+        total: Option<u64>,
+    },
+    Finished {
+        status: &'static str,
+        error: Option<String>,
+    },
+}
+
+fn poll_job(uri: &str, uuid: &str) -> ApiResult<JobPoll> {
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup_domain(&conn, uuid)?;
+    let stats = dom.get_job_stats(0).map_err(libvirt_error)?;
+    if stats.r#type as u32 != sys::VIR_DOMAIN_JOB_NONE {
+        return Ok(JobPoll::Running {
+            processed: stats.data_processed,
+            total: stats.data_total,
+        });
+    }
+    let done = dom
+        .get_job_stats(sys::VIR_DOMAIN_JOB_STATS_COMPLETED)
+        .map_err(libvirt_error)?;
+    let status = match done.r#type as u32 {
+        sys::VIR_DOMAIN_JOB_COMPLETED => "completed",
+        sys::VIR_DOMAIN_JOB_CANCELLED => "cancelled",
+        _ => "failed",
+    };
+    Ok(JobPoll::Finished {
+        status,
+        error: done.error_message,
+    })
+}
+
+/// Follow backup `id` of domain `uuid` until libvirt reports it finished,
+/// keeping the progress in the database up to date.
+pub fn watch(state: AppState, id: i64, uuid: String) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let uri = state.config.libvirt_uri.clone();
+            let domain = uuid.clone();
+            let poll = tokio::task::spawn_blocking(move || poll_job(&uri, &domain))
+                .await
+                .unwrap_or_else(|e| Err(internal_error(e)));
+            let (status, error) = match poll {
+                Ok(JobPoll::Running { processed, total }) => {
+                    if let Err(e) =
+                        backups::update_progress(&state.pool, id, processed, total).await
+                    {
+                        eprintln!("⚠️  Could not update backup {}: {}", id, e);
+                    }
+                    continue;
+                }
+                Ok(JobPoll::Finished { status, error }) => (status, error),
+                // The domain went away or stopped while the job ran
+                Err((_, message)) => ("failed", Some(message)),
+            };
+            if let Err(e) = backups::finish(&state.pool, id, status, error.as_deref()).await {
+                eprintln!("⚠️  Could not record the end of backup {}: {}", id, e);
+            }
+            println!("💾 Backup {} of {} {}", id, uuid, status);
+            if status == "completed" {
+                apply_retention(&state, &uuid).await;
+            }
+            break;
+        }
+    });
+}
+
+/// Resume watching backups that were running when the server stopped.
+pub async fn resume(state: &AppState) {
+    match backups::running(&state.pool).await {
+        Ok(running) => {
+            for backup in running {
+                watch(state.clone(), backup.id, backup.domain_uuid);
+            }
+        }
+        Err(e) => eprintln!("⚠️  Could not load running backups: {}", e),
+    }
+}
+
+/// Delete backup images. Images inside a storage pool are deleted through
+/// the storage-volume API, which also reaches a remote hypervisor; images
+/// in a plain `target_dir` outside every pool can only be removed when
+/// libvirt runs on this machine. Returns a message per image left behind.
+fn remove_images(conn: &Libvirt, local: bool, files: &[String]) -> Vec<String> {
+    let mut refreshed = false;
+    let mut failures = Vec::new();
+    for file in files {
+        let mut vol = StorageVol::lookup_by_path(conn, file);
+        if vol.is_err() && !refreshed {
+            // Pools only know images written since their last refresh
+            refreshed = true;
+            for pool in conn.list_all_storage_pools(0).unwrap_or_default() {
+                if pool.is_active().unwrap_or(false) {
+                    let _ = pool.refresh(0);
+                }
+            }
+            vol = StorageVol::lookup_by_path(conn, file);
+        }
+        let result = match vol {
+            Ok(vol) => vol.delete(0).map_err(|e| e.to_string()),
+            Err(_) if local => match std::fs::remove_file(file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            },
+            Err(_) => Err("not in a storage pool of the remote hypervisor".to_string()),
+        };
+        if let Err(e) = result {
+            failures.push(format!("Could not remove {}: {}", file, e));
+        }
+    }
+    failures
+}
+
+/// Delete backups (image files, checkpoint and history row) that fall
+/// outside the domain's retention policy.
+async fn apply_retention(state: &AppState, uuid: &str) {
+    let keep = match backups::retention(&state.pool, uuid).await {
+        Ok(Some(keep)) => keep,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("⚠️  Could not load the retention policy of {}: {}", uuid, e);
+            return;
+        }
+    };
+    let expired = match backups::expired(&state.pool, uuid, keep).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            eprintln!("⚠️  Could not list expired backups of {}: {}", uuid, e);
+            return;
+        }
+    };
+    for backup in expired {
+        let uri = state.config.libvirt_uri.clone();
+        let domain = backup.domain_uuid.clone();
+        let checkpoint = backup.checkpoint.clone();
+        let files: Vec<String> = backup.files.lines().map(str::to_string).collect();
+        let removed = tokio::task::spawn_blocking(move || -> ApiResult<Vec<String>> {
+            let conn = Libvirt::open(&uri)?;
+            let failures = remove_images(&conn, uri_host(&uri).is_none(), &files);
+            // The checkpoint may already be gone; that is fine
+            let _ = lookup_domain(&conn, &domain).and_then(|dom| {
+                Checkpoint::lookup(&dom, &checkpoint)
+                    .and_then(|c| c.delete())
+                    .map_err(libvirt_error)
+            });
+            Ok(failures)
+        })
+        .await;
+        match removed {
+            Ok(Ok(failures)) => {
+                for failure in failures {
+                    eprintln!("⚠️  {}", failure);
+                }
+            }
+            Ok(Err((_, e))) => {
+                eprintln!("⚠️  Could not expire backup {}: {}", backup.id, e);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("⚠️  Could not expire backup {}: {}", backup.id, e);
+                continue;
+            }
+        }
+        if let Err(e) = backups::delete(&state.pool, backup.id).await {
+            eprintln!("⚠️  Could not delete backup {}: {}", backup.id, e);
+            continue;
+        }
+        audit::record(
+            &state.pool,
+            RETENTION_ACTOR,
+            "backup.expire",
+            &format!("{} {} {}", backup.domain_name, backup.id, backup.checkpoint),
+        )
+        .await;
+    }
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/checkpoints
+// ---------------------------------------------------------------------
+pub async fn list_checkpoints(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<Vec<CheckpointInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    let mut out = Checkpoint::list(&dom)
+        .map_err(libvirt_error)?
+        .iter()
+        .map(checkpoint_info)
+        .collect::<ApiResult<Vec<_>>>()?;
+    out.sort_by_key(|c| c.created);
+    Ok(Json(out))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/checkpoints – checkpoint without a backup
+// ---------------------------------------------------------------------
+pub async fn create_checkpoint(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<CreateCheckpoint>,
+) -> ApiResult<(StatusCode, Json<CheckpointInfo>)> {
+    let xml = format!(
+        "<domaincheckpoint><name>{}</name><description>{}</description></domaincheckpoint>",
+        xml_escape(&req.name),
+        xml_escape(&req.description)
+    );
+    reject_nul(&xml, "checkpoint")?;
+    let info = {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        let dom = lookup_domain(&conn, &uuid)?;
+        checkpoint_info(&Checkpoint::create(&dom, &xml).map_err(libvirt_error)?)?
+    };
+    audit::record(
+        &state.pool,
+        &user.username,
+        "checkpoint.create",
+        &format!("{} {}", uuid, info.name),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/checkpoints/{name}
+// ---------------------------------------------------------------------
+pub async fn delete_checkpoint(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, name)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    reject_nul(&name, "checkpoint name")?;
+    {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        let dom = lookup_domain(&conn, &uuid)?;
+        Checkpoint::lookup(&dom, &name)
+            .and_then(|c| c.delete())
+            .map_err(libvirt_error)?;
+    }
+    audit::record(
+        &state.pool,
+        &user.username,
+        "checkpoint.delete",
+        &format!("{} {}", uuid, name),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/backups – start a push-mode backup
+// ---------------------------------------------------------------------
+pub async fn start_backup(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<StartBackup>,
+) -> ApiResult<(StatusCode, Json<Backup>)> {
+    let parent = if req.incremental {
+        let base = backups::latest_completed(&state.pool, &uuid)
+            .await
+            .map_err(internal_error)?
+            .ok_or((
+                StatusCode::CONFLICT,
+                "no completed backup to base an incremental backup on".to_string(),
+            ))?;
+        Some(base.checkpoint)
+    } else {
+        None
+    };
+
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    let target_dir = match (&req.target_dir, &req.pool) {
+        (Some(dir), _) => dir.clone(),
+        (None, Some(pool)) => pool_target_path(&conn, pool)?,
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "target_dir or pool is required".into(),
+            ));
+        }
+    };
+    if !target_dir.starts_with('/') {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "target_dir must be an absolute path".into(),
+        ));
+    }
+    let target_dir = target_dir.trim_end_matches('/').to_string();
+
+    let domain_name = dom.get_name().map_err(libvirt_error)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // The suffix keeps backups started within the same second apart
+    let checkpoint = format!("backup-{}-{:04x}", now, OsRng.next_u32() & 0xffff);
+    let disks = disk_targets(&dom)?;
+    let files: Vec<String> = disks
+        .iter()
+        .map(|disk| {
+            format!(
+                "{}/{}-{}-{}.qcow2",
+                target_dir, domain_name, checkpoint, disk
+            )
+        })
+        .collect();
+
+    let disk_xml: String = disks
+        .iter()
+        .zip(&files)
+        .map(|(disk, file)| {
+            format!(
+                "<disk name='{}' backup='yes' type='file'><target file='{}'/>\
+                 <driver type='qcow2'/></disk>",
+                xml_escape(disk),
+                xml_escape(file)
+            )
+        })
+        .collect();
+    let incremental = parent
+        .as_deref()
+        .map(|p| format!("<incremental>{}</incremental>", xml_escape(p)))
+        .unwrap_or_default();
+    let backup_xml = format!(
+        "<domainbackup mode='push'>{}<disks>{}</disks></domainbackup>",
+        incremental, disk_xml
+    );
+    let checkpoint_xml = format!(
+        "<domaincheckpoint><name>{}</name></domaincheckpoint>",
+        xml_escape(&checkpoint)
+    );
+    begin_backup(&dom, &backup_xml, &checkpoint_xml).map_err(libvirt_error)?;
+
+    let kind = if parent.is_some() {
+        "incremental"
+    } else {
+        "full"
+    };
+    let id = backups::insert(
+        &state.pool,
+        &NewBackup {
+            domain_uuid: &uuid,
+            domain_name: &domain_name,
+            kind,
+            checkpoint: &checkpoint,
+            parent_checkpoint: parent.as_deref(),
+            target_dir: &target_dir,
+            files: &files,
+            started_by: &user.username,
+        },
+    )
+    .await
+    .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "backup.start",
+        &format!("{} {} {} -> {}", domain_name, kind, checkpoint, target_dir),
+    )
+    .await;
+    watch(state.clone(), id, uuid);
+
+    let backup = backups::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "backup not found".to_string()))?;
+    Ok((StatusCode::CREATED, Json(backup)))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/backups – backup history, newest first
+// ---------------------------------------------------------------------
+pub async fn list_backups(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<Vec<Backup>>> {
+    let backups = backups::list_for_domain(&state.pool, &uuid)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(backups))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/backups/{id} – one backup job including its progress
+// ---------------------------------------------------------------------
+pub async fn get_backup(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Backup>> {
+    backups::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "backup not found".into()))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/backups/{id}/cancel – abort a running backup job
+// ---------------------------------------------------------------------
+pub async fn cancel_backup(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    let backup = backups::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "backup not found".to_string()))?;
+    if backup.status != "running" {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("backup is already {}", backup.status),
+        ));
+    }
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &backup.domain_uuid)?;
+    if unsafe { sys::virDomainAbortJob(dom.as_ptr()) } == -1 {
+        return Err(libvirt_error(Error::last_error()));
+    }
+    audit::record(
+        &state.pool,
+        &user.username,
+        "backup.cancel",
+        &format!("{} {}", backup.domain_name, id),
+    )
+    .await;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/backup-retention
+// PUT /api/domains/{uuid}/backup-retention – {"keep_full": 3}
+// ---------------------------------------------------------------------
+pub async fn get_retention(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<Retention>> {
+    let keep_full = backups::retention(&state.pool, &uuid)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(Retention { keep_full }))
+}
+
+pub async fn set_retention(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<Retention>,
+) -> ApiResult<Json<Retention>> {
+    if req.keep_full.is_some_and(|keep| keep < 1) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "keep_full must be at least 1".into(),
+        ));
+    }
+    backups::set_retention(&state.pool, &uuid, req.keep_full)
+        .await
+        .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "backup.retention",
+        &format!(
+            "{} {}",
+            uuid,
+            req.keep_full
+                .map(|k| k.to_string())
+                .unwrap_or_else(|| "unlimited".into())
+        ),
+    )
+    .await;
+    apply_retention(&state, &uuid).await;
+    Ok(Json(req))
+}
@@ -0,0 +1,298 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use virt::{storage_pool::StoragePool, storage_vol::StorageVol, sys};
+
+use super::domains::lookup;
+use super::pools::lookup as lookup_pool;
+use super::volumes::create_volume_with;
+use super::{ApiResult, DeviceScope, Libvirt, internal_error, libvirt_error, xml_escape};
+use crate::cloudinit::{CloudConfig, CloudUser, Seed, instance_id};
+use crate::{AppState, audit, auth::CurrentUser, ssh_keys};
+
+/// cloud-init settings of a NoCloud seed as entered in the UI.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SeedRequest {
+    pub hostname: Option<String>,
+    /// Stored keys of the requesting user
+    pub ssh_key_ids: Vec<i64>,
+    /// Further public keys, one `authorized_keys` line each
+    pub ssh_keys: Vec<String>,
+    pub users: Vec<CloudUser>,
+    pub packages: Vec<String>,
+    /// Update and upgrade all packages on first boot
+    pub package_upgrade: bool,
+    /// Complete user-data instead of the generated `#cloud-config`
+    pub user_data: Option<String>,
+    /// Network configuration (version 1 or 2) as YAML
+    pub network_config: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AttachSeed {
+    /// Pool the seed image is created in
+    pool: String,
+    #[serde(flatten)]
+    seed: SeedRequest,
+}
+
+#[derive(Serialize)]
+pub struct AttachedSeed {
+    pub volume: String,
+    pub path: String,
+    pub instance_id: String,
+    /// Target of the CD-ROM holding the seed
+    pub target: String,
+    /// The running domain picks the seed up on its next boot
+    pub next_boot: bool,
+}
+
+/// RFC 1123 host name, optionally fully qualified.
+fn valid_hostname(name: &str) -> bool {
+    name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// Login name as accepted by `useradd` in its default configuration.
+fn valid_user_name(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    name.len() <= 32
+        && bytes
+            .next()
+            .is_some_and(|b| b.is_ascii_lowercase() || b == b'_')
+        && bytes.all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-')
+}
+
+fn public_key(line: &str) -> ApiResult<String> {
+    ssh_keys::normalize(line).ok_or((
+        StatusCode::BAD_REQUEST,
+        format!("not an OpenSSH public key: {}", line),
+    ))
+}
+
+/// Turn the form into a seed for domain `name`, resolving the user's
+/// stored SSH keys.
+pub(crate) async fn build_seed(
+    pool: &SqlitePool,
+    user: &CurrentUser,
+    name: &str,
+    req: SeedRequest,
+) -> ApiResult<Seed> {
+    if req.hostname.as_deref().is_some_and(|h| !valid_hostname(h)) {
+        return Err((StatusCode::BAD_REQUEST, "invalid hostname".into()));
+    }
+    let mut keys = Vec::new();
+    for id in &req.ssh_key_ids {
+        let key = ssh_keys::get(pool, user.id, *id)
+            .await
+            .map_err(internal_error)?
+            .ok_or((StatusCode::BAD_REQUEST, format!("unknown SSH key {}", id)))?;
+        keys.push(key.public_key);
+    }
+    for line in &req.ssh_keys {
+        keys.push(public_key(line)?);
+    }
+    let mut users = req.users;
+    for user in &mut users {
+        if !valid_user_name(&user.name) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("invalid user name: {}", user.name),
+            ));
+        }
+        user.ssh_authorized_keys = user
+            .ssh_authorized_keys
+            .iter()
+            .map(|line| public_key(line))
+            .collect::<ApiResult<_>>()?;
+    }
+    let packages: Vec<String> = req
+        .packages
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if packages.iter().any(|p| p.contains(char::is_whitespace)) {
+        return Err((StatusCode::BAD_REQUEST, "invalid package name".into()));
+    }
+
+    let config = CloudConfig {
+        ssh_authorized_keys: keys,
+        users,
+        packages,
+        package_upgrade: req.package_upgrade,
+    };
+    let user_data = match req.user_data.filter(|data| !data.trim().is_empty()) {
+        Some(data) => {
+            if !config.ssh_authorized_keys.is_empty()
+                || !config.users.is_empty()
+                || !config.packages.is_empty()
+                || config.package_upgrade
+            {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "custom user_data cannot be combined with SSH keys, users or packages".into(),
+                ));
+            }
+            data
+        }
+        None => config.to_user_data(),
+    };
+    Ok(Seed {
+        instance_id: instance_id(name),
+        hostname: req.hostname,
+        user_data: Some(user_data),
+        network_config: req.network_config.filter(|c| !c.trim().is_empty()),
+    })
+}
+
+/// Write the seed of domain `name` to `{name}-cidata.iso` in `pool`,
+/// replacing an earlier one.
+pub(crate) fn seed_volume(
+    conn: &Libvirt,
+    pool: &StoragePool,
+    name: &str,
+    seed: &Seed,
+) -> ApiResult<StorageVol> {
+    let volume = format!("{}-cidata.iso", name);
+    if let Ok(old) = StorageVol::lookup_by_name(pool, &volume) {
+        old.delete(0).map_err(libvirt_error)?;
+    }
+    create_volume_with(conn, pool, &volume, &seed.to_iso())
+}
+
+/// Read-only CD-ROM holding the image at `path`.
+pub(crate) fn seed_disk_xml(path: &str, target: &str, bus: &str) -> String {
+    format!(
+        "<disk type='file' device='cdrom'><driver name='qemu' type='raw'/>\
+         <source file='{}'/><target dev='{}' bus='{}'/><readonly/></disk>",
+        xml_escape(path),
+        xml_escape(target),
+        xml_escape(bus)
+    )
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// First `sdX` target not used by any disk, for the seed CD-ROM.
+pub(crate) fn free_sata_target(doc: &roxmltree::Document<'_>) -> ApiResult<String> {
+    let used: Vec<&str> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("target"))
+        .filter(|n| n.parent().is_some_and(|p| p.has_tag_name("disk")))
+        .filter_map(|n| n.attribute("dev"))
+        .collect();
+    (b'a'..=b'z')
+        .map(|c| format!("sd{}", c as char))
+        .find(|dev| !used.contains(&dev.as_str()))
+        .ok_or((
+            StatusCode::CONFLICT,
+            "no free disk target for the cloud-init seed".to_string(),
+        ))
+}
+
+/// Create the seed image and make sure the persistent definition has it in
+/// a CD-ROM: the one already pointing at it, an empty one, or a new one.
+fn attach_seed(uri: &str, uuid: &str, pool: &str, seed: &Seed) -> ApiResult<AttachedSeed> {
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup(&conn, uuid)?;
+    let name = dom.get_name().map_err(libvirt_error)?;
+    let pool = lookup_pool(&conn, pool)?;
+    let vol = seed_volume(&conn, &pool, &name, seed)?;
+    let path = vol.get_path().map_err(libvirt_error)?;
+
+    let xml = dom
+        .get_xml_desc(sys::VIR_DOMAIN_XML_INACTIVE)
+        .map_err(libvirt_error)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(internal_error)?;
+    let cdroms: Vec<_> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("disk") && n.attribute("device") == Some("cdrom"))
+        .collect();
+    let target_of = |disk: roxmltree::Node<'_, '_>| {
+        child(disk, "target")
+            .and_then(|t| t.attribute("dev"))
+            .unwrap_or_default()
+            .to_string()
+    };
+    let flags = DeviceScope::Config.flags();
+    let current = cdroms.iter().copied().find(|disk| {
+        child(*disk, "source").and_then(|s| s.attribute("file")) == Some(path.as_str())
+    });
+    let empty = cdroms
+        .iter()
+        .copied()
+        .find(|disk| child(*disk, "source").is_none());
+    let target = match (current, empty) {
+        (Some(disk), _) => target_of(disk),
+        (None, Some(disk)) => {
+            // Insert the seed into the empty drive, keeping its bus
+            let target = target_of(disk);
+            let bus = child(disk, "target")
+                .and_then(|t| t.attribute("bus"))
+                .unwrap_or("sata");
+            dom.update_device_flags(&seed_disk_xml(&path, &target, bus), flags)
+                .map_err(libvirt_error)?;
+            target
+        }
+        (None, None) => {
+            let target = free_sata_target(&doc)?;
+            dom.attach_device_flags(&seed_disk_xml(&path, &target, "sata"), flags)
+                .map_err(libvirt_error)?;
+            target
+        }
+    };
+    Ok(AttachedSeed {
+        volume: vol.get_name().map_err(libvirt_error)?,
+        path,
+        instance_id: seed.instance_id.clone(),
+        target,
+        next_boot: dom.is_active().map_err(libvirt_error)?,
+    })
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/cloud-init – build a NoCloud seed and attach it
+// ---------------------------------------------------------------------
+pub async fn attach_cloud_init(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<AttachSeed>,
+) -> ApiResult<Json<AttachedSeed>> {
+    let name = {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        lookup(&conn, &uuid)?.get_name().map_err(libvirt_error)?
+    };
+    let seed = build_seed(&state.pool, &user, &name, req.seed).await?;
+    let uri = state.config.libvirt_uri.clone();
+    let attached = tokio::task::spawn_blocking(move || attach_seed(&uri, &uuid, &req.pool, &seed))
+        .await
+        .map_err(internal_error)??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.cloud_init",
+        &format!("{} ({})", name, attached.instance_id),
+    )
+    .await;
+    Ok(Json(attached))
+}
@@ -0,0 +1,518 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use virt::{error::Error, stream::Stream, sys};
+
+use super::domains::lookup;
+use super::{ApiResult, Libvirt, libvirt_error, reject_nul};
+use crate::console::{SerialEvent, SerialViewer, SerialWorker, TICKET_TTL, Target, Ticket};
+use crate::{AppState, audit, auth::CurrentUser};
+
+/// A console ticket as handed to the browser.
+#[derive(Serialize)]
+pub struct ConsoleTicket {
+    pub token: String,
+    /// `vnc` or `serial`
+    pub kind: &'static str,
+    /// Seconds the token stays valid
+    pub expires_in: u64,
+    /// WebSocket path to open with the token
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct TicketQuery {
+    token: String,
+}
+
+/// Options of a serial console session.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SerialRequest {
+    /// Console alias such as `serial0` – the domain's first console when absent
+    pub device: Option<String>,
+    /// Only watch the output; the only mode open to non-admins
+    pub read_only: bool,
+    /// Take typing over from another viewer, and the console over from
+    /// clients outside this application (e.g. `virsh console`)
+    pub force: bool,
+    /// Save the session output to the audit log when it ends
+    pub transcript: bool,
+}
+
+/// Wait between polls while a serial console is quiet.
+const SERIAL_POLL: Duration = Duration::from_millis(20);
+
+/// Output kept for the audit log transcript of one serial session.
+const TRANSCRIPT_LIMIT: usize = 64 * 1024;
+
+/// Host name of a libvirt URI such as `qemu+ssh://root@kvm1/system`.
+pub(crate) fn uri_host(uri: &str) -> Option<&str> {
+    let rest = uri.split_once("://")?.1;
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        // [v6]:port
+        Some(v6) => v6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// Address of the first VNC display in live domain XML. SPICE displays
+/// are not supported – the dashboard only bundles a VNC client.
+/// Wildcard listen addresses are reached through `hypervisor_host`.
+pub fn parse_graphics(xml: &str, hypervisor_host: Option<&str>) -> Result<Target, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+    let displays: Vec<_> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("graphics"))
+        .collect();
+    let graphics = displays
+        .iter()
+        .find(|n| n.attribute("type") == Some("vnc"))
+        .ok_or_else(|| {
+            if displays
+                .iter()
+                .any(|n| n.attribute("type") == Some("spice"))
+            {
+                "only VNC consoles are supported; the domain has a SPICE display"
+            } else {
+                "the domain has no VNC display"
+            }
+        })?;
+    let listen = graphics.children().find(|n| n.has_tag_name("listen"));
+    if let Some(socket) = listen
+        .filter(|l| l.attribute("type") == Some("socket"))
+        .and_then(|l| l.attribute("socket"))
+    {
+        return Ok(Target::Unix(socket.into()));
+    }
+    // Autoport displays only have a port while the domain runs
+    let port = graphics
+        .attribute("port")
+        .and_then(|p| p.parse::<u16>().ok())
+        .filter(|p| *p > 0)
+        .ok_or("the display has no port – is the domain running?")?;
+    let address = listen
+        .and_then(|l| l.attribute("address"))
+        .or(graphics.attribute("listen"))
+        .unwrap_or("127.0.0.1");
+    let host = match address {
+        "" | "0.0.0.0" | "::" => hypervisor_host.unwrap_or("127.0.0.1"),
+        address => address,
+    };
+    Ok(Target::Tcp {
+        host: host.to_string(),
+        port,
+    })
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/console – issue a single-use VNC console
+// ticket (SPICE displays are refused with 409); administrators only
+// ---------------------------------------------------------------------
+pub async fn create_console_ticket(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<ConsoleTicket>> {
+    // A graphical console has no read-only mode, so it gets the same rule
+    // as an interactive serial console
+    user.require_admin()?;
+    let uri = &state.config.libvirt_uri;
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    if !dom.is_active().map_err(libvirt_error)? {
+        return Err((StatusCode::CONFLICT, "the domain is not running".into()));
+    }
+    let xml = dom.get_xml_desc(0).map_err(libvirt_error)?;
+    let target = parse_graphics(&xml, uri_host(uri)).map_err(|e| (StatusCode::CONFLICT, e))?;
+    let name = dom.get_name().map_err(libvirt_error)?;
+    let token = state.tickets.issue(Ticket::new(
+        user.id,
+        user.username.clone(),
+        dom.get_uuid_string().map_err(libvirt_error)?,
+        name.clone(),
+        "vnc",
+        target,
+    ));
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.console",
+        &format!("{} (vnc)", name),
+    )
+    .await;
+    Ok(Json(ConsoleTicket {
+        path: format!("/api/console/ws?token={}", token),
+        token,
+        kind: "vnc",
+        expires_in: TICKET_TTL.as_secs(),
+    }))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/serial – issue a single-use serial console
+// ticket; typing into the console requires administrator rights
+// ---------------------------------------------------------------------
+pub async fn create_serial_ticket(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<SerialRequest>,
+) -> ApiResult<Json<ConsoleTicket>> {
+    // A serial console is usually a root login prompt
+    if !req.read_only {
+        user.require_admin()?;
+    }
+    if let Some(device) = &req.device {
+        reject_nul(device, "console device")?;
+    }
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    if !dom.is_active().map_err(libvirt_error)? {
+        return Err((StatusCode::CONFLICT, "the domain is not running".into()));
+    }
+    let name = dom.get_name().map_err(libvirt_error)?;
+    let token = state.tickets.issue(Ticket::new(
+        user.id,
+        user.username.clone(),
+        dom.get_uuid_string().map_err(libvirt_error)?,
+        name.clone(),
+        "serial",
+        Target::Serial {
+            device: req.device.clone(),
+            read_only: req.read_only,
+            // A viewer must not kick out the person typing
+            force: req.force && !req.read_only,
+            transcript: req.transcript,
+        },
+    ));
+    let mode = if req.read_only {
+        "read-only"
+    } else {
+        "interactive"
+    };
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.serial",
+        &format!(
+            "{} {} ({})",
+            name,
+            req.device.as_deref().unwrap_or("console"),
+            mode
+        ),
+    )
+    .await;
+    Ok(Json(ConsoleTicket {
+        path: format!("/api/console/ws?token={}", token),
+        token,
+        kind: "serial",
+        expires_in: TICKET_TTL.as_secs(),
+    }))
+}
+
+/// Write all of `data` to a non-blocking stream.
+fn send_all(stream: &Stream, mut data: &[u8]) -> ApiResult<()> {
+    while !data.is_empty() {
+        // SAFETY: `data` is valid for `data.len()` bytes; libvirt only reads it
+        let sent = unsafe {
+            sys::virStreamSend(
+                stream.as_ptr(),
+                data.as_ptr() as *mut libc::c_char,
+                data.len(),
+            )
+        };
+        match sent {
+            // The stream buffer is full
+            -2 => std::thread::sleep(SERIAL_POLL),
+            sent if sent < 0 => return Err(libvirt_error(Error::last_error())),
+            sent => data = &data[sent as usize..],
+        }
+    }
+    Ok(())
+}
+
+/// Open the domain's console and move bytes between it and the channels
+/// until the console closes or the last viewer goes away.
+///
+/// One thread both reads and writes, so the stream is non-blocking and
+/// polled rather than parked in `virStreamRecv`.
+fn pump_serial(
+    uri: &str,
+    uuid: &str,
+    device: Option<&str>,
+    flags: u32,
+    mut input: mpsc::Receiver<Bytes>,
+    output: broadcast::Sender<SerialEvent>,
+) -> ApiResult<()> {
+    let conn = Libvirt::open_long_lived(uri)?;
+    let dom = lookup(&conn, uuid)?;
+    let stream = Stream::new(&conn, sys::VIR_STREAM_NONBLOCK).map_err(libvirt_error)?;
+    dom.open_console(device, &stream, flags)
+        .map_err(libvirt_error)?;
+    let mut buf = vec![0u8; 4096];
+    let result = loop {
+        let mut idle = true;
+        // SAFETY: `buf` is valid for writes of `buf.len()` bytes
+        let received = unsafe {
+            sys::virStreamRecv(
+                stream.as_ptr(),
+                buf.as_mut_ptr() as *mut libc::c_char,
+                buf.len(),
+            )
+        };
+        match received {
+            // Nothing to read right now
+            -2 => {}
+            // The console was closed, e.g. the domain stopped
+            0 => break Ok(()),
+            received if received < 0 => break Err(libvirt_error(Error::last_error())),
+            received => {
+                idle = false;
+                let data = Bytes::copy_from_slice(&buf[..received as usize]);
+                // Nobody may be subscribed for a moment between viewers
+                let _ = output.send(SerialEvent::Output(data));
+            }
+        }
+        match input.try_recv() {
+            Ok(data) => {
+                idle = false;
+                if let Err(e) = send_all(&stream, &data) {
+                    break Err(e);
+                }
+            }
+            Err(TryRecvError::Empty) => {}
+            // The last viewer left
+            Err(TryRecvError::Disconnected) => break Ok(()),
+        }
+        if idle {
+            std::thread::sleep(SERIAL_POLL);
+        }
+    };
+    let _ = stream.abort();
+    result
+}
+
+/// Bridge a browser terminal to the serial console named by `ticket`.
+/// Every viewer of a console shares one libvirt stream; console output
+/// travels as binary frames, status messages as text frames.
+async fn serial_bridge(socket: WebSocket, state: AppState, ticket: Ticket) {
+    let Target::Serial {
+        device,
+        read_only,
+        force,
+        transcript,
+    } = ticket.target
+    else {
+        return;
+    };
+    let mut flags = sys::VIR_DOMAIN_CONSOLE_SAFE;
+    if force {
+        flags |= sys::VIR_DOMAIN_CONSOLE_FORCE;
+    }
+    let uri = state.config.libvirt_uri.clone();
+    let uuid = ticket.domain_uuid.clone();
+    let SerialViewer {
+        id,
+        session,
+        mut events,
+    } = state
+        .serial
+        .join(&ticket.domain_uuid, device.as_deref(), |worker| {
+            let device = device.clone();
+            tokio::task::spawn_blocking(move || {
+                let SerialWorker {
+                    session,
+                    output,
+                    input,
+                } = worker;
+                let result = pump_serial(&uri, &uuid, device.as_deref(), flags, input, output);
+                if let Some(session) = session.upgrade() {
+                    session.close(result.err().map(|(_, message)| message));
+                }
+            });
+        });
+
+    let (mut to_browser, mut from_browser) = socket.split();
+    // Only one viewer types at a time; the others watch
+    let writer = !read_only && session.claim_writer(id, force);
+    if !read_only && !writer {
+        let notice = "someone else is typing into this console – connected read-only";
+        let _ = to_browser.send(Message::Text(notice.into())).await;
+    }
+    let mut kept = Vec::new();
+    let mut taken_over = false;
+    let mut closed = None;
+    let upstream = async {
+        while let Some(Ok(message)) = from_browser.next().await {
+            let data = match message {
+                Message::Binary(data) => data,
+                Message::Text(text) => Bytes::copy_from_slice(text.as_str().as_bytes()),
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            if !writer {
+                continue;
+            }
+            if !session.is_writer(id) {
+                taken_over = true;
+                break;
+            }
+            if session.input.send(data).await.is_err() {
+                break;
+            }
+        }
+    };
+    let downstream = async {
+        loop {
+            match events.recv().await {
+                Ok(SerialEvent::Output(data)) => {
+                    if transcript {
+                        let room = TRANSCRIPT_LIMIT.saturating_sub(kept.len());
+                        kept.extend_from_slice(&data[..data.len().min(room)]);
+                    }
+                    if to_browser.send(Message::Binary(data)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(SerialEvent::Closed(error)) => {
+                    closed = error;
+                    break;
+                }
+                // This viewer fell behind; it misses some output
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+    tokio::select! {
+        _ = upstream => {},
+        _ = downstream => {},
+    }
+    // The console closes with the last viewer's handle
+    session.release(id);
+    drop(session);
+    if taken_over {
+        let notice = "another session took over the console";
+        let _ = to_browser.send(Message::Text(notice.into())).await;
+    }
+    if let Some(message) = closed {
+        let _ = to_browser.send(Message::Text(message.into())).await;
+    }
+    let _ = to_browser.close().await;
+    println!(
+        "🖥️  {} closed the serial console of {}",
+        ticket.username, ticket.domain_name
+    );
+    if transcript {
+        let mut detail = format!(
+            "{} ({}):\n{}",
+            ticket.domain_name,
+            ticket.domain_uuid,
+            String::from_utf8_lossy(&kept)
+        );
+        if kept.len() >= TRANSCRIPT_LIMIT {
+            detail.push_str("\n[transcript truncated]");
+        }
+        audit::record(
+            &state.pool,
+            &ticket.username,
+            "domain.serial_transcript",
+            &detail,
+        )
+        .await;
+    }
+}
+
+/// Copy bytes between the browser and the display until either side closes.
+async fn bridge<S: AsyncRead + AsyncWrite>(socket: WebSocket, stream: S) {
+    let (mut to_browser, mut from_browser) = socket.split();
+    let (mut from_display, mut to_display) = tokio::io::split(stream);
+    let upstream = async {
+        while let Some(Ok(message)) = from_browser.next().await {
+            match message {
+                Message::Binary(data) if to_display.write_all(&data).await.is_err() => break,
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    };
+    let downstream = async {
+        let mut buf = vec![0u8; 16 * 1024];
+        loop {
+            match from_display.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let data = Bytes::copy_from_slice(&buf[..n]);
+                    if to_browser.send(Message::Binary(data)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        let _ = to_browser.close().await;
+    };
+    tokio::select! {
+        _ = upstream => {},
+        _ = downstream => {},
+    }
+}
+
+// ---------------------------------------------------------------------
+// GET /api/console/ws?token=… – WebSocket bridged to the display socket
+// or serial console. The token must have been issued to the same user.
+// ---------------------------------------------------------------------
+pub async fn console_socket(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Query(query): Query<TicketQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let ticket = match state.tickets.take(&query.token) {
+        Some(ticket) if ticket.user_id == user.id => ticket,
+        _ => {
+            return (StatusCode::FORBIDDEN, "invalid or expired console token").into_response();
+        }
+    };
+    let unavailable = |e: std::io::Error| {
+        (
+            StatusCode::BAD_GATEWAY,
+            format!("cannot reach the console: {}", e),
+        )
+            .into_response()
+    };
+    // noVNC asks for the `binary` subprotocol
+    let ws = ws.protocols(["binary"]);
+    println!(
+        "🖥️  {} opened the {} console of {} ({})",
+        ticket.username, ticket.kind, ticket.domain_name, ticket.domain_uuid
+    );
+    match ticket.target {
+        Target::Tcp { ref host, port } => match TcpStream::connect((host.as_str(), port)).await {
+            Ok(stream) => ws.on_upgrade(move |socket| bridge(socket, stream)),
+            Err(e) => unavailable(e),
+        },
+        Target::Unix(ref path) => match UnixStream::connect(path).await {
+            Ok(stream) => ws.on_upgrade(move |socket| bridge(socket, stream)),
+            Err(e) => unavailable(e),
+        },
+        Target::Serial { .. } => ws.on_upgrade(move |socket| serial_bridge(socket, state, ticket)),
+    }
+}
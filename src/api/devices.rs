@@ -0,0 +1,721 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use virt::{domain::Domain, storage_vol::StorageVol, sys};
+
+use super::domains::lookup;
+use super::pools::lookup as lookup_pool;
+use super::volumes::volume_info;
+use super::{
+    ApiResult, DeviceScope, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape,
+};
+use crate::{AppState, audit, auth::CurrentUser};
+
+/// A `<disk>` of a domain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskDevice {
+    pub target: String,
+    pub bus: String,
+    /// `disk`, `cdrom` or `floppy`
+    pub device: String,
+    /// File, block device or `pool/volume`; `None` for an empty drive
+    pub source: Option<String>,
+    pub format: Option<String>,
+    pub readonly: bool,
+}
+
+/// An `<interface>` of a domain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NicDevice {
+    pub mac: String,
+    /// `network`, `bridge`, `direct`, …
+    pub kind: String,
+    pub source: Option<String>,
+    pub model: Option<String>,
+}
+
+/// The hot-pluggable parts of a domain definition.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeviceList {
+    pub vcpus: u32,
+    pub max_vcpus: u32,
+    /// Balloon target
+    pub memory_kib: u64,
+    pub max_memory_kib: u64,
+    pub disks: Vec<DiskDevice>,
+    pub interfaces: Vec<NicDevice>,
+}
+
+#[derive(Deserialize)]
+pub struct ScopeQuery {
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+#[derive(Deserialize)]
+pub struct EjectQuery {
+    #[serde(default)]
+    scope: DeviceScope,
+    /// Eject even when the guest has locked the tray
+    #[serde(default)]
+    force: bool,
+}
+
+/// Image for a disk or CD-ROM: a pool volume or a path on the host.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct DiskSource {
+    pool: Option<String>,
+    volume: Option<String>,
+    path: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AttachDisk {
+    #[serde(flatten)]
+    source: DiskSource,
+    /// `disk` or `cdrom`
+    #[serde(default = "default_device")]
+    device: String,
+    #[serde(default = "default_bus")]
+    bus: String,
+    /// Picked from the free names of the bus when absent
+    #[serde(default)]
+    target: Option<String>,
+    /// Image format; the volume's own, otherwise raw
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    readonly: bool,
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+#[derive(Deserialize)]
+pub struct ChangeMedia {
+    #[serde(flatten)]
+    source: DiskSource,
+    #[serde(default)]
+    scope: DeviceScope,
+    /// Change media even when the guest has locked the tray
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct AttachNic {
+    /// libvirt network to connect to
+    #[serde(default)]
+    network: Option<String>,
+    /// Host bridge to connect to instead
+    #[serde(default)]
+    bridge: Option<String>,
+    #[serde(default = "default_model")]
+    model: String,
+    /// Generated when absent
+    #[serde(default)]
+    mac: Option<String>,
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+#[derive(Deserialize)]
+pub struct SetVcpus {
+    count: u32,
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+#[derive(Deserialize)]
+pub struct SetMemory {
+    memory_mib: u64,
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+fn default_device() -> String {
+    "disk".into()
+}
+
+fn default_bus() -> String {
+    "virtio".into()
+}
+
+fn default_model() -> String {
+    "virtio".into()
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// Disks, interfaces, vCPUs and memory of a domain definition.
+pub fn parse_devices(xml: &str) -> Result<DeviceList, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let number = |name: &str| {
+        child(root, name)
+            .and_then(|n| n.text())
+            .and_then(|t| t.trim().parse::<u64>().ok())
+            .unwrap_or_default()
+    };
+    let max_vcpus = number("vcpu") as u32;
+    let vcpus = child(root, "vcpu")
+        .and_then(|n| n.attribute("current"))
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(max_vcpus);
+    let mut list = DeviceList {
+        vcpus,
+        max_vcpus,
+        memory_kib: number("currentMemory"),
+        max_memory_kib: number("memory"),
+        ..DeviceList::default()
+    };
+    let Some(devices) = child(root, "devices") else {
+        return Ok(list);
+    };
+    for disk in devices.children().filter(|n| n.has_tag_name("disk")) {
+        let target = child(disk, "target");
+        let source = child(disk, "source").and_then(|s| {
+            match (s.attribute("pool"), s.attribute("volume")) {
+                (Some(pool), Some(volume)) => Some(format!("{}/{}", pool, volume)),
+                _ => s
+                    .attribute("file")
+                    .or(s.attribute("dev"))
+                    .or(s.attribute("name"))
+                    .map(str::to_string),
+            }
+        });
+        list.disks.push(DiskDevice {
+            target: target
+                .and_then(|t| t.attribute("dev"))
+                .unwrap_or_default()
+                .to_string(),
+            bus: target
+                .and_then(|t| t.attribute("bus"))
+                .unwrap_or_default()
+                .to_string(),
+            device: disk.attribute("device").unwrap_or("disk").to_string(),
+            source,
+            format: child(disk, "driver")
+                .and_then(|d| d.attribute("type"))
+                .map(str::to_string),
+            readonly: child(disk, "readonly").is_some(),
+        });
+    }
+    for iface in devices.children().filter(|n| n.has_tag_name("interface")) {
+        let source = child(iface, "source").and_then(|s| {
+            s.attribute("network")
+                .or(s.attribute("bridge"))
+                .or(s.attribute("dev"))
+                .map(str::to_string)
+        });
+        list.interfaces.push(NicDevice {
+            mac: child(iface, "mac")
+                .and_then(|m| m.attribute("address"))
+                .unwrap_or_default()
+                .to_string(),
+            kind: iface.attribute("type").unwrap_or_default().to_string(),
+            source,
+            model: child(iface, "model")
+                .and_then(|m| m.attribute("type"))
+                .map(str::to_string),
+        });
+    }
+    Ok(list)
+}
+
+/// The definition a change in `scope` is based on: the saved one unless
+/// only the running domain is changed.
+fn scope_xml(dom: &Domain, scope: DeviceScope) -> ApiResult<String> {
+    let inactive = match scope {
+        DeviceScope::Live => false,
+        DeviceScope::Current => !dom.is_active().map_err(libvirt_error)?,
+        DeviceScope::Config | DeviceScope::Both => true,
+    };
+    let flags = if inactive {
+        sys::VIR_DOMAIN_XML_INACTIVE
+    } else {
+        0
+    };
+    dom.get_xml_desc(flags).map_err(libvirt_error)
+}
+
+/// Source text of the `<disk>` with target `target`.
+fn disk_element(xml: &str, target: &str) -> ApiResult<String> {
+    let doc = roxmltree::Document::parse(xml).map_err(internal_error)?;
+    doc.descendants()
+        .filter(|n| n.has_tag_name("disk"))
+        .find(|n| child(*n, "target").and_then(|t| t.attribute("dev")) == Some(target))
+        .map(|n| xml[n.range()].to_string())
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("domain has no disk {}", target),
+        ))
+}
+
+/// `type` attribute, `<source>` element and default format of an image.
+fn source_xml(conn: &Libvirt, source: &DiskSource) -> ApiResult<(&'static str, String, String)> {
+    for (value, what) in [
+        (&source.pool, "pool"),
+        (&source.volume, "volume"),
+        (&source.path, "path"),
+    ] {
+        reject_nul(value.as_deref().unwrap_or_default(), what)?;
+    }
+    match (&source.pool, &source.volume, &source.path) {
+        (Some(pool), Some(volume), None) => {
+            let vol = StorageVol::lookup_by_name(&lookup_pool(conn, pool)?, volume)
+                .map_err(libvirt_error)?;
+            let format = volume_info(&vol)?.format.unwrap_or_else(|| "raw".into());
+            Ok((
+                "volume",
+                format!(
+                    "<source pool='{}' volume='{}'/>",
+                    xml_escape(pool),
+                    xml_escape(volume)
+                ),
+                format,
+            ))
+        }
+        (None, None, Some(path)) if path.starts_with("/dev/") => Ok((
+            "block",
+            format!("<source dev='{}'/>", xml_escape(path)),
+            "raw".into(),
+        )),
+        (None, None, Some(path)) if path.starts_with('/') => Ok((
+            "file",
+            format!("<source file='{}'/>", xml_escape(path)),
+            "raw".into(),
+        )),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            "give either pool and volume or an absolute path".into(),
+        )),
+    }
+}
+
+fn has_source(source: &DiskSource) -> bool {
+    source.pool.is_some() || source.volume.is_some() || source.path.is_some()
+}
+
+/// First free target name of a bus, e.g. `vdb`.
+fn free_target(xml: &str, bus: &str) -> ApiResult<String> {
+    let prefix = match bus {
+        "virtio" => "vd",
+        "ide" => "hd",
+        "fdc" => "fd",
+        "scsi" | "sata" | "usb" => "sd",
+        _ => {
+            return Err((StatusCode::BAD_REQUEST, format!("unsupported bus {}", bus)));
+        }
+    };
+    let used = parse_devices(xml).map_err(internal_error)?.disks;
+    (b'a'..=b'z')
+        .map(|c| format!("{}{}", prefix, c as char))
+        .find(|dev| !used.iter().any(|d| d.target == *dev))
+        .ok_or((
+            StatusCode::CONFLICT,
+            format!("no free target on bus {}", bus),
+        ))
+}
+
+/// Random MAC address in the range libvirt uses for QEMU guests.
+fn random_mac() -> String {
+    let mut bytes = [0u8; 3];
+    OsRng.fill_bytes(&mut bytes);
+    format!(
+        "52:54:00:{:02x}:{:02x}:{:02x}",
+        bytes[0], bytes[1], bytes[2]
+    )
+}
+
+fn valid_mac(mac: &str) -> bool {
+    let parts: Vec<&str> = mac.split(':').collect();
+    parts.len() == 6
+        && parts
+            .iter()
+            .all(|p| p.len() == 2 && p.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/devices?scope=config – disks, NICs, vCPUs, memory
+// ---------------------------------------------------------------------
+pub async fn get_devices(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Query(req): Query<ScopeQuery>,
+) -> ApiResult<Json<DeviceList>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let xml = scope_xml(&dom, req.scope)?;
+    Ok(Json(parse_devices(&xml).map_err(internal_error)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/disks – attach a disk or CD-ROM drive
+// ---------------------------------------------------------------------
+pub async fn attach_disk(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<AttachDisk>,
+) -> ApiResult<(StatusCode, Json<DiskDevice>)> {
+    reject_nul(&req.bus, "bus")?;
+    reject_nul(req.target.as_deref().unwrap_or_default(), "target")?;
+    reject_nul(req.format.as_deref().unwrap_or_default(), "format")?;
+    let cdrom = match req.device.as_str() {
+        "disk" => false,
+        "cdrom" => true,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "device must be disk or cdrom".into(),
+            ));
+        }
+    };
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let target = match req.target {
+        Some(target) => target,
+        None => free_target(&scope_xml(&dom, req.scope)?, &req.bus)?,
+    };
+    // A CD-ROM drive may start out empty
+    let (kind, source, format) = if cdrom && !has_source(&req.source) {
+        ("file", String::new(), "raw".to_string())
+    } else {
+        source_xml(&conn, &req.source)?
+    };
+    let format = req.format.unwrap_or(format);
+    let readonly = cdrom || req.readonly;
+    let xml = format!(
+        "<disk type='{}' device='{}'><driver name='qemu' type='{}'/>{}\
+         <target dev='{}' bus='{}'/>{}</disk>",
+        kind,
+        req.device,
+        xml_escape(&format),
+        source,
+        xml_escape(&target),
+        xml_escape(&req.bus),
+        if readonly { "<readonly/>" } else { "" }
+    );
+    dom.attach_device_flags(&xml, req.scope.flags())
+        .map_err(libvirt_error)?;
+    let attached = parse_devices(&scope_xml(&dom, req.scope)?)
+        .map_err(internal_error)?
+        .disks
+        .into_iter()
+        .find(|d| d.target == target)
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "attached disk not found".to_string(),
+        ))?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.disk_attach",
+        &format!(
+            "{} {} {} {}",
+            uuid,
+            target,
+            attached.source.as_deref().unwrap_or("empty"),
+            req.scope.name()
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(attached)))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/disks/{target}?scope=both
+// ---------------------------------------------------------------------
+pub async fn detach_disk(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, target)): Path<(String, String)>,
+    Query(req): Query<ScopeQuery>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let xml = disk_element(&scope_xml(&dom, req.scope)?, &target)?;
+    dom.detach_device_flags(&xml, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.disk_detach",
+        &format!("{} {} {}", uuid, target, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The removable `<disk>` with target `target`, its `<source>` replaced by
+/// the one in `media` (with the disk type it needs) or removed for `None`.
+fn disk_with_media(
+    domain_xml: &str,
+    target: &str,
+    media: Option<(&str, &str)>,
+) -> ApiResult<String> {
+    let element = disk_element(domain_xml, target)?;
+    let doc = roxmltree::Document::parse(&element).map_err(internal_error)?;
+    let disk = doc.root_element();
+    if !matches!(disk.attribute("device"), Some("cdrom" | "floppy")) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{} is not a CD-ROM or floppy drive", target),
+        ));
+    }
+    let mut xml = element.clone();
+    match (child(disk, "source"), media) {
+        (Some(old), Some((_, source))) => xml.replace_range(old.range(), source),
+        (Some(old), None) => xml.replace_range(old.range(), ""),
+        (None, Some((_, source))) => {
+            let end = xml.rfind("</disk>").ok_or((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unexpected disk XML".to_string(),
+            ))?;
+            xml.insert_str(end, source);
+        }
+        (None, None) => {}
+    }
+    // The disk type follows the kind of source, e.g. file to volume
+    if let Some((kind, _)) = media {
+        let old = format!("type='{}'", disk.attribute("type").unwrap_or("file"));
+        let start_tag = xml.find('>').unwrap_or_default();
+        if let Some(pos) = xml[..start_tag].find(&old) {
+            xml.replace_range(pos..pos + old.len(), &format!("type='{}'", kind));
+        }
+    }
+    Ok(xml)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/domains/{uuid}/disks/{target}/media – insert or change media
+// DELETE /api/domains/{uuid}/disks/{target}/media?scope=live – eject
+// ---------------------------------------------------------------------
+pub async fn change_media(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, target)): Path<(String, String)>,
+    Json(req): Json<ChangeMedia>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let (kind, source, _) = source_xml(&conn, &req.source)?;
+    let xml = disk_with_media(&scope_xml(&dom, req.scope)?, &target, Some((kind, &source)))?;
+    let mut flags = req.scope.flags();
+    if req.force {
+        flags |= sys::VIR_DOMAIN_DEVICE_MODIFY_FORCE;
+    }
+    dom.update_device_flags(&xml, flags)
+        .map_err(libvirt_error)?;
+    let media = req
+        .source
+        .path
+        .clone()
+        .or(req.source.volume.clone())
+        .unwrap_or_default();
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.media_change",
+        &format!("{} {} {} {}", uuid, target, media, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn eject_media(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, target)): Path<(String, String)>,
+    Query(req): Query<EjectQuery>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let xml = disk_with_media(&scope_xml(&dom, req.scope)?, &target, None)?;
+    let mut flags = req.scope.flags();
+    if req.force {
+        flags |= sys::VIR_DOMAIN_DEVICE_MODIFY_FORCE;
+    }
+    dom.update_device_flags(&xml, flags)
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.media_eject",
+        &format!("{} {} {}", uuid, target, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/interfaces – add a NIC
+// ---------------------------------------------------------------------
+pub async fn attach_interface(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<AttachNic>,
+) -> ApiResult<(StatusCode, Json<NicDevice>)> {
+    reject_nul(req.network.as_deref().unwrap_or_default(), "network")?;
+    reject_nul(req.bridge.as_deref().unwrap_or_default(), "bridge")?;
+    reject_nul(&req.model, "model")?;
+    let (kind, source) = match (&req.network, &req.bridge) {
+        (Some(network), None) => (
+            "network",
+            format!("<source network='{}'/>", xml_escape(network)),
+        ),
+        (None, Some(bridge)) => (
+            "bridge",
+            format!("<source bridge='{}'/>", xml_escape(bridge)),
+        ),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "give either network or bridge".into(),
+            ));
+        }
+    };
+    let mac = match req.mac {
+        Some(mac) if valid_mac(&mac) => mac.to_ascii_lowercase(),
+        Some(_) => return Err((StatusCode::BAD_REQUEST, "invalid MAC address".into())),
+        // Chosen here so live and saved definition get the same address
+        None => random_mac(),
+    };
+    let xml = format!(
+        "<interface type='{}'><mac address='{}'/>{}<model type='{}'/></interface>",
+        kind,
+        mac,
+        source,
+        xml_escape(&req.model)
+    );
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    dom.attach_device_flags(&xml, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.nic_attach",
+        &format!("{} {} {} {}", uuid, mac, kind, req.scope.name()),
+    )
+    .await;
+    Ok((
+        StatusCode::CREATED,
+        Json(NicDevice {
+            mac,
+            kind: kind.to_string(),
+            source: req.network.or(req.bridge),
+            model: Some(req.model),
+        }),
+    ))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/interfaces/{mac}?scope=both
+// ---------------------------------------------------------------------
+pub async fn detach_interface(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, mac)): Path<(String, String)>,
+    Query(req): Query<ScopeQuery>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    let domain_xml = scope_xml(&dom, req.scope)?;
+    let doc = roxmltree::Document::parse(&domain_xml).map_err(internal_error)?;
+    let xml = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("interface"))
+        .find(|n| {
+            child(*n, "mac")
+                .and_then(|m| m.attribute("address"))
+                .is_some_and(|a| a.eq_ignore_ascii_case(&mac))
+        })
+        .map(|n| &domain_xml[n.range()])
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("domain has no interface with MAC {}", mac),
+        ))?;
+    dom.detach_device_flags(xml, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.nic_detach",
+        &format!("{} {} {}", uuid, mac, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/domains/{uuid}/vcpus – number of active vCPUs
+// ---------------------------------------------------------------------
+pub async fn set_vcpus(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<SetVcpus>,
+) -> ApiResult<StatusCode> {
+    if req.count == 0 {
+        return Err((StatusCode::BAD_REQUEST, "count must be at least 1".into()));
+    }
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    dom.set_vcpus_flags(req.count, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.vcpus",
+        &format!("{} {} {}", uuid, req.count, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/domains/{uuid}/memory – balloon target
+// ---------------------------------------------------------------------
+pub async fn set_memory(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<SetMemory>,
+) -> ApiResult<StatusCode> {
+    if req.memory_mib == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "memory_mib must be at least 1".into(),
+        ));
+    }
+    let memory_kib = req.memory_mib.checked_mul(1024).ok_or((
+        StatusCode::BAD_REQUEST,
+        "memory_mib is too large".to_string(),
+    ))?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    dom.set_memory_flags(memory_kib, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.memory",
+        &format!("{} {}MiB {}", uuid, req.memory_mib, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
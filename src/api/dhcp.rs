@@ -0,0 +1,300 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::ffi::CStr;
+use virt::{error::Error, network::Network, sys};
+
+use super::networks::lookup;
+use super::{ApiResult, Libvirt, libvirt_error, reject_nul, xml_escape};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// DHCP lease handed out by a libvirt network
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct DhcpLease {
+    pub iface: String,
+    pub mac: String,
+    pub ip: String,
+    pub prefix: u32,
+    pub family: &'static str,
+    pub hostname: Option<String>,
+    pub client_id: Option<String>,
+    /// Expiry as seconds since the unix epoch
+    pub expiry: i64,
+}
+
+/// `<host>` reservation inside the network's `<dhcp>` element.
+#[derive(Deserialize)]
+pub struct StaticHost {
+    mac: String,
+    ip: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// `<host>` entry inside the network's `<dns>` element.
+#[derive(Deserialize)]
+pub struct DnsHost {
+    ip: String,
+    hostnames: Vec<String>,
+}
+
+/// The virt crate has no wrapper for virNetworkGetDHCPLeases, so call it
+/// through the raw bindings.
+pub fn dhcp_leases(net: &Network) -> Result<Vec<DhcpLease>, Error> {
+    let mut leases: *mut sys::virNetworkDHCPLeasePtr = std::ptr::null_mut();
+
+    let size =
+        unsafe { sys::virNetworkGetDHCPLeases(net.as_ptr(), std::ptr::null(), &mut leases, 0) };
+    if size == -1 {
+        return Err(Error::last_error());
+    }
+
+    // SAFETY: libvirt returned `size` valid lease pointers; each lease and the
+    // array itself are owned by us and freed exactly once below.
+    let mut out = Vec::with_capacity(size as usize);
+    unsafe {
+        for i in 0..size as isize {
+            let lease = *leases.offset(i);
+            out.push(DhcpLease {
+                iface: c_string(&(*lease).iface).unwrap_or_default(),
+                mac: c_string(&(*lease).mac).unwrap_or_default(),
+                ip: c_string(&(*lease).ipaddr).unwrap_or_default(),
+                prefix: (*lease).prefix,
+                family: if (*lease).type_ == sys::VIR_IP_ADDR_TYPE_IPV6 as libc::c_int {
+                    "ipv6"
+                } else {
+                    "ipv4"
+                },
+                hostname: c_string(&(*lease).hostname),
+                client_id: c_string(&(*lease).clientid),
+                expiry: (*lease).expirytime,
+            });
+            sys::virNetworkDHCPLeaseFree(lease);
+        }
+        if !leases.is_null() {
+            libc::free(leases as *mut libc::c_void);
+        }
+    }
+    Ok(out)
+}
+
+/// Copy a nullable C string owned by libvirt.
+unsafe fn c_string(ptr: &*mut libc::c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { CStr::from_ptr(*ptr) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+/// Apply a live network XML update – to the running network and, when the
+/// network is persistent, to its saved definition as well.
+fn update_network(
+    net: &Network,
+    command: sys::virNetworkUpdateCommand,
+    section: sys::virNetworkUpdateSection,
+    xml: &str,
+) -> ApiResult<()> {
+    let mut flags = 0;
+    if net.is_active().map_err(libvirt_error)? {
+        flags |= sys::VIR_NETWORK_UPDATE_AFFECT_LIVE;
+    }
+    if net.is_persistent().map_err(libvirt_error)? {
+        flags |= sys::VIR_NETWORK_UPDATE_AFFECT_CONFIG;
+    }
+    // -1 selects the first <ip> element that fits the host's address
+    net.update(command, section, -1, xml, flags)
+        .map_err(libvirt_error)
+}
+
+fn static_host_xml(host: &StaticHost) -> ApiResult<String> {
+    reject_nul(&host.mac, "MAC address")?;
+    reject_nul(&host.ip, "IP address")?;
+    reject_nul(host.name.as_deref().unwrap_or_default(), "host name")?;
+    Ok(match &host.name {
+        Some(name) => format!(
+            "<host mac='{}' name='{}' ip='{}'/>",
+            xml_escape(&host.mac),
+            xml_escape(name),
+            xml_escape(&host.ip)
+        ),
+        None => format!(
+            "<host mac='{}' ip='{}'/>",
+            xml_escape(&host.mac),
+            xml_escape(&host.ip)
+        ),
+    })
+}
+
+// ---------------------------------------------------------------------
+// GET /api/networks/{name}/leases – current DHCP leases
+// ---------------------------------------------------------------------
+pub async fn list_leases(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<Vec<DhcpLease>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    Ok(Json(dhcp_leases(&net).map_err(libvirt_error)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/networks/{name}/hosts – add a static DHCP reservation
+// PUT  /api/networks/{name}/hosts – change the reservation for `mac`
+// ---------------------------------------------------------------------
+pub async fn add_static_host(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(host): Json<StaticHost>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    update_network(
+        &net,
+        sys::VIR_NETWORK_UPDATE_COMMAND_ADD_LAST,
+        sys::VIR_NETWORK_SECTION_IP_DHCP_HOST,
+        &static_host_xml(&host)?,
+    )?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.dhcp_host.add",
+        &format!("{} {} {}", name, host.mac, host.ip),
+    )
+    .await;
+    Ok(StatusCode::CREATED)
+}
+
+pub async fn modify_static_host(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(host): Json<StaticHost>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    update_network(
+        &net,
+        sys::VIR_NETWORK_UPDATE_COMMAND_MODIFY,
+        sys::VIR_NETWORK_SECTION_IP_DHCP_HOST,
+        &static_host_xml(&host)?,
+    )?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.dhcp_host.modify",
+        &format!("{} {} {}", name, host.mac, host.ip),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/networks/{name}/hosts/{mac}
+// ---------------------------------------------------------------------
+pub async fn delete_static_host(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, mac)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    reject_nul(&mac, "MAC address")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    // libvirt matches the entry to delete by its MAC address
+    update_network(
+        &net,
+        sys::VIR_NETWORK_UPDATE_COMMAND_DELETE,
+        sys::VIR_NETWORK_SECTION_IP_DHCP_HOST,
+        &format!("<host mac='{}'/>", xml_escape(&mac)),
+    )?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.dhcp_host.delete",
+        &format!("{} {}", name, mac),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/networks/{name}/dns-hosts – add a DNS host entry
+// ---------------------------------------------------------------------
+pub async fn add_dns_host(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(host): Json<DnsHost>,
+) -> ApiResult<StatusCode> {
+    if host.hostnames.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "hostnames must not be empty".into(),
+        ));
+    }
+    reject_nul(&host.ip, "IP address")?;
+    for hostname in &host.hostnames {
+        reject_nul(hostname, "hostname")?;
+    }
+    let hostnames: String = host
+        .hostnames
+        .iter()
+        .map(|h| format!("<hostname>{}</hostname>", xml_escape(h)))
+        .collect();
+    let xml = format!("<host ip='{}'>{}</host>", xml_escape(&host.ip), hostnames);
+
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    update_network(
+        &net,
+        sys::VIR_NETWORK_UPDATE_COMMAND_ADD_LAST,
+        sys::VIR_NETWORK_SECTION_DNS_HOST,
+        &xml,
+    )?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.dns_host.add",
+        &format!("{} {} {}", name, host.ip, host.hostnames.join(",")),
+    )
+    .await;
+    Ok(StatusCode::CREATED)
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/networks/{name}/dns-hosts/{ip}
+// ---------------------------------------------------------------------
+pub async fn delete_dns_host(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, ip)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    reject_nul(&ip, "IP address")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    update_network(
+        &net,
+        sys::VIR_NETWORK_UPDATE_COMMAND_DELETE,
+        sys::VIR_NETWORK_SECTION_DNS_HOST,
+        &format!("<host ip='{}'/>", xml_escape(&ip)),
+    )?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.dns_host.delete",
+        &format!("{} {}", name, ip),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
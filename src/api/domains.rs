@@ -0,0 +1,169 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::Serialize;
+use virt::{domain::Domain, sys};
+
+use super::templates::ensure_no_linked_clones;
+use super::{
+    ApiResult, DomainInfo, Libvirt, domain_info, internal_error, libvirt_error, reject_nul,
+};
+use crate::{AppState, audit, auth::CurrentUser, templates};
+
+// ---------------------------------------------------------------------
+// Addresses of one guest interface
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct InterfaceAddresses {
+    pub name: String,
+    pub mac: String,
+    pub addresses: Vec<IpAddress>,
+}
+
+#[derive(Serialize)]
+pub struct IpAddress {
+    pub ip: String,
+    pub prefix: u64,
+    pub family: &'static str,
+}
+
+/// Find a domain by UUID.
+pub fn lookup(conn: &Libvirt, uuid: &str) -> ApiResult<Domain> {
+    reject_nul(uuid, "uuid")?;
+    Domain::lookup_by_uuid_string(conn, uuid).map_err(libvirt_error)
+}
+
+/// IP addresses the libvirt DHCP server leased to the domain's interfaces.
+pub fn leased_addresses(dom: &Domain) -> Result<Vec<InterfaceAddresses>, virt::error::Error> {
+    let interfaces = dom.interface_addresses(sys::VIR_DOMAIN_INTERFACE_ADDRESSES_SRC_LEASE, 0)?;
+    Ok(interfaces
+        .into_iter()
+        .map(|iface| InterfaceAddresses {
+            name: iface.name,
+            mac: iface.hwaddr,
+            addresses: iface
+                .addrs
+                .into_iter()
+                .map(|a| IpAddress {
+                    ip: a.addr,
+                    prefix: a.prefix,
+                    family: if a.typed == sys::VIR_IP_ADDR_TYPE_IPV6 as i64 {
+                        "ipv6"
+                    } else {
+                        "ipv4"
+                    },
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/addresses – leased IPs per interface
+// ---------------------------------------------------------------------
+pub async fn get_addresses(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<Vec<InterfaceAddresses>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    Ok(Json(leased_addresses(&dom).map_err(libvirt_error)?))
+}
+
+/// Lifecycle operations that take no request body.
+#[derive(Clone, Copy)]
+enum DomainAction {
+    Start,
+    Shutdown,
+    Destroy,
+    Reboot,
+    Suspend,
+    Resume,
+}
+
+async fn domain_action(
+    user: CurrentUser,
+    state: AppState,
+    uuid: String,
+    action: DomainAction,
+) -> ApiResult<Json<DomainInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup(&conn, &uuid)?;
+    if matches!(action, DomainAction::Start) {
+        let uuid = dom.get_uuid_string().map_err(libvirt_error)?;
+        if templates::get(&state.pool, &uuid)
+            .await
+            .map_err(internal_error)?
+            .is_some()
+        {
+            ensure_no_linked_clones(&conn, &dom, "start")?;
+        }
+    }
+    let label = match action {
+        DomainAction::Start => dom.create().map(|_| "domain.start"),
+        // ACPI request – the guest decides when it is off
+        DomainAction::Shutdown => dom.shutdown().map(|_| "domain.shutdown"),
+        // Immediate power off
+        DomainAction::Destroy => dom.destroy().map(|_| "domain.destroy"),
+        DomainAction::Reboot => dom.reboot(0).map(|_| "domain.reboot"),
+        DomainAction::Suspend => dom.suspend().map(|_| "domain.suspend"),
+        DomainAction::Resume => dom.resume().map(|_| "domain.resume"),
+    }
+    .map_err(libvirt_error)?;
+    let info = domain_info(&dom).map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, label, &info.name).await;
+    Ok(Json(info))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/start|shutdown|destroy|reboot|suspend|resume
+// ---------------------------------------------------------------------
+pub async fn start_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Start).await
+}
+
+pub async fn shutdown_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Shutdown).await
+}
+
+pub async fn destroy_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Destroy).await
+}
+
+pub async fn reboot_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Reboot).await
+}
+
+pub async fn suspend_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Suspend).await
+}
+
+pub async fn resume_domain(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainInfo>> {
+    domain_action(user, state, uuid, DomainAction::Resume).await
+}
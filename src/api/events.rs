@@ -0,0 +1,29 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{AppState, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// GET /api/events – domain lifecycle events as Server-Sent Events.
+// `domain` events carry a JSON `DomainEvent`; `resync` means events were
+// dropped and the client should reload what it shows.
+// ---------------------------------------------------------------------
+pub async fn domain_events(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let receiver = state.events.subscribe();
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(event) => Event::default().event("domain").json_data(&event),
+            Err(RecvError::Lagged(_)) => Ok(Event::default().event("resync").data("")),
+            Err(RecvError::Closed) => return None,
+        };
+        Some((event, receiver))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
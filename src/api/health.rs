@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use axum::{Json, extract::State, http::StatusCode};
+use futures_util::future::join_all;
+use serde::Serialize;
+use virt::connect::Connect;
+
+use super::Libvirt;
+use crate::{AppState, hosts};
+
+/// How long a readiness check waits for one hypervisor.
+const HOST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------
+// Probe responses – these endpoints are reachable without a session, so
+// they never contain URIs, usernames or raw error messages.
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct Readiness {
+    ready: bool,
+    database: bool,
+    session_store: bool,
+    hosts: Vec<HostReadiness>,
+}
+
+#[derive(Serialize)]
+pub struct HostReadiness {
+    name: String,
+    alive: bool,
+}
+
+#[derive(Serialize)]
+pub struct VersionInfo {
+    version: &'static str,
+    git_hash: &'static str,
+    build_time: &'static str,
+    libvirt_library: Option<String>,
+    libvirt_daemon: Option<String>,
+    hypervisor: Option<String>,
+}
+
+// ---------------------------------------------------------------------
+// GET /healthz – the process is up and serving requests
+// ---------------------------------------------------------------------
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+// ---------------------------------------------------------------------
+// GET /readyz – every dependency answers
+// ---------------------------------------------------------------------
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Readiness>) {
+    let database = sqlx::query("SELECT 1").execute(&state.pool).await.is_ok();
+    let session_store = sqlx::query("SELECT COUNT(*) FROM sessions_table")
+        .execute(&state.pool)
+        .await
+        .is_ok();
+
+    // The local hypervisor plus every registered host
+    let mut targets = vec![("local".to_string(), state.config.libvirt_uri.clone())];
+    if let Ok(registered) = hosts::list(&state.pool).await {
+        targets.extend(registered.into_iter().map(|h| (h.name, h.uri)));
+    }
+
+    // Opening a remote connection can block for a while (ssh, tls), so the
+    // hosts are checked in parallel and a host that does not answer in time
+    // counts as down
+    let host_status = join_all(targets.into_iter().map(|(name, uri)| async move {
+        let check = tokio::task::spawn_blocking(move || {
+            Libvirt::open(&uri)
+                .map(|conn| conn.is_alive().unwrap_or(false))
+                .unwrap_or(false)
+        });
+        let alive = matches!(
+            tokio::time::timeout(HOST_TIMEOUT, check).await,
+            Ok(Ok(true))
+        );
+        HostReadiness { name, alive }
+    }))
+    .await;
+
+    let ready = database && session_store && host_status.iter().all(|h| h.alive);
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        code,
+        Json(Readiness {
+            ready,
+            database,
+            session_store,
+            hosts: host_status,
+        }),
+    )
+}
+
+// ---------------------------------------------------------------------
+// GET /version – build and libvirt versions
+// ---------------------------------------------------------------------
+pub async fn version(State(state): State<AppState>) -> Json<VersionInfo> {
+    let uri = state.config.libvirt_uri.clone();
+    let (libvirt_daemon, hypervisor) =
+        tokio::task::spawn_blocking(move || match Libvirt::open(&uri) {
+            Ok(conn) => (
+                conn.get_lib_version().ok().map(format_version),
+                conn.get_hyp_version().ok().map(format_version),
+            ),
+            Err(_) => (None, None),
+        })
+        .await
+        .unwrap_or((None, None));
+
+    Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: env!("RUST_MANAGER_GIT_HASH"),
+        build_time: env!("RUST_MANAGER_BUILD_TIME"),
+        libvirt_library: Connect::get_version().ok().map(format_version),
+        libvirt_daemon,
+        hypervisor,
+    })
+}
+
+/// libvirt encodes versions as `major * 1_000_000 + minor * 1_000 + release`.
+pub fn format_version(v: u32) -> String {
+    format!("{}.{}.{}", v / 1_000_000, (v / 1_000) % 1_000, v % 1_000)
+}
@@ -0,0 +1,293 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+use super::health::format_version;
+use super::{ApiResult, Libvirt, internal_error, libvirt_error};
+use crate::{AppState, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Host overview returned as JSON – what `virsh nodeinfo`, `capabilities`,
+// `freecell` and `cpu-models` would show
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct HostOverview {
+    pub hostname: String,
+    pub hypervisor: String,
+    pub libvirt_version: Option<String>,
+    pub hypervisor_version: Option<String>,
+    pub cpu: HostCpu,
+    /// Total memory in bytes
+    pub memory: u64,
+    /// Free memory in bytes
+    pub free_memory: Option<u64>,
+    pub numa_cells: Vec<NumaCell>,
+    pub guests: Vec<GuestArch>,
+    /// CPU models usable for guests (`custom` mode), by host architecture
+    pub cpu_models: Vec<CpuModel>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct HostCpu {
+    pub arch: String,
+    pub model: String,
+    pub vendor: Option<String>,
+    /// Online CPUs
+    pub cpus: u32,
+    pub mhz: u32,
+    pub sockets: u32,
+    pub dies: u32,
+    pub cores: u32,
+    pub threads: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NumaCell {
+    pub id: u32,
+    /// Memory of the cell in bytes
+    pub memory: u64,
+    /// Free memory of the cell in bytes
+    pub free_memory: Option<u64>,
+    pub cpus: Vec<u32>,
+}
+
+/// A guest architecture the hypervisor can run.
+#[derive(Debug, Serialize)]
+pub struct GuestArch {
+    pub os_type: String,
+    pub arch: String,
+    pub wordsize: Option<u32>,
+    pub emulator: Option<String>,
+    /// Virtualisation types, e.g. `qemu` and `kvm`
+    pub domain_types: Vec<String>,
+    pub machines: Vec<MachineType>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MachineType {
+    pub name: String,
+    /// The versioned machine type an alias such as `pc` resolves to
+    pub canonical: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuModel {
+    pub name: String,
+    pub vendor: Option<String>,
+    pub usable: bool,
+}
+
+/// The interesting parts of the capabilities XML.
+#[derive(Debug, Default)]
+pub struct ParsedCapabilities {
+    pub cpu: HostCpu,
+    pub cells: Vec<NumaCell>,
+    pub guests: Vec<GuestArch>,
+}
+
+// ---------------------------------------------------------------------
+// Parse `virConnectGetCapabilities` – host CPU, NUMA topology, guests
+// ---------------------------------------------------------------------
+pub fn parse_capabilities(xml: &str) -> Result<ParsedCapabilities, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let mut cpu = HostCpu::default();
+    let host = child(root, "host");
+    if let Some(host_cpu) = host.and_then(|h| child(h, "cpu")) {
+        cpu.arch = text(child(host_cpu, "arch")).unwrap_or_default();
+        cpu.model = text(child(host_cpu, "model")).unwrap_or_default();
+        cpu.vendor = text(child(host_cpu, "vendor"));
+        if let Some(topology) = child(host_cpu, "topology") {
+            cpu.sockets = number(topology, "sockets");
+            cpu.dies = number(topology, "dies").max(1);
+            cpu.cores = number(topology, "cores");
+            cpu.threads = number(topology, "threads");
+        }
+    }
+
+    let cells = host
+        .and_then(|h| child(h, "topology"))
+        .and_then(|t| child(t, "cells"))
+        .into_iter()
+        .flat_map(|cells| cells.children())
+        .filter(|n| n.has_tag_name("cell"))
+        .map(|cell| NumaCell {
+            id: number(cell, "id"),
+            memory: child(cell, "memory")
+                .map(|m| {
+                    let value: u64 = text(Some(m)).and_then(|v| v.parse().ok()).unwrap_or(0);
+                    value.saturating_mul(unit_bytes(m.attribute("unit").unwrap_or("KiB")))
+                })
+                .unwrap_or(0),
+            free_memory: None,
+            cpus: child(cell, "cpus")
+                .into_iter()
+                .flat_map(|cpus| cpus.children())
+                .filter(|n| n.has_tag_name("cpu"))
+                .map(|c| number(c, "id"))
+                .collect(),
+        })
+        .collect();
+
+    let guests = root
+        .children()
+        .filter(|n| n.has_tag_name("guest"))
+        .filter_map(|guest| {
+            let arch = child(guest, "arch")?;
+            let machines = |node: roxmltree::Node<'_, '_>| {
+                node.children()
+                    .filter(|n| n.has_tag_name("machine"))
+                    .filter_map(|m| {
+                        Some(MachineType {
+                            name: text(Some(m))?,
+                            canonical: m.attribute("canonical").map(str::to_string),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            };
+            // Machines listed per `<domain>` only add to the generic list
+            let mut all = machines(arch);
+            for domain in arch.children().filter(|n| n.has_tag_name("domain")) {
+                for machine in machines(domain) {
+                    if !all.iter().any(|m| m.name == machine.name) {
+                        all.push(machine);
+                    }
+                }
+            }
+            Some(GuestArch {
+                os_type: text(child(guest, "os_type")).unwrap_or_default(),
+                arch: arch.attribute("name").unwrap_or_default().to_string(),
+                wordsize: text(child(arch, "wordsize")).and_then(|w| w.parse().ok()),
+                emulator: text(child(arch, "emulator")),
+                domain_types: arch
+                    .children()
+                    .filter(|n| n.has_tag_name("domain"))
+                    .filter_map(|d| d.attribute("type"))
+                    .map(str::to_string)
+                    .collect(),
+                machines: all,
+            })
+        })
+        .collect();
+
+    Ok(ParsedCapabilities { cpu, cells, guests })
+}
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn text(node: Option<roxmltree::Node<'_, '_>>) -> Option<String> {
+    node.and_then(|n| n.text()).map(|t| t.trim().to_string())
+}
+
+fn number(node: roxmltree::Node<'_, '_>, attr: &str) -> u32 {
+    node.attribute(attr)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Bytes per libvirt memory unit.
+fn unit_bytes(unit: &str) -> u64 {
+    match unit {
+        "b" | "bytes" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        _ => 1 << 10,
+    }
+}
+
+// ---------------------------------------------------------------------
+// Parse `virConnectGetDomainCapabilities` – the named CPU models
+// ---------------------------------------------------------------------
+pub fn parse_cpu_models(xml: &str) -> Result<Vec<CpuModel>, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name("mode") && n.attribute("name") == Some("custom"))
+        .flat_map(|mode| mode.children())
+        .filter(|n| n.has_tag_name("model"))
+        .filter_map(|m| {
+            Some(CpuModel {
+                name: m.text()?.trim().to_string(),
+                vendor: m.attribute("vendor").map(str::to_string),
+                usable: m.attribute("usable") != Some("no"),
+            })
+        })
+        .collect())
+}
+
+/// Topology from the capabilities, or from virNodeGetInfo when missing.
+fn or_node(caps: u32, node: u32) -> u32 {
+    if caps == 0 { node } else { caps }
+}
+
+/// Collect the overview; blocking, as every call is a libvirt round trip.
+pub fn host_overview(conn: &Libvirt) -> ApiResult<HostOverview> {
+    let node = conn.get_node_info().map_err(libvirt_error)?;
+    let xml = conn.get_capabilities().map_err(libvirt_error)?;
+    let caps = parse_capabilities(&xml).map_err(internal_error)?;
+
+    let mut cells = caps.cells;
+    // Cell IDs need not be contiguous (offline or hot-plugged nodes), so
+    // every cell is asked for by its own ID
+    for cell in cells.iter_mut() {
+        cell.free_memory = conn
+            .get_cells_free_memory(cell.id as i32, 1)
+            .ok()
+            .and_then(|free| free.first().copied());
+    }
+
+    // Hypervisors without domain capabilities (e.g. the test driver) have no
+    // model list
+    let cpu_models = conn
+        .get_domain_capabilities(None, None, None, None, 0)
+        .ok()
+        .and_then(|xml| parse_cpu_models(&xml).ok())
+        .unwrap_or_default();
+
+    Ok(HostOverview {
+        hostname: conn.get_hostname().map_err(libvirt_error)?,
+        hypervisor: conn.get_type().map_err(libvirt_error)?,
+        libvirt_version: conn.get_lib_version().ok().map(format_version),
+        hypervisor_version: conn.get_hyp_version().ok().map(format_version),
+        cpu: HostCpu {
+            // virNodeInfo's "model" is the architecture, e.g. x86_64
+            arch: if caps.cpu.arch.is_empty() {
+                node.model
+            } else {
+                caps.cpu.arch
+            },
+            cpus: node.cpus,
+            mhz: node.mhz,
+            sockets: or_node(caps.cpu.sockets, node.sockets),
+            cores: or_node(caps.cpu.cores, node.cores),
+            threads: or_node(caps.cpu.threads, node.threads),
+            ..caps.cpu
+        },
+        memory: node.memory.saturating_mul(1024),
+        free_memory: conn.get_free_memory().ok(),
+        numa_cells: cells,
+        guests: caps.guests,
+        cpu_models,
+    })
+}
+
+// ---------------------------------------------------------------------
+// GET /api/host – hardware, versions and guest capabilities
+// ---------------------------------------------------------------------
+pub async fn get_host(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<HostOverview>> {
+    let uri = state.config.libvirt_uri.clone();
+    let overview = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        host_overview(&conn)
+    })
+    .await
+    .map_err(internal_error)??;
+    Ok(Json(overview))
+}
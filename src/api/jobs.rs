@@ -0,0 +1,261 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{ApiResult, internal_error};
+use crate::jobs::{self, Job, JobRun, JobSpec};
+use crate::{AppState, audit, auth::CurrentUser, scheduler};
+
+/// Runs returned by the history endpoint.
+const HISTORY_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct JobRequest {
+    name: String,
+    /// Cron expression, e.g. `0 19 * * 1-5`
+    schedule: String,
+    method: String,
+    /// API path of the action, e.g. `/api/domains/{uuid}/shutdown`
+    path: String,
+    /// JSON body of the call – an object, or its JSON text as a string
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct Enabled {
+    enabled: bool,
+}
+
+impl JobRequest {
+    /// Validate the request; returns the JSON body text and the first run.
+    fn check(&self) -> ApiResult<(Option<String>, i64)> {
+        let bad = |msg: String| (StatusCode::BAD_REQUEST, msg);
+        if self.name.trim().is_empty() {
+            return Err(bad("name must not be empty".into()));
+        }
+        scheduler::validate_call(&self.method, &self.path).map_err(bad)?;
+        let body = match &self.body {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) if text.trim().is_empty() => None,
+            Some(Value::String(text)) => {
+                serde_json::from_str::<Value>(text)
+                    .map_err(|e| bad(format!("body is not valid JSON: {}", e)))?;
+                Some(text.clone())
+            }
+            Some(value) => Some(value.to_string()),
+        };
+        let next = scheduler::next_run(&self.schedule, scheduler::now()).map_err(bad)?;
+        Ok((body, next))
+    }
+
+    fn spec<'a>(&'a self, body: Option<&'a str>) -> JobSpec<'a> {
+        JobSpec {
+            name: &self.name,
+            schedule: &self.schedule,
+            method: &self.method,
+            path: &self.path,
+            body,
+        }
+    }
+}
+
+/// Load a job that `user` may change – only its owner may.
+async fn owned_job(state: &AppState, user: &CurrentUser, id: i64) -> ApiResult<Job> {
+    let job = jobs::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "job not found".to_string()))?;
+    if job.owner != user.username {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("job belongs to {}", job.owner),
+        ));
+    }
+    Ok(job)
+}
+
+async fn fetch(state: &AppState, id: i64) -> ApiResult<Json<Job>> {
+    jobs::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "job not found".into()))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/jobs
+// ---------------------------------------------------------------------
+pub async fn list_jobs(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<Job>>> {
+    Ok(Json(jobs::list(&state.pool).await.map_err(internal_error)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/jobs – the caller becomes the job's owner
+// ---------------------------------------------------------------------
+pub async fn create_job(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<JobRequest>,
+) -> ApiResult<(StatusCode, Json<Job>)> {
+    let (body, next) = req.check()?;
+    let id = jobs::add(
+        &state.pool,
+        &user.username,
+        &req.spec(body.as_deref()),
+        next,
+    )
+    .await
+    .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "job.create",
+        &format!(
+            "{} {} [{}] {} {}",
+            id, req.name, req.schedule, req.method, req.path
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, fetch(&state, id).await?))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/jobs/{id}
+// ---------------------------------------------------------------------
+pub async fn get_job(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Job>> {
+    fetch(&state, id).await
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/jobs/{id} – replace schedule and action
+// ---------------------------------------------------------------------
+pub async fn update_job(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<JobRequest>,
+) -> ApiResult<Json<Job>> {
+    owned_job(&state, &user, id).await?;
+    let (body, next) = req.check()?;
+    jobs::update(&state.pool, id, &req.spec(body.as_deref()), next)
+        .await
+        .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "job.update",
+        &format!(
+            "{} {} [{}] {} {}",
+            id, req.name, req.schedule, req.method, req.path
+        ),
+    )
+    .await;
+    fetch(&state, id).await
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/jobs/{id}
+// ---------------------------------------------------------------------
+pub async fn delete_job(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    let job = owned_job(&state, &user, id).await?;
+    jobs::delete(&state.pool, id)
+        .await
+        .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "job.delete",
+        &format!("{} {}", id, job.name),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/jobs/{id}/enabled – {"enabled": true|false}
+// ---------------------------------------------------------------------
+pub async fn set_job_enabled(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<Enabled>,
+) -> ApiResult<Json<Job>> {
+    let job = owned_job(&state, &user, id).await?;
+    jobs::set_enabled(&state.pool, id, req.enabled)
+        .await
+        .map_err(internal_error)?;
+    if req.enabled {
+        // Runs missed while disabled are skipped
+        let next = scheduler::next_run(&job.schedule, scheduler::now()).ok();
+        jobs::set_next_run(&state.pool, id, next)
+            .await
+            .map_err(internal_error)?;
+    }
+    audit::record(
+        &state.pool,
+        &user.username,
+        if req.enabled {
+            "job.enable"
+        } else {
+            "job.disable"
+        },
+        &format!("{} {}", id, job.name),
+    )
+    .await;
+    fetch(&state, id).await
+}
+
+// ---------------------------------------------------------------------
+// POST /api/jobs/{id}/run – run on the scheduler's next tick
+// ---------------------------------------------------------------------
+pub async fn run_job(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    let job = owned_job(&state, &user, id).await?;
+    if !job.enabled {
+        return Err((StatusCode::CONFLICT, "job is disabled".into()));
+    }
+    jobs::set_next_run(&state.pool, id, Some(scheduler::now()))
+        .await
+        .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "job.run",
+        &format!("{} {}", id, job.name),
+    )
+    .await;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/jobs/{id}/runs – run history, newest first
+// ---------------------------------------------------------------------
+pub async fn list_runs(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<JobRun>>> {
+    let runs = jobs::runs(&state.pool, Some(id), HISTORY_LIMIT)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(runs))
+}
@@ -0,0 +1,581 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use virt::{
+    domain::MigrateParameters,
+    error::{Error, ErrorNumber},
+    sys,
+};
+
+use super::domains::lookup;
+use super::{ApiResult, Libvirt, internal_error, libvirt_error};
+use crate::migrations::{self, Migration, NewMigration};
+use crate::{AppState, audit, auth::CurrentUser, hosts};
+
+/// How often a running migration is asked for progress.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Migrations listed by `GET /api/migrations`.
+const HISTORY_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct StartMigration {
+    /// Name of a registered host
+    host: String,
+    /// Keep a running domain running while its memory is copied; otherwise
+    /// it is paused for the transfer. Stopped domains migrate offline.
+    #[serde(default = "default_live")]
+    live: bool,
+    /// The source libvirtd connects to the destination itself instead of
+    /// rust-manager driving both sides
+    #[serde(default)]
+    peer_to_peer: bool,
+    /// `all` or `incremental` – copy disks that are not on shared storage
+    #[serde(default)]
+    copy_storage: Option<String>,
+    /// Bandwidth limit in MiB/s
+    #[serde(default)]
+    bandwidth: Option<u64>,
+    /// Throttle the guest's vCPUs when memory is dirtied faster than it is
+    /// copied
+    #[serde(default)]
+    auto_converge: bool,
+    /// Switch to post-copy once the first pass over memory is done
+    #[serde(default)]
+    post_copy: bool,
+    /// Keep the domain defined on this host afterwards
+    #[serde(default)]
+    keep_source: bool,
+}
+
+fn default_live() -> bool {
+    true
+}
+
+/// What the migration worker needs to know.
+struct Job {
+    uuid: String,
+    destination_uri: String,
+    peer_to_peer: bool,
+    bandwidth: Option<u64>,
+    flags: u32,
+}
+
+/// libvirt flags, mode name and option summary for a migration request.
+fn plan(
+    req: &StartMigration,
+    active: bool,
+    persistent: bool,
+) -> Result<(u32, &'static str, String), String> {
+    let mut flags = 0;
+    let mut options = Vec::new();
+    let mode = if !active {
+        if !persistent {
+            return Err("a stopped domain must be persistent to migrate".into());
+        }
+        if req.copy_storage.is_some() || req.auto_converge || req.post_copy {
+            return Err("storage copy, auto-converge and post-copy need a running domain".into());
+        }
+        flags |= sys::VIR_MIGRATE_OFFLINE;
+        "offline"
+    } else if req.live {
+        flags |= sys::VIR_MIGRATE_LIVE;
+        "live"
+    } else {
+        "suspended"
+    };
+    if persistent {
+        flags |= sys::VIR_MIGRATE_PERSIST_DEST;
+        if !req.keep_source {
+            flags |= sys::VIR_MIGRATE_UNDEFINE_SOURCE;
+        }
+    }
+    if req.keep_source {
+        options.push("keep-source".to_string());
+    }
+    if req.peer_to_peer {
+        options.push("p2p".to_string());
+    }
+    match req.copy_storage.as_deref() {
+        None => {}
+        Some("all") => flags |= sys::VIR_MIGRATE_NON_SHARED_DISK,
+        Some("incremental") => flags |= sys::VIR_MIGRATE_NON_SHARED_INC,
+        Some(other) => {
+            return Err(format!(
+                "copy_storage must be all or incremental, not {}",
+                other
+            ));
+        }
+    }
+    if let Some(copy) = &req.copy_storage {
+        options.push(format!("copy-storage={}", copy));
+    }
+    match req.bandwidth {
+        Some(0) => return Err("bandwidth must be at least 1 MiB/s".into()),
+        Some(bandwidth) => options.push(format!("bandwidth={}MiB/s", bandwidth)),
+        None => {}
+    }
+    if req.auto_converge {
+        flags |= sys::VIR_MIGRATE_AUTO_CONVERGE;
+        options.push("auto-converge".to_string());
+    }
+    if req.post_copy {
+        if !req.live {
+            return Err("post-copy needs a live migration".into());
+        }
+        flags |= sys::VIR_MIGRATE_POSTCOPY;
+        options.push("post-copy".to_string());
+    }
+    Ok((flags, mode, options.join(" ")))
+}
+
+/// Run the migration to completion; the error carries the final status,
+/// `failed` or `cancelled`.
+fn migrate(uri: &str, job: &Job) -> Result<(), (&'static str, String)> {
+    let failed = |(_, message): (StatusCode, String)| ("failed", message);
+    let conn = Libvirt::open_long_lived(uri).map_err(failed)?;
+    let dom = lookup(&conn, &job.uuid).map_err(failed)?;
+    let params = MigrateParameters {
+        bandwidth: job.bandwidth,
+        ..MigrateParameters::default()
+    };
+    let outcome = if job.peer_to_peer {
+        dom.migrate_to_uri3(
+            Some(job.destination_uri.as_str()),
+            params,
+            job.flags | sys::VIR_MIGRATE_PEER2PEER,
+        )
+    } else {
+        let dconn = Libvirt::open_long_lived(&job.destination_uri).map_err(failed)?;
+        dom.migrate3(&dconn, params, job.flags).map(|_| ())
+    };
+    outcome.map_err(|e| match e.code() {
+        ErrorNumber::OperationAborted => ("cancelled", e.message().to_string()),
+        _ => failed(libvirt_error(e)),
+    })
+}
+
+/// Progress of the migration job of a domain.
+struct Progress {
+    processed: Option<u64>,
+    total: Option<u64>,
+    /// Post-copy was started by this poll
+    post_copy: bool,
+}
+
+/// Read the job progress and, when `switch` is set, start post-copy once
+/// the first pass over memory is done. `None` once the job is gone.
+fn poll_progress(uri: &str, uuid: &str, switch: bool) -> ApiResult<Option<Progress>> {
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup(&conn, uuid)?;
+    let stats = dom.get_job_stats(0).map_err(libvirt_error)?;
+    if stats.r#type as u32 == sys::VIR_DOMAIN_JOB_NONE {
+        return Ok(None);
+    }
+    let post_copy = switch
+        && stats.mem_iteration.is_some_and(|i| i >= 2)
+        && unsafe { sys::virDomainMigrateStartPostCopy(dom.as_ptr(), 0) } == 0;
+    Ok(Some(Progress {
+        processed: stats.data_processed,
+        total: stats.data_total,
+        post_copy,
+    }))
+}
+
+/// Run migration `id` in the background, keeping its progress in the
+/// database up to date until libvirt returns.
+fn run(state: AppState, id: i64, name: String, job: Job) {
+    tokio::spawn(async move {
+        let uri = state.config.libvirt_uri.clone();
+        let uuid = job.uuid.clone();
+        let mut switch = job.flags & sys::VIR_MIGRATE_POSTCOPY != 0;
+        let mut worker = tokio::task::spawn_blocking(move || migrate(&uri, &job));
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut worker => {
+                    break outcome.unwrap_or_else(|e| Err(("failed", e.to_string())));
+                }
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+            let uri = state.config.libvirt_uri.clone();
+            let domain = uuid.clone();
+            let poll = tokio::task::spawn_blocking(move || poll_progress(&uri, &domain, switch))
+                .await
+                .unwrap_or_else(|e| Err(internal_error(e)));
+            // The job may not have started yet or already be gone
+            if let Ok(Some(progress)) = poll {
+                if progress.post_copy {
+                    println!("🚚 Migration {} of {} switched to post-copy", id, name);
+                    switch = false;
+                }
+                let updated = migrations::update_progress(
+                    &state.pool,
+                    id,
+                    progress.processed,
+                    progress.total,
+                    progress.post_copy,
+                )
+                .await;
+                if let Err(e) = updated {
+                    eprintln!("⚠️  Could not update migration {}: {}", id, e);
+                }
+            }
+        };
+        let (status, error) = match outcome {
+            Ok(()) => ("completed", None),
+            Err((status, message)) => (status, Some(message)),
+        };
+        if let Err(e) = migrations::finish(&state.pool, id, status, error.as_deref()).await {
+            eprintln!("⚠️  Could not record the end of migration {}: {}", id, e);
+        }
+        println!("🚚 Migration {} of {} {}", id, name, status);
+    });
+}
+
+/// Mark migrations that were running when the server stopped.
+pub async fn interrupt_running(state: &AppState) {
+    match migrations::interrupt_running(&state.pool).await {
+        Ok(0) => {}
+        Ok(n) => eprintln!("⚠️  Marked {} unfinished migration(s) as interrupted", n),
+        Err(e) => eprintln!("⚠️  Could not update unfinished migrations: {}", e),
+    }
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/migrate – migrate to a registered host
+// ---------------------------------------------------------------------
+pub async fn start_migration(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<StartMigration>,
+) -> ApiResult<(StatusCode, Json<Migration>)> {
+    let host = hosts::find(&state.pool, &req.host)
+        .await
+        .map_err(internal_error)?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("no registered host named {}", req.host),
+        ))?;
+    if host.uri == state.config.libvirt_uri {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{} is the local hypervisor", host.name),
+        ));
+    }
+    let (name, uuid, active, persistent) = {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        let dom = lookup(&conn, &uuid)?;
+        (
+            dom.get_name().map_err(libvirt_error)?,
+            dom.get_uuid_string().map_err(libvirt_error)?,
+            dom.is_active().map_err(libvirt_error)?,
+            dom.is_persistent().map_err(libvirt_error)?,
+        )
+    };
+    let (flags, mode, options) =
+        plan(&req, active, persistent).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let id = migrations::insert(
+        &state.pool,
+        &NewMigration {
+            domain_uuid: &uuid,
+            domain_name: &name,
+            destination: &host.name,
+            mode,
+            options: &options,
+            started_by: &user.username,
+        },
+    )
+    .await
+    .map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.migrate",
+        format!("{} -> {} {} {}", name, host.name, mode, options).trim_end(),
+    )
+    .await;
+    run(
+        state.clone(),
+        id,
+        name,
+        Job {
+            uuid,
+            destination_uri: host.uri,
+            peer_to_peer: req.peer_to_peer,
+            bandwidth: req.bandwidth,
+            flags,
+        },
+    );
+
+    let migration = migrations::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "migration not found".to_string()))?;
+    Ok((StatusCode::CREATED, Json(migration)))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/migrations – recent migrations, newest first
+// ---------------------------------------------------------------------
+pub async fn list_migrations(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<Migration>>> {
+    let migrations = migrations::list(&state.pool, HISTORY_LIMIT)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(migrations))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/migrations/{id} – one migration including its progress
+// ---------------------------------------------------------------------
+pub async fn get_migration(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Migration>> {
+    migrations::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "migration not found".into()))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/migrations/{id}/cancel – abort a running migration; not
+// possible any more once it switched to post-copy
+// ---------------------------------------------------------------------
+pub async fn cancel_migration(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    let migration = migrations::get(&state.pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "migration not found".to_string()))?;
+    if migration.status != "running" {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("migration is already {}", migration.status),
+        ));
+    }
+    {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        let dom = lookup(&conn, &migration.domain_uuid)?;
+        if unsafe { sys::virDomainAbortJob(dom.as_ptr()) } == -1 {
+            return Err(libvirt_error(Error::last_error()));
+        }
+    }
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.migrate_cancel",
+        &format!("{} {}", migration.domain_name, id),
+    )
+    .await;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::Arc;
+
+    /// libvirt's built-in test driver; every connection in the process
+    /// shares its state while one stays open.
+    const TEST_URI: &str = "test:///default";
+
+    fn request(json: serde_json::Value) -> StartMigration {
+        serde_json::from_value(json).unwrap()
+    }
+
+    async fn test_state() -> AppState {
+        // One connection, as every connection gets its own in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::MIGRATOR.run(&pool).await.unwrap();
+        AppState {
+            pool,
+            config: Arc::new(crate::config::Config {
+                libvirt_uri: TEST_URI.to_string(),
+                ..crate::config::Config::default()
+            }),
+            metrics: Default::default(),
+            events: Default::default(),
+            tickets: Default::default(),
+            serial: Default::default(),
+        }
+    }
+
+    fn admin() -> CurrentUser {
+        CurrentUser {
+            id: 1,
+            username: "admin".to_string(),
+            admin: true,
+        }
+    }
+
+    #[test]
+    fn running_domains_migrate_live_by_default() {
+        let (flags, mode, options) =
+            plan(&request(serde_json::json!({"host": "b"})), true, true).unwrap();
+        assert_eq!(mode, "live");
+        assert_eq!(
+            flags,
+            sys::VIR_MIGRATE_LIVE
+                | sys::VIR_MIGRATE_PERSIST_DEST
+                | sys::VIR_MIGRATE_UNDEFINE_SOURCE
+        );
+        assert_eq!(options, "");
+    }
+
+    #[test]
+    fn suspended_migration_keeps_the_source() {
+        let req = request(serde_json::json!({"host": "b", "live": false, "keep_source": true}));
+        let (flags, mode, options) = plan(&req, true, true).unwrap();
+        assert_eq!(mode, "suspended");
+        assert_eq!(flags, sys::VIR_MIGRATE_PERSIST_DEST);
+        assert_eq!(options, "keep-source");
+    }
+
+    #[test]
+    fn transient_domains_are_not_persisted() {
+        let (flags, _, _) = plan(&request(serde_json::json!({"host": "b"})), true, false).unwrap();
+        assert_eq!(flags, sys::VIR_MIGRATE_LIVE);
+    }
+
+    #[test]
+    fn stopped_domains_migrate_offline() {
+        let (flags, mode, _) =
+            plan(&request(serde_json::json!({"host": "b"})), false, true).unwrap();
+        assert_eq!(mode, "offline");
+        assert_ne!(flags & sys::VIR_MIGRATE_OFFLINE, 0);
+        assert_eq!(flags & sys::VIR_MIGRATE_LIVE, 0);
+        assert!(plan(&request(serde_json::json!({"host": "b"})), false, false).is_err());
+        for option in ["copy_storage", "auto_converge", "post_copy"] {
+            let value = if option == "copy_storage" {
+                serde_json::json!("all")
+            } else {
+                serde_json::json!(true)
+            };
+            let req = request(serde_json::json!({"host": "b", option: value}));
+            assert!(plan(&req, false, true).is_err(), "{} offline", option);
+        }
+    }
+
+    #[test]
+    fn options_set_their_flags() {
+        let req = request(serde_json::json!({
+            "host": "b",
+            "peer_to_peer": true,
+            "copy_storage": "incremental",
+            "bandwidth": 100,
+            "auto_converge": true,
+            "post_copy": true,
+        }));
+        let (flags, mode, options) = plan(&req, true, true).unwrap();
+        assert_eq!(mode, "live");
+        for flag in [
+            sys::VIR_MIGRATE_NON_SHARED_INC,
+            sys::VIR_MIGRATE_AUTO_CONVERGE,
+            sys::VIR_MIGRATE_POSTCOPY,
+        ] {
+            assert_ne!(flags & flag, 0);
+        }
+        assert_eq!(flags & sys::VIR_MIGRATE_NON_SHARED_DISK, 0);
+        assert_eq!(
+            options,
+            "p2p copy-storage=incremental bandwidth=100MiB/s auto-converge post-copy"
+        );
+        let req = request(serde_json::json!({"host": "b", "copy_storage": "all"}));
+        let (flags, _, _) = plan(&req, true, true).unwrap();
+        assert_ne!(flags & sys::VIR_MIGRATE_NON_SHARED_DISK, 0);
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        for json in [
+            serde_json::json!({"host": "b", "copy_storage": "some"}),
+            serde_json::json!({"host": "b", "bandwidth": 0}),
+            serde_json::json!({"host": "b", "live": false, "post_copy": true}),
+        ] {
+            assert!(
+                plan(&request(json.clone()), true, true).is_err(),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_offline_migration_is_recorded() {
+        let state = test_state().await;
+        // Keeps the test driver's state alive between the worker's connections
+        let conn = Libvirt::open(TEST_URI).unwrap();
+        let dom = virt::domain::Domain::lookup_by_name(&conn, "test").unwrap();
+        if dom.is_active().unwrap() {
+            dom.destroy().unwrap();
+        }
+        let uuid = dom.get_uuid_string().unwrap();
+        let (flags, mode, options) =
+            plan(&request(serde_json::json!({"host": "b"})), false, true).unwrap();
+        let id = migrations::insert(
+            &state.pool,
+            &NewMigration {
+                domain_uuid: &uuid,
+                domain_name: "test",
+                destination: "b",
+                mode,
+                options: &options,
+                started_by: "admin",
+            },
+        )
+        .await
+        .unwrap();
+
+        run(
+            state.clone(),
+            id,
+            "test".to_string(),
+            Job {
+                uuid,
+                destination_uri: TEST_URI.to_string(),
+                peer_to_peer: false,
+                bandwidth: None,
+                flags,
+            },
+        );
+        let mut migration = migrations::get(&state.pool, id).await.unwrap().unwrap();
+        for _ in 0..100 {
+            if migration.status != "running" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            migration = migrations::get(&state.pool, id).await.unwrap().unwrap();
+        }
+        assert_eq!(migration.mode, "offline");
+        // The test driver has no migration support, so the worker has to
+        // record libvirt's error
+        assert_eq!(migration.status, "failed");
+        assert!(migration.error.is_some_and(|e| !e.is_empty()));
+        assert!(migration.finished_at.is_some());
+
+        // A finished migration can no longer be cancelled
+        let cancelled = cancel_migration(admin(), State(state.clone()), Path(id)).await;
+        assert_eq!(cancelled.unwrap_err().0, StatusCode::CONFLICT);
+        let missing = cancel_migration(admin(), State(state), Path(id + 1)).await;
+        assert_eq!(missing.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,62 +1,256 @@
-use axum::{Json, http::StatusCode};
-use serde::Serialize;
+use axum::{Json, extract::State, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::time::Instant;
 use virt::connect::Connect;
+use virt::domain::Domain;
+
+use crate::{AppState, auth::CurrentUser, telemetry};
+
+pub mod backups;
+pub mod cloudinit;
+pub mod console;
+pub mod devices;
+pub mod dhcp;
+pub mod domains;
+pub mod events;
+pub mod health;
+pub mod host;
+pub mod jobs;
+pub mod migrations;
+pub mod networks;
+pub mod nodedevs;
+pub mod nwfilters;
+pub mod pools;
+pub mod prometheus;
+pub mod secrets;
+pub mod snapshots;
+pub mod ssh_keys;
+pub mod stats;
+pub mod templates;
+pub mod volumes;
 
 // use virt::domain::DomainFlag; // DomainFlag is also likely in the `domain` module
+/// Result type of every API handler – errors become `status: message`.
+pub type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+// ---------------------------------------------------------------------
+// libvirt connection that is closed again when it goes out of scope
+// ---------------------------------------------------------------------
+pub struct Libvirt(Connect, Option<Instant>);
+
+impl Libvirt {
+    /// Open a connection to the hypervisor at `uri`.
+    pub fn open(uri: &str) -> Result<Libvirt, (StatusCode, String)> {
+        let mut conn = Libvirt::open_long_lived(uri)?;
+        conn.1 = Some(Instant::now());
+        Ok(conn)
+    }
+
+    /// Open a connection that stays open for a stream, console, migration
+    /// or event loop; it is left out of the connection lifetime histogram.
+    pub fn open_long_lived(uri: &str) -> Result<Libvirt, (StatusCode, String)> {
+        Connect::open(Some(uri))
+            .map(|conn| Libvirt(conn, None))
+            .map_err(|e| {
+                telemetry::libvirt_error(&format!("{:?}", e.code()));
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            })
+    }
+}
+
+impl Deref for Libvirt {
+    type Target = Connect;
+
+    fn deref(&self) -> &Connect {
+        &self.0
+    }
+}
+
+impl Drop for Libvirt {
+    // `virt::connect::Connect` does not close itself, so without this every
+    // request would leak a connection to libvirtd.
+    fn drop(&mut self) {
+        let _ = self.0.close();
+        if let Some(opened) = self.1 {
+            telemetry::libvirt_connection(opened.elapsed());
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Map a libvirt error to a response: missing objects are 404, operations
+// that do not fit the object's current state are 409, bad input and
+// configurations the hypervisor cannot apply (e.g. hot-plugging on a bus
+// without hot-plug) are 400, a guest that does not answer in time is 504.
+// Only libvirt's own message is returned, without the code/domain suffix.
+// ---------------------------------------------------------------------
+pub fn libvirt_error(e: virt::error::Error) -> (StatusCode, String) {
+    use virt::error::ErrorNumber as E;
+    telemetry::libvirt_error(&format!("{:?}", e.code()));
+    let code = match e.code() {
+        E::NoDomain
+        | E::NoNetwork
+        | E::NoStoragePool
+        | E::NoStorageVolume
+        | E::NoNodeDevice
+        | E::NoNwfilter
+        | E::NoNwfilterBinding
+        | E::NoSecret
+        | E::NoDomainSnapshot
+        | E::NoDomainCheckpoint
+        | E::NoDomainBackup => StatusCode::NOT_FOUND,
+        E::OperationInvalid | E::DomExist | E::NetworkExist | E::StorageVolExist => {
+            StatusCode::CONFLICT
+        }
+        E::InvalidArg
+        | E::XmlError
+        | E::XmlDetail
+        | E::InvalidMac
+        | E::ConfigUnsupported
+        | E::ArgumentUnsupported => StatusCode::BAD_REQUEST,
+        E::NoSupport | E::OperationUnsupported => StatusCode::NOT_IMPLEMENTED,
+        E::OperationTimeout => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (code, e.message().to_string())
+}
+
+/// Any other failure (database, XML parsing, …) as a 500 response.
+pub fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// Body of the `…/autostart` endpoints.
+#[derive(Deserialize)]
+pub struct Autostart {
+    pub autostart: bool,
+}
+
+/// Which definition of a domain a device change applies to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceScope {
+    /// The running domain only
+    Live,
+    /// The persistent definition only – takes effect on the next boot
+    Config,
+    /// The running domain and its persistent definition
+    Both,
+    /// The running domain if it runs, otherwise the definition
+    #[default]
+    Current,
+}
+
+impl DeviceScope {
+    /// `virDomainModificationImpact` flags for this scope.
+    pub fn flags(self) -> u32 {
+        use virt::sys;
+        match self {
+            DeviceScope::Live => sys::VIR_DOMAIN_AFFECT_LIVE,
+            DeviceScope::Config => sys::VIR_DOMAIN_AFFECT_CONFIG,
+            DeviceScope::Both => sys::VIR_DOMAIN_AFFECT_LIVE | sys::VIR_DOMAIN_AFFECT_CONFIG,
+            DeviceScope::Current => sys::VIR_DOMAIN_AFFECT_CURRENT,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DeviceScope::Live => "live",
+            DeviceScope::Config => "config",
+            DeviceScope::Both => "both",
+            DeviceScope::Current => "current",
+        }
+    }
+}
+
+/// Refuse user input with NUL bytes before it reaches libvirt: the virt
+/// crate unwraps `CString::new` on every string it passes on and would
+/// panic instead. The error reads "invalid `what`".
+pub fn reject_nul(value: &str, what: &str) -> ApiResult<()> {
+    if value.contains('\0') {
+        return Err((StatusCode::BAD_REQUEST, format!("invalid {}", what)));
+    }
+    Ok(())
+}
+
+/// Escape text for use inside XML attribute values and element content.
+pub fn xml_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\'' => out.push_str("&apos;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 // ---------------------------------------------------------------------
 // Domain information returned as JSON
 // ---------------------------------------------------------------------
 #[derive(Serialize)]
 pub struct DomainInfo {
-    name: String,
-    full_name: String,
-    uuid: String,
-    state: String,
-    time: u64,
-    memory: u64,
-    max_mem: u64,
+    pub name: String,
+    pub full_name: String,
+    pub uuid: String,
+    pub state: String,
+    pub time: u64,
+    pub memory: u64,
+    pub max_mem: u64,
+}
+
+/// Human readable name of a `virDomainState`.
+pub fn domain_state_name(state: u32) -> &'static str {
+    match state {
+        0 => "No State",
+        1 => "Running",
+        2 => "Blocked",
+        3 => "Paused",
+        4 => "Shutdown",
+        5 => "Shutoff",
+        6 => "Crashed",
+        7 => "Suspended",
+        _ => "Unknown",
+    }
+}
+
+pub fn domain_info(dom: &Domain) -> Result<DomainInfo, virt::error::Error> {
+    let info = dom.get_info()?;
+    Ok(DomainInfo {
+        name: dom.get_name()?,
+        full_name: dom.get_hostname(0).unwrap_or_default(),
+        uuid: dom.get_uuid_string()?,
+        state: domain_state_name(info.state).to_string(),
+        time: info.cpu_time,
+        memory: info.memory,
+        max_mem: info.max_mem,
+    })
 }
 
 // ---------------------------------------------------------------------
 // GET /api/domains – query libvirt for all domains
 // ---------------------------------------------------------------------
-pub async fn get_domains() -> Result<Json<Vec<DomainInfo>>, (StatusCode, String)> {
+pub async fn get_domains(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DomainInfo>>, (StatusCode, String)> {
     // 1. Open a connection to the local hypervisor (qemu)
-    let conn = Connect::open(Some("qemu:///system"))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
 
     // 2. List all domains (including inactive ones)
+    let domains = conn.list_all_domains(0).map_err(libvirt_error)?;
 
-    let domains = conn.list_all_domains(0).unwrap();
     // 3. Build a serialisable vector
-    let mut out = Vec::new();
-    for dom in domains {
-        let name = dom.get_name().unwrap();
-        let uuid = dom.get_uuid_string().unwrap();
-
-        let info = dom.get_info().unwrap();
-        let state = match info.state {
-            0 => &"No State",
-            1 => &"Running",
-            2 => &"Blocked",
-            3 => &"Paused",
-            4 => &"Shutdown",
-            5 => &"Shutoff",
-            6 => &"Crashed",
-            7 => &"Suspended",
-            _ => &"Unknown",
-        };
-
-        out.push(DomainInfo {
-            name,
-            full_name: dom.get_hostname(0).unwrap_or_default(),
-            uuid,
-            state: state.to_string(),
-            time: info.cpu_time,
-            memory: info.memory,
-            max_mem: info.max_mem,
-        });
-    }
+    let out = domains
+        .iter()
+        .map(domain_info)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(libvirt_error)?;
 
     Ok(Json(out))
 }
@@ -0,0 +1,292 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use virt::network::Network;
+
+use super::{ApiResult, Autostart, Libvirt, libvirt_error, reject_nul};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Network information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub uuid: String,
+    pub active: bool,
+    pub persistent: bool,
+    pub autostart: bool,
+    pub bridge: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NetworkDetail {
+    #[serde(flatten)]
+    pub info: NetworkInfo,
+    pub parsed: ParsedNetwork,
+    pub xml: String,
+}
+
+/// The interesting parts of a `<network>` definition.
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedNetwork {
+    pub bridge: Option<String>,
+    pub forward_mode: Option<String>,
+    pub ips: Vec<NetworkIp>,
+    pub dns_hosts: Vec<DnsHostEntry>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct NetworkIp {
+    pub family: String,
+    pub address: Option<String>,
+    pub netmask: Option<String>,
+    pub prefix: Option<u8>,
+    pub dhcp_ranges: Vec<DhcpRange>,
+    pub hosts: Vec<DhcpHost>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DhcpRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// A static `<host>` entry inside `<dhcp>`.
+#[derive(Debug, Serialize)]
+pub struct DhcpHost {
+    pub mac: Option<String>,
+    pub name: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// A `<host>` entry inside `<dns>`.
+#[derive(Debug, Serialize)]
+pub struct DnsHostEntry {
+    pub ip: String,
+    pub hostnames: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DefineNetwork {
+    xml: String,
+    #[serde(default)]
+    start: bool,
+    #[serde(default)]
+    autostart: bool,
+}
+
+// ---------------------------------------------------------------------
+// Parse network XML – bridge, forward mode, addresses and DHCP config
+// ---------------------------------------------------------------------
+pub fn parse_network_xml(xml: &str) -> Result<ParsedNetwork, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let child = |name: &str| root.children().find(|n| n.has_tag_name(name));
+
+    let ips = root
+        .children()
+        .filter(|n| n.has_tag_name("ip"))
+        .map(|ip| {
+            let dhcp = ip.children().find(|n| n.has_tag_name("dhcp"));
+            let dhcp_children = || dhcp.into_iter().flat_map(|d| d.children());
+            NetworkIp {
+                family: ip.attribute("family").unwrap_or("ipv4").to_string(),
+                address: ip.attribute("address").map(str::to_string),
+                netmask: ip.attribute("netmask").map(str::to_string),
+                prefix: ip.attribute("prefix").and_then(|p| p.parse().ok()),
+                dhcp_ranges: dhcp_children()
+                    .filter(|n| n.has_tag_name("range"))
+                    .filter_map(|r| {
+                        Some(DhcpRange {
+                            start: r.attribute("start")?.to_string(),
+                            end: r.attribute("end")?.to_string(),
+                        })
+                    })
+                    .collect(),
+                hosts: dhcp_children()
+                    .filter(|n| n.has_tag_name("host"))
+                    .map(|h| DhcpHost {
+                        mac: h.attribute("mac").map(str::to_string),
+                        name: h.attribute("name").map(str::to_string),
+                        ip: h.attribute("ip").map(str::to_string),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(ParsedNetwork {
+        bridge: child("bridge")
+            .and_then(|b| b.attribute("name"))
+            .map(str::to_string),
+        forward_mode: child("forward").map(|f| f.attribute("mode").unwrap_or("nat").to_string()),
+        ips,
+        dns_hosts: child("dns")
+            .into_iter()
+            .flat_map(|dns| dns.children())
+            .filter(|n| n.has_tag_name("host"))
+            .map(|h| DnsHostEntry {
+                ip: h.attribute("ip").unwrap_or_default().to_string(),
+                hostnames: h
+                    .children()
+                    .filter(|n| n.has_tag_name("hostname"))
+                    .filter_map(|n| n.text())
+                    .map(str::to_string)
+                    .collect(),
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn network_info(net: &Network) -> ApiResult<NetworkInfo> {
+    Ok(NetworkInfo {
+        name: net.get_name().map_err(libvirt_error)?,
+        uuid: net.get_uuid_string().map_err(libvirt_error)?,
+        active: net.is_active().map_err(libvirt_error)?,
+        persistent: net.is_persistent().map_err(libvirt_error)?,
+        autostart: net.get_autostart().unwrap_or(false),
+        // Isolated/hostdev networks have no bridge
+        bridge: net.get_bridge_name().ok(),
+    })
+}
+
+pub(crate) fn lookup(conn: &Libvirt, name: &str) -> ApiResult<Network> {
+    reject_nul(name, "network name")?;
+    Network::lookup_by_name(conn, name).map_err(libvirt_error)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/networks – all networks, active and inactive
+// ---------------------------------------------------------------------
+pub async fn list_networks(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<NetworkInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let networks = conn.list_all_networks(0).map_err(libvirt_error)?;
+    let out = networks
+        .iter()
+        .map(network_info)
+        .collect::<ApiResult<Vec<_>>>()?;
+    Ok(Json(out))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/networks/{name} – XML plus the parsed highlights
+// ---------------------------------------------------------------------
+pub async fn get_network(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<NetworkDetail>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    let xml = net.get_xml_desc(0).map_err(libvirt_error)?;
+    let parsed =
+        parse_network_xml(&xml).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(NetworkDetail {
+        info: network_info(&net)?,
+        parsed,
+        xml,
+    }))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/networks – define a persistent network from XML
+// ---------------------------------------------------------------------
+pub async fn define_network(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<DefineNetwork>,
+) -> ApiResult<(StatusCode, Json<NetworkInfo>)> {
+    reject_nul(&req.xml, "network XML")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = Network::define_xml(&conn, &req.xml).map_err(libvirt_error)?;
+    if req.autostart {
+        net.set_autostart(true).map_err(libvirt_error)?;
+    }
+    if req.start {
+        net.create().map_err(libvirt_error)?;
+    }
+    let info = network_info(&net)?;
+    audit::record(&state.pool, &user.username, "network.define", &info.name).await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/networks/{name} – undefine (an active network keeps running
+// as a transient network until stopped)
+// ---------------------------------------------------------------------
+pub async fn undefine_network(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name)?.undefine().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "network.undefine", &name).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/networks/{name}/start
+// ---------------------------------------------------------------------
+pub async fn start_network(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<NetworkInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    net.create().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "network.start", &name).await;
+    Ok(Json(network_info(&net)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/networks/{name}/stop
+// ---------------------------------------------------------------------
+pub async fn stop_network(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<NetworkInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    net.destroy().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "network.stop", &name).await;
+    // A transient network disappears once stopped
+    match network_info(&net) {
+        Ok(info) => Ok(Json(info)),
+        Err(_) => Err((
+            StatusCode::GONE,
+            format!("network {} no longer exists", name),
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/networks/{name}/autostart – {"autostart": true|false}
+// ---------------------------------------------------------------------
+pub async fn set_network_autostart(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<Autostart>,
+) -> ApiResult<Json<NetworkInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let net = lookup(&conn, &name)?;
+    net.set_autostart(req.autostart).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "network.autostart",
+        &format!("{} {}", name, req.autostart),
+    )
+    .await;
+    Ok(Json(network_info(&net)?))
+}
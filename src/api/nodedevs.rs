@@ -0,0 +1,553 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use virt::{nodedev::NodeDevice, sys};
+
+use super::domains::lookup as lookup_domain;
+use super::{
+    ApiResult, DeviceScope, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape,
+};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Node device information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct NodeDeviceInfo {
+    pub name: String,
+    pub parent: Option<String>,
+    /// Capability types, e.g. `pci` or `usb_device`
+    pub capabilities: Vec<String>,
+    #[serde(flatten)]
+    pub parsed: ParsedNodeDevice,
+    /// Domains whose definition already contains the device
+    pub assigned_to: Vec<Assignment>,
+}
+
+#[derive(Serialize)]
+pub struct NodeDeviceDetail {
+    #[serde(flatten)]
+    pub info: NodeDeviceInfo,
+    pub xml: String,
+}
+
+/// The interesting parts of a `<device>` description.
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedNodeDevice {
+    /// Host driver currently bound, e.g. `vfio-pci` once detached
+    pub driver: Option<String>,
+    pub vendor: Option<String>,
+    pub product: Option<String>,
+    pub iommu_group: Option<u32>,
+    /// Network interface name of `net` devices
+    pub interface: Option<String>,
+    /// MAC address of `net` devices
+    pub mac: Option<String>,
+    /// What a `<hostdev>` refers to; `None` for devices that cannot be
+    /// passed through directly
+    pub source: Option<HostdevSource>,
+}
+
+/// Address of a device as used inside `<hostdev><source>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HostdevSource {
+    Pci {
+        domain: u32,
+        bus: u32,
+        slot: u32,
+        function: u32,
+    },
+    Usb {
+        vendor: Option<u32>,
+        product: Option<u32>,
+        bus: Option<u32>,
+        device: Option<u32>,
+    },
+    Scsi {
+        adapter: String,
+        bus: u32,
+        target: u32,
+        unit: u32,
+    },
+    Mdev {
+        uuid: String,
+    },
+}
+
+/// A domain a device is assigned to.
+#[derive(Debug, Clone, Serialize)]
+pub struct Assignment {
+    pub domain: String,
+    pub uuid: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeviceFilter {
+    /// `pci`, `usb_device`, `net`, `scsi` or `mdev`; all devices if omitted
+    #[serde(default)]
+    cap: Option<String>,
+}
+
+/// Body of `POST /api/domains/{uuid}/hostdevs`.
+#[derive(Deserialize)]
+pub struct AttachHostdev {
+    /// Node device name, e.g. `pci_0000_01_00_0`
+    device: String,
+    #[serde(default)]
+    scope: DeviceScope,
+    /// Let libvirt detach the device from its host driver and reattach it
+    /// when the guest releases it (PCI and USB)
+    #[serde(default = "default_managed")]
+    managed: bool,
+    /// mdev device model; `vfio-pci` unless given
+    #[serde(default)]
+    model: Option<String>,
+}
+
+fn default_managed() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct DetachHostdev {
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+impl HostdevSource {
+    /// Whether a `<hostdev>` source found in a domain refers to this device.
+    /// USB devices match by vendor/product when both sides know them.
+    fn matches(&self, other: &HostdevSource) -> bool {
+        match (self, other) {
+            (
+                HostdevSource::Usb {
+                    vendor: v1,
+                    product: p1,
+                    bus: b1,
+                    device: d1,
+                },
+                HostdevSource::Usb {
+                    vendor: v2,
+                    product: p2,
+                    bus: b2,
+                    device: d2,
+                },
+            ) => {
+                if v1.is_some() && v2.is_some() && p1.is_some() && p2.is_some() {
+                    v1 == v2 && p1 == p2
+                } else {
+                    b1.is_some() && b1 == b2 && d1 == d2
+                }
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// `<hostdev>` element that passes this device through.
+    fn hostdev_xml(&self, managed: bool, model: Option<&str>) -> String {
+        let managed = if managed { "yes" } else { "no" };
+        match self {
+            HostdevSource::Pci {
+                domain,
+                bus,
+                slot,
+                function,
+            } => format!(
+                "<hostdev mode='subsystem' type='pci' managed='{managed}'><source>\
+                 <address domain='0x{domain:04x}' bus='0x{bus:02x}' slot='0x{slot:02x}' \
+                 function='0x{function:x}'/></source></hostdev>"
+            ),
+            HostdevSource::Usb {
+                vendor,
+                product,
+                bus,
+                device,
+            } => {
+                let mut source = String::new();
+                if let (Some(vendor), Some(product)) = (vendor, product) {
+                    source.push_str(&format!(
+                        "<vendor id='0x{vendor:04x}'/><product id='0x{product:04x}'/>"
+                    ));
+                } else if let (Some(bus), Some(device)) = (bus, device) {
+                    source.push_str(&format!("<address bus='{bus}' device='{device}'/>"));
+                }
+                format!(
+                    "<hostdev mode='subsystem' type='usb' managed='{managed}'>\
+                     <source>{source}</source></hostdev>"
+                )
+            }
+            HostdevSource::Scsi {
+                adapter,
+                bus,
+                target,
+                unit,
+            } => format!(
+                "<hostdev mode='subsystem' type='scsi'><source>\
+                 <adapter name='{}'/><address bus='{bus}' target='{target}' unit='{unit}'/>\
+                 </source></hostdev>",
+                xml_escape(adapter)
+            ),
+            HostdevSource::Mdev { uuid } => format!(
+                "<hostdev mode='subsystem' type='mdev' model='{}'><source>\
+                 <address uuid='{}'/></source></hostdev>",
+                xml_escape(model.unwrap_or("vfio-pci")),
+                xml_escape(uuid)
+            ),
+        }
+    }
+}
+
+/// Numbers in device XML are decimal in node devices and usually hex
+/// (`0x…`) in domain definitions.
+fn parse_int(value: &str) -> Option<u32> {
+    let value = value.trim();
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text(node: roxmltree::Node<'_, '_>, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+fn child_int(node: roxmltree::Node<'_, '_>, name: &str) -> Option<u32> {
+    child_text(node, name).and_then(|t| parse_int(&t))
+}
+
+// ---------------------------------------------------------------------
+// Parse node device XML – driver, vendor/product and hostdev address
+// ---------------------------------------------------------------------
+pub fn parse_nodedev_xml(xml: &str) -> Result<ParsedNodeDevice, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let mut parsed = ParsedNodeDevice {
+        driver: child(root, "driver").and_then(|d| child_text(d, "name")),
+        ..Default::default()
+    };
+    let name = child_text(root, "name").unwrap_or_default();
+
+    for cap in root.children().filter(|n| n.has_tag_name("capability")) {
+        let id = |name: &str| child(cap, name).and_then(|n| n.attribute("id"));
+        parsed.vendor = parsed.vendor.or(child_text(cap, "vendor"));
+        parsed.product = parsed.product.or(child_text(cap, "product"));
+        parsed.iommu_group = parsed.iommu_group.or(child(cap, "iommuGroup")
+            .and_then(|g| g.attribute("number"))
+            .and_then(parse_int));
+        let source = match cap.attribute("type") {
+            Some("pci") => Some(HostdevSource::Pci {
+                domain: child_int(cap, "domain").unwrap_or(0),
+                bus: child_int(cap, "bus").unwrap_or(0),
+                slot: child_int(cap, "slot").unwrap_or(0),
+                function: child_int(cap, "function").unwrap_or(0),
+            }),
+            Some("usb_device") => Some(HostdevSource::Usb {
+                vendor: id("vendor").and_then(parse_int),
+                product: id("product").and_then(parse_int),
+                bus: child_int(cap, "bus"),
+                device: child_int(cap, "device"),
+            }),
+            Some("scsi") => Some(HostdevSource::Scsi {
+                adapter: format!("scsi_host{}", child_int(cap, "host").unwrap_or(0)),
+                bus: child_int(cap, "bus").unwrap_or(0),
+                target: child_int(cap, "target").unwrap_or(0),
+                unit: child_int(cap, "lun").unwrap_or(0),
+            }),
+            Some("mdev") => Some(HostdevSource::Mdev {
+                // Older libvirt only has the UUID in the name, `mdev_<uuid>`
+                uuid: child_text(cap, "uuid").unwrap_or_else(|| {
+                    name.trim_start_matches("mdev_")
+                        .chars()
+                        .take(36)
+                        .collect::<String>()
+                        .replace('_', "-")
+                }),
+            }),
+            Some("net") => {
+                parsed.interface = child_text(cap, "interface");
+                parsed.mac = child_text(cap, "address");
+                None
+            }
+            _ => None,
+        };
+        if parsed.source.is_none() {
+            parsed.source = source;
+        }
+    }
+    Ok(parsed)
+}
+
+/// `<hostdev>` sources in a domain definition.
+pub fn parse_domain_hostdevs(xml: &str) -> Result<Vec<HostdevSource>, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let hostdevs = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("hostdev") && n.attribute("mode") != Some("capabilities"))
+        .filter_map(|hostdev| {
+            let source = child(hostdev, "source")?;
+            let address = child(source, "address");
+            let attr = |name: &str| address.and_then(|a| a.attribute(name)).and_then(parse_int);
+            match hostdev.attribute("type")? {
+                "pci" => Some(HostdevSource::Pci {
+                    domain: attr("domain").unwrap_or(0),
+                    bus: attr("bus")?,
+                    slot: attr("slot")?,
+                    function: attr("function").unwrap_or(0),
+                }),
+                "usb" => Some(HostdevSource::Usb {
+                    vendor: child(source, "vendor")
+                        .and_then(|v| v.attribute("id"))
+                        .and_then(parse_int),
+                    product: child(source, "product")
+                        .and_then(|p| p.attribute("id"))
+                        .and_then(parse_int),
+                    bus: attr("bus"),
+                    device: attr("device"),
+                }),
+                "scsi" => Some(HostdevSource::Scsi {
+                    adapter: child(source, "adapter")?.attribute("name")?.to_string(),
+                    bus: attr("bus").unwrap_or(0),
+                    target: attr("target").unwrap_or(0),
+                    unit: attr("unit").unwrap_or(0),
+                }),
+                "mdev" => Some(HostdevSource::Mdev {
+                    uuid: address?.attribute("uuid")?.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .collect();
+    Ok(hostdevs)
+}
+
+/// Every `<hostdev>` of every domain, live or in the saved definition.
+pub fn hostdev_assignments(conn: &Libvirt) -> ApiResult<Vec<(HostdevSource, Assignment)>> {
+    let mut out: Vec<(HostdevSource, Assignment)> = Vec::new();
+    for dom in conn.list_all_domains(0).map_err(libvirt_error)? {
+        let assignment = Assignment {
+            domain: dom.get_name().map_err(libvirt_error)?,
+            uuid: dom.get_uuid_string().map_err(libvirt_error)?,
+        };
+        let mut xmls = vec![dom.get_xml_desc(0).map_err(libvirt_error)?];
+        if dom.is_active().unwrap_or(false) && dom.is_persistent().unwrap_or(false) {
+            xmls.push(
+                dom.get_xml_desc(sys::VIR_DOMAIN_XML_INACTIVE)
+                    .map_err(libvirt_error)?,
+            );
+        }
+        for xml in xmls {
+            for source in parse_domain_hostdevs(&xml).map_err(internal_error)? {
+                let known = out
+                    .iter()
+                    .any(|(s, a)| a.uuid == assignment.uuid && *s == source);
+                if !known {
+                    out.push((source, assignment.clone()));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// `virConnectListAllNodeDevices` flag of a capability name.
+fn capability_flag(cap: &str) -> ApiResult<sys::virConnectListAllNodeDeviceFlags> {
+    Ok(match cap {
+        "pci" => sys::VIR_CONNECT_LIST_NODE_DEVICES_CAP_PCI_DEV,
+        "usb_device" => sys::VIR_CONNECT_LIST_NODE_DEVICES_CAP_USB_DEV,
+        "net" => sys::VIR_CONNECT_LIST_NODE_DEVICES_CAP_NET,
+        "scsi" => sys::VIR_CONNECT_LIST_NODE_DEVICES_CAP_SCSI,
+        "mdev" => sys::VIR_CONNECT_LIST_NODE_DEVICES_CAP_MDEV,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "cap must be pci, usb_device, net, scsi or mdev".into(),
+            ));
+        }
+    })
+}
+
+pub(crate) fn nodedev_info(
+    dev: &NodeDevice,
+    assignments: &[(HostdevSource, Assignment)],
+) -> ApiResult<NodeDeviceInfo> {
+    let xml = dev.get_xml_desc(0).map_err(libvirt_error)?;
+    let parsed = parse_nodedev_xml(&xml).map_err(internal_error)?;
+    let assigned_to = match &parsed.source {
+        Some(source) => assignments
+            .iter()
+            .filter(|(s, _)| source.matches(s))
+            .map(|(_, a)| a.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(NodeDeviceInfo {
+        name: dev.get_name().map_err(libvirt_error)?,
+        // The root device has no parent
+        parent: dev.get_parent().ok(),
+        capabilities: dev.list_caps().map_err(libvirt_error)?,
+        parsed,
+        assigned_to,
+    })
+}
+
+pub(crate) fn lookup(conn: &Libvirt, name: &str) -> ApiResult<NodeDevice> {
+    reject_nul(name, "device name")?;
+    NodeDevice::lookup_by_name(conn, name).map_err(libvirt_error)
+}
+
+/// All node devices with the capability `cap`, with their assignments.
+pub fn list_nodedevs(conn: &Libvirt, cap: Option<&str>) -> ApiResult<Vec<NodeDeviceInfo>> {
+    let flags = cap.map(capability_flag).transpose()?.unwrap_or(0);
+    let devices = conn.list_all_node_devices(flags).map_err(libvirt_error)?;
+    let assignments = hostdev_assignments(conn)?;
+    let mut out = devices
+        .iter()
+        .map(|dev| nodedev_info(dev, &assignments))
+        .collect::<ApiResult<Vec<_>>>()?;
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(out)
+}
+
+/// The node device's hostdev address, or 400 for devices without one.
+fn hostdev_source(dev: &NodeDevice, name: &str) -> ApiResult<HostdevSource> {
+    let xml = dev.get_xml_desc(0).map_err(libvirt_error)?;
+    parse_nodedev_xml(&xml)
+        .map_err(internal_error)?
+        .source
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{} cannot be passed through; use its PCI parent instead",
+                    name
+                ),
+            )
+        })
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nodedevs?cap=pci – host devices by capability
+// ---------------------------------------------------------------------
+pub async fn get_nodedevs(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Query(filter): Query<DeviceFilter>,
+) -> ApiResult<Json<Vec<NodeDeviceInfo>>> {
+    let uri = state.config.libvirt_uri.clone();
+    let devices = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        list_nodedevs(&conn, filter.cap.as_deref())
+    })
+    .await
+    .map_err(internal_error)??;
+    Ok(Json(devices))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nodedevs/{name}
+// ---------------------------------------------------------------------
+pub async fn get_nodedev(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<NodeDeviceDetail>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dev = lookup(&conn, &name)?;
+    let assignments = hostdev_assignments(&conn)?;
+    Ok(Json(NodeDeviceDetail {
+        info: nodedev_info(&dev, &assignments)?,
+        xml: dev.get_xml_desc(0).map_err(libvirt_error)?,
+    }))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/nodedevs/{name}/detach – unbind a PCI device from its host
+// driver and bind it to vfio-pci
+// ---------------------------------------------------------------------
+pub async fn detach_nodedev(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name)?
+        .detach_flags(None, 0)
+        .map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "nodedev.detach", &name).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/nodedevs/{name}/reattach – give a PCI device back to the host
+// ---------------------------------------------------------------------
+pub async fn reattach_nodedev(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name)?.reattach().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "nodedev.reattach", &name).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/hostdevs – pass a node device through
+// ---------------------------------------------------------------------
+pub async fn attach_hostdev(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<AttachHostdev>,
+) -> ApiResult<StatusCode> {
+    reject_nul(req.model.as_deref().unwrap_or_default(), "model")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    let source = hostdev_source(&lookup(&conn, &req.device)?, &req.device)?;
+    let xml = source.hostdev_xml(req.managed, req.model.as_deref());
+    dom.attach_device_flags(&xml, req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.hostdev_attach",
+        &format!("{} {} {}", uuid, req.device, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/hostdevs/{device}?scope=both
+// ---------------------------------------------------------------------
+pub async fn detach_hostdev(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, device)): Path<(String, String)>,
+    Query(req): Query<DetachHostdev>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    let source = hostdev_source(&lookup(&conn, &device)?, &device)?;
+    // libvirt finds the hostdev to remove by its source address
+    dom.detach_device_flags(&source.hostdev_xml(true, None), req.scope.flags())
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.hostdev_detach",
+        &format!("{} {} {}", uuid, device, req.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
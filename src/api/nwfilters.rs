@@ -0,0 +1,527 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use virt::{error::Error, nwfilter::NWFilter, sys};
+
+use super::domains::lookup as lookup_domain;
+use super::{
+    ApiResult, DeviceScope, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape,
+};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Filter and binding information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct NwFilterInfo {
+    pub name: String,
+    pub uuid: String,
+    #[serde(flatten)]
+    pub parsed: ParsedNwFilter,
+}
+
+#[derive(Serialize)]
+pub struct NwFilterDetail {
+    #[serde(flatten)]
+    pub info: NwFilterInfo,
+    pub xml: String,
+}
+
+/// The interesting parts of a `<filter>` definition.
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedNwFilter {
+    pub chain: Option<String>,
+    pub priority: Option<i32>,
+    /// Filters pulled in with `<filterref>`
+    pub references: Vec<String>,
+    pub rules: usize,
+}
+
+/// A filter instantiated on a guest's tap device.
+#[derive(Debug, Default, Serialize)]
+pub struct NwFilterBindingInfo {
+    pub portdev: String,
+    pub filter: String,
+    pub owner_name: Option<String>,
+    pub owner_uuid: Option<String>,
+    pub mac: Option<String>,
+    pub parameters: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct NwFilterBindingDetail {
+    #[serde(flatten)]
+    pub info: NwFilterBindingInfo,
+    pub xml: String,
+}
+
+/// Body of the define endpoints – the full XML document.
+#[derive(Deserialize)]
+pub struct DefineXml {
+    xml: String,
+}
+
+/// Body of `PUT /api/domains/{uuid}/interfaces/{mac}/filter`.
+#[derive(Deserialize)]
+pub struct InterfaceFilter {
+    filter: String,
+    /// Filter variables, e.g. `{"IP": ["192.0.2.10"]}`; a variable may be
+    /// given several times
+    #[serde(default)]
+    parameters: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+#[derive(Deserialize)]
+pub struct ScopeQuery {
+    #[serde(default)]
+    scope: DeviceScope,
+}
+
+/// Owned `virNWFilterBindingPtr` – the virt crate has no binding API.
+struct NwFilterBinding(sys::virNWFilterBindingPtr);
+
+impl Drop for NwFilterBinding {
+    fn drop(&mut self) {
+        unsafe { sys::virNWFilterBindingFree(self.0) };
+    }
+}
+
+impl NwFilterBinding {
+    fn list(conn: &Libvirt) -> Result<Vec<NwFilterBinding>, Error> {
+        let mut list: *mut sys::virNWFilterBindingPtr = std::ptr::null_mut();
+        let size = unsafe { sys::virConnectListAllNWFilterBindings(conn.as_ptr(), &mut list, 0) };
+        if size == -1 {
+            return Err(Error::last_error());
+        }
+        // SAFETY: libvirt returned `size` bindings which we now own; the
+        // array itself is freed here, each binding when it is dropped.
+        let mut out = Vec::with_capacity(size as usize);
+        unsafe {
+            for i in 0..size as isize {
+                out.push(NwFilterBinding(*list.offset(i)));
+            }
+            if !list.is_null() {
+                libc::free(list as *mut libc::c_void);
+            }
+        }
+        Ok(out)
+    }
+
+    fn lookup(conn: &Libvirt, portdev: &str) -> ApiResult<NwFilterBinding> {
+        reject_nul(portdev, "port device")?;
+        let portdev = CString::new(portdev).unwrap_or_default();
+        let ptr =
+            unsafe { sys::virNWFilterBindingLookupByPortDev(conn.as_ptr(), portdev.as_ptr()) };
+        if ptr.is_null() {
+            return Err(libvirt_error(Error::last_error()));
+        }
+        Ok(NwFilterBinding(ptr))
+    }
+
+    fn create(conn: &Libvirt, xml: &str) -> ApiResult<NwFilterBinding> {
+        reject_nul(xml, "binding XML")?;
+        let xml = CString::new(xml).unwrap_or_default();
+        let ptr = unsafe { sys::virNWFilterBindingCreateXML(conn.as_ptr(), xml.as_ptr(), 0) };
+        if ptr.is_null() {
+            return Err(libvirt_error(Error::last_error()));
+        }
+        Ok(NwFilterBinding(ptr))
+    }
+
+    fn xml(&self) -> Result<String, Error> {
+        let xml = unsafe { sys::virNWFilterBindingGetXMLDesc(self.0, 0) };
+        if xml.is_null() {
+            return Err(Error::last_error());
+        }
+        let out = unsafe { CStr::from_ptr(xml) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { libc::free(xml as *mut libc::c_void) };
+        Ok(out)
+    }
+
+    fn delete(&self) -> Result<(), Error> {
+        if unsafe { sys::virNWFilterBindingDelete(self.0) } == -1 {
+            return Err(Error::last_error());
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------
+// Parse filter and binding XML
+// ---------------------------------------------------------------------
+pub fn parse_nwfilter_xml(xml: &str) -> Result<ParsedNwFilter, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    Ok(ParsedNwFilter {
+        chain: root.attribute("chain").map(str::to_string),
+        priority: root.attribute("priority").and_then(|p| p.parse().ok()),
+        references: root
+            .children()
+            .filter(|n| n.has_tag_name("filterref"))
+            .filter_map(|n| n.attribute("filter"))
+            .map(str::to_string)
+            .collect(),
+        rules: root.children().filter(|n| n.has_tag_name("rule")).count(),
+    })
+}
+
+/// `<parameter name=… value=…/>` children of a `<filterref>`.
+fn filter_parameters(filterref: roxmltree::Node<'_, '_>) -> BTreeMap<String, Vec<String>> {
+    let mut parameters: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for p in filterref.children().filter(|n| n.has_tag_name("parameter")) {
+        if let (Some(name), Some(value)) = (p.attribute("name"), p.attribute("value")) {
+            parameters
+                .entry(name.to_string())
+                .or_default()
+                .push(value.to_string());
+        }
+    }
+    parameters
+}
+
+pub fn parse_binding_xml(xml: &str) -> Result<NwFilterBindingInfo, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let child = |name: &str| root.children().find(|n| n.has_tag_name(name));
+    let owner = child("owner");
+    let owner_text = |name: &str| {
+        owner
+            .and_then(|o| o.children().find(|n| n.has_tag_name(name)))
+            .and_then(|n| n.text())
+            .map(str::to_string)
+    };
+    let filterref = child("filterref");
+    Ok(NwFilterBindingInfo {
+        portdev: child("portdev")
+            .and_then(|p| p.attribute("name"))
+            .unwrap_or_default()
+            .to_string(),
+        filter: filterref
+            .and_then(|f| f.attribute("filter"))
+            .unwrap_or_default()
+            .to_string(),
+        owner_name: owner_text("name"),
+        owner_uuid: owner_text("uuid"),
+        mac: child("mac")
+            .and_then(|m| m.attribute("address"))
+            .map(str::to_string),
+        parameters: filterref.map(filter_parameters).unwrap_or_default(),
+    })
+}
+
+/// `<filterref>` element with its parameters.
+fn filterref_xml(filter: &str, parameters: &BTreeMap<String, Vec<String>>) -> ApiResult<String> {
+    reject_nul(filter, "filter name")?;
+    let mut xml = format!("<filterref filter='{}'>", xml_escape(filter));
+    for (name, values) in parameters {
+        reject_nul(name, "parameter name")?;
+        for value in values {
+            reject_nul(value, "parameter value")?;
+            xml.push_str(&format!(
+                "<parameter name='{}' value='{}'/>",
+                xml_escape(name),
+                xml_escape(value)
+            ));
+        }
+    }
+    xml.push_str("</filterref>");
+    Ok(xml)
+}
+
+/// The `<interface>` with MAC address `mac` from a domain definition, with
+/// its `<filterref>` replaced by `filterref` (or removed for `None`).
+fn interface_with_filter(
+    domain_xml: &str,
+    mac: &str,
+    filterref: Option<&str>,
+) -> ApiResult<String> {
+    let doc = roxmltree::Document::parse(domain_xml).map_err(internal_error)?;
+    let iface = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("interface"))
+        .find(|n| {
+            n.children()
+                .find(|c| c.has_tag_name("mac"))
+                .and_then(|m| m.attribute("address"))
+                .is_some_and(|a| a.eq_ignore_ascii_case(mac))
+        })
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("domain has no interface with MAC {}", mac),
+        ))?;
+
+    // Edit the element's source text so everything libvirt does not show in
+    // our structs is kept as it is
+    let range = iface.range();
+    let mut xml = domain_xml[range.clone()].to_string();
+    if let Some(old) = iface.children().find(|n| n.has_tag_name("filterref")) {
+        let old = old.range();
+        xml.replace_range(old.start - range.start..old.end - range.start, "");
+    }
+    if let Some(filterref) = filterref {
+        let end = xml.rfind("</interface>").ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unexpected interface XML".to_string(),
+        ))?;
+        xml.insert_str(end, filterref);
+    }
+    Ok(xml)
+}
+
+/// Apply `filterref` to the interface `mac` of domain `uuid` in `scope`.
+fn update_interface_filter(
+    conn: &Libvirt,
+    uuid: &str,
+    mac: &str,
+    filterref: Option<&str>,
+    scope: DeviceScope,
+) -> ApiResult<()> {
+    let dom = lookup_domain(conn, uuid)?;
+    // The saved definition is the better base unless only the running
+    // domain is changed
+    let inactive = match scope {
+        DeviceScope::Live => false,
+        DeviceScope::Current => !dom.is_active().map_err(libvirt_error)?,
+        DeviceScope::Config | DeviceScope::Both => true,
+    };
+    let flags = if inactive {
+        sys::VIR_DOMAIN_XML_INACTIVE
+    } else {
+        0
+    };
+    let domain_xml = dom.get_xml_desc(flags).map_err(libvirt_error)?;
+    let xml = interface_with_filter(&domain_xml, mac, filterref)?;
+    dom.update_device_flags(&xml, scope.flags())
+        .map_err(libvirt_error)?;
+    Ok(())
+}
+
+pub(crate) fn nwfilter_info(filter: &NWFilter) -> ApiResult<NwFilterInfo> {
+    let xml = filter.get_xml_desc(0).map_err(libvirt_error)?;
+    Ok(NwFilterInfo {
+        name: filter.get_name().map_err(libvirt_error)?,
+        uuid: filter.get_uuid_string().map_err(libvirt_error)?,
+        parsed: parse_nwfilter_xml(&xml).map_err(internal_error)?,
+    })
+}
+
+pub(crate) fn lookup(conn: &Libvirt, name: &str) -> ApiResult<NWFilter> {
+    reject_nul(name, "filter name")?;
+    NWFilter::lookup_by_name(conn, name).map_err(libvirt_error)
+}
+
+/// All filters ordered by name.
+pub fn list_nwfilters(conn: &Libvirt) -> ApiResult<Vec<NwFilterInfo>> {
+    let filters = conn.list_all_nw_filters(0).map_err(libvirt_error)?;
+    let mut out = filters
+        .iter()
+        .map(nwfilter_info)
+        .collect::<ApiResult<Vec<_>>>()?;
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(out)
+}
+
+/// All bindings ordered by port device.
+pub fn list_bindings(conn: &Libvirt) -> ApiResult<Vec<NwFilterBindingInfo>> {
+    let bindings = NwFilterBinding::list(conn).map_err(libvirt_error)?;
+    let mut out = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        let xml = binding.xml().map_err(libvirt_error)?;
+        out.push(parse_binding_xml(&xml).map_err(internal_error)?);
+    }
+    out.sort_by(|a, b| a.portdev.cmp(&b.portdev));
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nwfilters
+// ---------------------------------------------------------------------
+pub async fn get_nwfilters(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<NwFilterInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(list_nwfilters(&conn)?))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nwfilters/{name} – XML plus the parsed highlights
+// ---------------------------------------------------------------------
+pub async fn get_nwfilter(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<NwFilterDetail>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let filter = lookup(&conn, &name)?;
+    Ok(Json(NwFilterDetail {
+        info: nwfilter_info(&filter)?,
+        xml: filter.get_xml_desc(0).map_err(libvirt_error)?,
+    }))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/nwfilters – define (or redefine) a filter from XML
+// ---------------------------------------------------------------------
+pub async fn define_nwfilter(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<DefineXml>,
+) -> ApiResult<(StatusCode, Json<NwFilterInfo>)> {
+    reject_nul(&req.xml, "filter XML")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let filter = NWFilter::define_xml(&conn, &req.xml).map_err(libvirt_error)?;
+    let info = nwfilter_info(&filter)?;
+    audit::record(&state.pool, &user.username, "nwfilter.define", &info.name).await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/nwfilters/{name} – fails while the filter is in use
+// ---------------------------------------------------------------------
+pub async fn undefine_nwfilter(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name)?.undefine().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "nwfilter.undefine", &name).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nwfilter-bindings
+// ---------------------------------------------------------------------
+pub async fn get_bindings(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<NwFilterBindingInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(list_bindings(&conn)?))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/nwfilter-bindings/{portdev}
+// ---------------------------------------------------------------------
+pub async fn get_binding(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(portdev): Path<String>,
+) -> ApiResult<Json<NwFilterBindingDetail>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let xml = NwFilterBinding::lookup(&conn, &portdev)?
+        .xml()
+        .map_err(libvirt_error)?;
+    Ok(Json(NwFilterBindingDetail {
+        info: parse_binding_xml(&xml).map_err(internal_error)?,
+        xml,
+    }))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/nwfilter-bindings – bind a filter to a port from XML
+// ---------------------------------------------------------------------
+pub async fn create_binding(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<DefineXml>,
+) -> ApiResult<(StatusCode, Json<NwFilterBindingInfo>)> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let xml = NwFilterBinding::create(&conn, &req.xml)?
+        .xml()
+        .map_err(libvirt_error)?;
+    let info = parse_binding_xml(&xml).map_err(internal_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "nwfilter_binding.create",
+        &format!("{} {}", info.portdev, info.filter),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/nwfilter-bindings/{portdev}
+// ---------------------------------------------------------------------
+pub async fn delete_binding(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(portdev): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    NwFilterBinding::lookup(&conn, &portdev)?
+        .delete()
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "nwfilter_binding.delete",
+        &portdev,
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/domains/{uuid}/interfaces/{mac}/filter – attach a filter,
+// e.g. {"filter": "clean-traffic", "parameters": {"IP": ["192.0.2.10"]}}
+// ---------------------------------------------------------------------
+pub async fn set_interface_filter(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, mac)): Path<(String, String)>,
+    Json(req): Json<InterfaceFilter>,
+) -> ApiResult<StatusCode> {
+    let filterref = filterref_xml(&req.filter, &req.parameters)?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    // Fail with 404 before touching the domain if the filter is unknown
+    lookup(&conn, &req.filter)?;
+    update_interface_filter(&conn, &uuid, &mac, Some(&filterref), req.scope)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.interface_filter",
+        &format!(
+            "{} {} {} {:?} {}",
+            uuid,
+            mac,
+            req.filter,
+            req.parameters,
+            req.scope.name()
+        ),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/interfaces/{mac}/filter?scope=both
+// ---------------------------------------------------------------------
+pub async fn remove_interface_filter(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, mac)): Path<(String, String)>,
+    Query(query): Query<ScopeQuery>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    update_interface_filter(&conn, &uuid, &mac, None, query.scope)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "domain.interface_unfilter",
+        &format!("{} {} {}", uuid, mac, query.scope.name()),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
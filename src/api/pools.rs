@@ -0,0 +1,327 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use virt::storage_pool::StoragePool;
+
+use super::{ApiResult, Autostart, Libvirt, libvirt_error, reject_nul, xml_escape};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Storage pool information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct PoolInfo {
+    pub name: String,
+    pub uuid: String,
+    pub pool_type: String,
+    pub state: &'static str,
+    pub active: bool,
+    pub persistent: bool,
+    pub autostart: bool,
+    pub capacity: u64,
+    pub allocation: u64,
+    pub available: u64,
+}
+
+/// Supported pool types – each variant becomes the matching `<pool type=…>`
+/// definition so callers never have to write XML.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PoolSource {
+    /// Directory on the host
+    Dir { path: String },
+    /// LVM volume group; `devices` are only needed to build a new group
+    Logical {
+        vg_name: String,
+        #[serde(default)]
+        devices: Vec<String>,
+    },
+    /// NFS (or other network) export mounted on `path`
+    Netfs {
+        host: String,
+        export: String,
+        path: String,
+        #[serde(default)]
+        format: Option<String>,
+    },
+    /// iSCSI target; LUNs show up as volumes
+    Iscsi {
+        host: String,
+        iqn: String,
+        #[serde(default)]
+        port: Option<u16>,
+    },
+}
+
+#[derive(Deserialize)]
+pub struct DefinePool {
+    name: String,
+    #[serde(flatten)]
+    source: PoolSource,
+    /// Run `build` (mkdir, vgcreate, …) after defining
+    #[serde(default)]
+    build: bool,
+    #[serde(default)]
+    start: bool,
+    #[serde(default)]
+    autostart: bool,
+}
+
+/// Build the pool XML for a typed define request.
+pub fn pool_xml(req: &DefinePool) -> String {
+    let name = xml_escape(&req.name);
+    match &req.source {
+        PoolSource::Dir { path } => format!(
+            "<pool type='dir'><name>{}</name><target><path>{}</path></target></pool>",
+            name,
+            xml_escape(path)
+        ),
+        PoolSource::Logical { vg_name, devices } => {
+            let devices: String = devices
+                .iter()
+                .map(|d| format!("<device path='{}'/>", xml_escape(d)))
+                .collect();
+            format!(
+                "<pool type='logical'><name>{}</name><source>{}<name>{}</name>\
+                 <format type='lvm2'/></source><target><path>/dev/{}</path></target></pool>",
+                name,
+                devices,
+                xml_escape(vg_name),
+                xml_escape(vg_name)
+            )
+        }
+        PoolSource::Netfs {
+            host,
+            export,
+            path,
+            format,
+        } => format!(
+            "<pool type='netfs'><name>{}</name><source><host name='{}'/><dir path='{}'/>\
+             <format type='{}'/></source><target><path>{}</path></target></pool>",
+            name,
+            xml_escape(host),
+            xml_escape(export),
+            xml_escape(format.as_deref().unwrap_or("nfs")),
+            xml_escape(path)
+        ),
+        PoolSource::Iscsi { host, iqn, port } => format!(
+            "<pool type='iscsi'><name>{}</name><source><host name='{}' port='{}'/>\
+             <device path='{}'/></source><target><path>/dev/disk/by-path</path></target></pool>",
+            name,
+            xml_escape(host),
+            port.unwrap_or(3260),
+            xml_escape(iqn)
+        ),
+    }
+}
+
+/// Human readable name of a `virStoragePoolState`.
+fn pool_state_name(state: u32) -> &'static str {
+    match state {
+        0 => "Inactive",
+        1 => "Building",
+        2 => "Running",
+        3 => "Degraded",
+        4 => "Inaccessible",
+        _ => "Unknown",
+    }
+}
+
+pub(crate) fn pool_info(pool: &StoragePool) -> ApiResult<PoolInfo> {
+    let info = pool.get_info().map_err(libvirt_error)?;
+    let pool_type = pool
+        .get_xml_desc(0)
+        .ok()
+        .and_then(|xml| {
+            let doc = roxmltree::Document::parse(&xml).ok()?;
+            doc.root_element().attribute("type").map(str::to_string)
+        })
+        .unwrap_or_default();
+    Ok(PoolInfo {
+        name: pool.get_name().map_err(libvirt_error)?,
+        uuid: pool.get_uuid_string().map_err(libvirt_error)?,
+        pool_type,
+        state: pool_state_name(info.state),
+        active: pool.is_active().map_err(libvirt_error)?,
+        persistent: pool.is_persistent().map_err(libvirt_error)?,
+        autostart: pool.get_autostart().unwrap_or(false),
+        capacity: info.capacity,
+        allocation: info.allocation,
+        available: info.available,
+    })
+}
+
+pub(crate) fn lookup(conn: &Libvirt, name: &str) -> ApiResult<StoragePool> {
+    reject_nul(name, "pool name")?;
+    StoragePool::lookup_by_name(conn, name).map_err(libvirt_error)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/pools – all storage pools with capacity figures
+// ---------------------------------------------------------------------
+pub async fn list_pools(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<PoolInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let pools = conn.list_all_storage_pools(0).map_err(libvirt_error)?;
+    let out = pools.iter().map(pool_info).collect::<ApiResult<Vec<_>>>()?;
+    Ok(Json(out))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/pools/{name}
+// ---------------------------------------------------------------------
+pub async fn get_pool(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(pool_info(&lookup(&conn, &name)?)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools – define a pool from a typed request
+// ---------------------------------------------------------------------
+pub async fn define_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<DefinePool>,
+) -> ApiResult<(StatusCode, Json<PoolInfo>)> {
+    let xml = pool_xml(&req);
+    reject_nul(&xml, "pool definition")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let pool = StoragePool::define_xml(&conn, &xml, 0).map_err(libvirt_error)?;
+    if req.build {
+        pool.build(0).map_err(libvirt_error)?;
+    }
+    if req.autostart {
+        pool.set_autostart(true).map_err(libvirt_error)?;
+    }
+    if req.start {
+        pool.create(0).map_err(libvirt_error)?;
+    }
+    let info = pool_info(&pool)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "pool.define",
+        &format!("{} {}", info.name, info.pool_type),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// Pool operations that take no request body.
+#[derive(Clone, Copy)]
+enum PoolAction {
+    Build,
+    Start,
+    Stop,
+    Refresh,
+    Delete,
+}
+
+async fn pool_action(
+    user: CurrentUser,
+    state: AppState,
+    name: String,
+    action: PoolAction,
+) -> ApiResult<Json<PoolInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let pool = lookup(&conn, &name)?;
+    let label = match action {
+        PoolAction::Build => pool.build(0).map(|_| "pool.build"),
+        PoolAction::Start => pool.create(0).map(|_| "pool.start"),
+        PoolAction::Stop => pool.destroy().map(|_| "pool.stop"),
+        PoolAction::Refresh => pool.refresh(0).map(|_| "pool.refresh"),
+        // Removes the underlying storage (directory, volume group, …)
+        PoolAction::Delete => pool.delete(0).map(|_| "pool.delete"),
+    }
+    .map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, label, &name).await;
+    Ok(Json(pool_info(&pool)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools/{name}/build|start|stop|refresh|delete
+// ---------------------------------------------------------------------
+pub async fn build_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    pool_action(user, state, name, PoolAction::Build).await
+}
+
+pub async fn start_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    pool_action(user, state, name, PoolAction::Start).await
+}
+
+pub async fn stop_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    pool_action(user, state, name, PoolAction::Stop).await
+}
+
+pub async fn refresh_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    pool_action(user, state, name, PoolAction::Refresh).await
+}
+
+pub async fn delete_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<PoolInfo>> {
+    pool_action(user, state, name, PoolAction::Delete).await
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/pools/{name} – undefine (the storage itself is kept)
+// ---------------------------------------------------------------------
+pub async fn undefine_pool(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name)?.undefine().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "pool.undefine", &name).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/pools/{name}/autostart – {"autostart": true|false}
+// ---------------------------------------------------------------------
+pub async fn set_pool_autostart(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<Autostart>,
+) -> ApiResult<Json<PoolInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let pool = lookup(&conn, &name)?;
+    pool.set_autostart(req.autostart).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "pool.autostart",
+        &format!("{} {}", name, req.autostart),
+    )
+    .await;
+    Ok(Json(pool_info(&pool)?))
+}
@@ -0,0 +1,358 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::join_all;
+use std::time::Duration;
+use virt::{storage_pool::StoragePoolInfo, sys};
+
+use super::stats::{DomainStats, all_domain_stats};
+use super::{Libvirt, libvirt_error};
+use crate::telemetry::{self, Text};
+use crate::{AppState, hosts, scheduler};
+
+/// Stat groups scraped for every domain.
+const STATS: u32 = sys::VIR_DOMAIN_STATS_STATE
+    | sys::VIR_DOMAIN_STATS_CPU_TOTAL
+    | sys::VIR_DOMAIN_STATS_BALLOON
+    | sys::VIR_DOMAIN_STATS_VCPU
+    | sys::VIR_DOMAIN_STATS_INTERFACE
+    | sys::VIR_DOMAIN_STATS_BLOCK;
+
+/// Per-domain metrics: name, type, stats field, scale to base units, help.
+const DOMAIN_METRICS: [(&str, &str, &str, f64, &str); 6] = [
+    (
+        "libvirt_domain_state",
+        "gauge",
+        "state.state",
+        1.0,
+        "virDomainState: 1 running, 3 paused, 4 shutting down, 5 shut off, 6 crashed.",
+    ),
+    (
+        "libvirt_domain_vcpus",
+        "gauge",
+        "vcpu.current",
+        1.0,
+        "Current number of vCPUs.",
+    ),
+    (
+        "libvirt_domain_cpu_time_seconds_total",
+        "counter",
+        "cpu.time",
+        1e-9,
+        "CPU time used by the domain.",
+    ),
+    (
+        "libvirt_domain_memory_current_bytes",
+        "gauge",
+        "balloon.current",
+        1024.0,
+        "Current balloon size.",
+    ),
+    (
+        "libvirt_domain_memory_maximum_bytes",
+        "gauge",
+        "balloon.maximum",
+        1024.0,
+        "Maximum memory of the domain.",
+    ),
+    (
+        "libvirt_domain_memory_rss_bytes",
+        "gauge",
+        "balloon.rss",
+        1024.0,
+        "Resident set size of the hypervisor process.",
+    ),
+];
+
+/// Per-device counters: stats group, name, field, help.
+const DEVICE_METRICS: [(&str, &str, &str, &str); 12] = [
+    (
+        "block",
+        "libvirt_domain_block_read_bytes_total",
+        "rd.bytes",
+        "Bytes read from the disk.",
+    ),
+    (
+        "block",
+        "libvirt_domain_block_write_bytes_total",
+        "wr.bytes",
+        "Bytes written to the disk.",
+    ),
+    (
+        "block",
+        "libvirt_domain_block_read_requests_total",
+        "rd.reqs",
+        "Read requests of the disk.",
+    ),
+    (
+        "block",
+        "libvirt_domain_block_write_requests_total",
+        "wr.reqs",
+        "Write requests of the disk.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_receive_bytes_total",
+        "rx.bytes",
+        "Bytes received by the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_transmit_bytes_total",
+        "tx.bytes",
+        "Bytes sent by the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_receive_packets_total",
+        "rx.pkts",
+        "Packets received by the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_transmit_packets_total",
+        "tx.pkts",
+        "Packets sent by the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_receive_errors_total",
+        "rx.errs",
+        "Receive errors of the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_transmit_errors_total",
+        "tx.errs",
+        "Transmit errors of the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_receive_drops_total",
+        "rx.drop",
+        "Received packets dropped by the interface.",
+    ),
+    (
+        "net",
+        "libvirt_domain_interface_transmit_drops_total",
+        "tx.drop",
+        "Sent packets dropped by the interface.",
+    ),
+];
+
+/// Host metric: name, help and the value from memory, free memory and CPUs.
+type NodeMetric = (&'static str, &'static str, fn(&(u64, u64, u32)) -> f64);
+
+/// Storage pool metric: name, help and the value from the pool info.
+type PoolMetric = (&'static str, &'static str, fn(&StoragePoolInfo) -> f64);
+
+/// How long one hypervisor may take to answer a scrape.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Everything scraped from one hypervisor.
+#[derive(Default)]
+struct HostScrape {
+    host: String,
+    up: bool,
+    /// Memory and free memory in bytes, active CPUs
+    node: Option<(u64, u64, u32)>,
+    domains: Vec<DomainStats>,
+    pools: Vec<(String, StoragePoolInfo)>,
+}
+
+fn scrape(uri: &str) -> Result<HostScrape, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let node = conn.get_node_info().map_err(libvirt_error)?;
+    let free = conn.get_free_memory().map_err(libvirt_error)?;
+    let domains = all_domain_stats(&conn, STATS, 0).map_err(libvirt_error)?;
+    let mut pools = Vec::new();
+    for pool in conn.list_all_storage_pools(0).map_err(libvirt_error)? {
+        let name = pool.get_name().map_err(libvirt_error)?;
+        pools.push((name, pool.get_info().map_err(libvirt_error)?));
+    }
+    Ok(HostScrape {
+        up: true,
+        node: Some((node.memory * 1024, free, node.cpus)),
+        domains,
+        pools,
+        ..HostScrape::default()
+    })
+}
+
+fn render_hosts(out: &mut Text, scrapes: &[HostScrape]) {
+    out.family(
+        "libvirt_up",
+        "gauge",
+        "Whether the hypervisor could be scraped.",
+    );
+    for s in scrapes {
+        out.sample(
+            "libvirt_up",
+            &[("host", s.host.as_str())],
+            s.up as u8 as f64,
+        );
+    }
+
+    let node_metrics: [NodeMetric; 3] = [
+        ("libvirt_node_memory_bytes", "Memory of the host.", |n| {
+            n.0 as f64
+        }),
+        (
+            "libvirt_node_memory_free_bytes",
+            "Free memory of the host.",
+            |n| n.1 as f64,
+        ),
+        ("libvirt_node_cpus", "Active CPUs of the host.", |n| {
+            n.2 as f64
+        }),
+    ];
+    for (name, help, value) in node_metrics {
+        out.family(name, "gauge", help);
+        for s in scrapes {
+            if let Some(node) = &s.node {
+                out.sample(name, &[("host", s.host.as_str())], value(node));
+            }
+        }
+    }
+
+    for (name, kind, field, scale, help) in DOMAIN_METRICS {
+        out.family(name, kind, help);
+        for s in scrapes {
+            for dom in &s.domains {
+                if let Some(value) = dom.number(field) {
+                    out.sample(
+                        name,
+                        &[
+                            ("host", s.host.as_str()),
+                            ("domain", dom.name.as_str()),
+                            ("uuid", dom.uuid.as_str()),
+                        ],
+                        value as f64 * scale,
+                    );
+                }
+            }
+        }
+    }
+
+    for (group, name, field, help) in DEVICE_METRICS {
+        out.family(name, "counter", help);
+        for s in scrapes {
+            for dom in &s.domains {
+                for i in 0..dom.number(&format!("{}.count", group)).unwrap_or(0) {
+                    let device = dom
+                        .strings
+                        .get(&format!("{}.{}.name", group, i))
+                        .map(String::as_str)
+                        .unwrap_or_default();
+                    if let Some(value) = dom.number(&format!("{}.{}.{}", group, i, field)) {
+                        out.sample(
+                            name,
+                            &[
+                                ("host", s.host.as_str()),
+                                ("domain", dom.name.as_str()),
+                                ("uuid", dom.uuid.as_str()),
+                                ("device", device),
+                            ],
+                            value as f64,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    let pool_metrics: [PoolMetric; 4] = [
+        ("libvirt_pool_active", "Whether the pool is running.", |p| {
+            (p.state == sys::VIR_STORAGE_POOL_RUNNING) as u8 as f64
+        }),
+        (
+            "libvirt_pool_capacity_bytes",
+            "Capacity of the pool.",
+            |p| p.capacity as f64,
+        ),
+        (
+            "libvirt_pool_allocation_bytes",
+            "Space allocated in the pool.",
+            |p| p.allocation as f64,
+        ),
+        (
+            "libvirt_pool_available_bytes",
+            "Free space of the pool.",
+            |p| p.available as f64,
+        ),
+    ];
+    for (name, help, value) in pool_metrics {
+        out.family(name, "gauge", help);
+        for s in scrapes {
+            for (pool, info) in &s.pools {
+                out.sample(
+                    name,
+                    &[("host", s.host.as_str()), ("pool", pool.as_str())],
+                    value(info),
+                );
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// GET /metrics – Prometheus text format; reachable without a session,
+// guarded by `metrics_token` when one is configured
+// ---------------------------------------------------------------------
+pub async fn get_metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(token) = &state.config.metrics_token {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if bearer != Some(token.as_str()) {
+            return (StatusCode::UNAUTHORIZED, "invalid metrics token").into_response();
+        }
+    }
+
+    // The local hypervisor plus every registered host
+    let mut targets = vec![("local".to_string(), state.config.libvirt_uri.clone())];
+    if let Ok(registered) = hosts::list(&state.pool).await {
+        targets.extend(registered.into_iter().map(|h| (h.name, h.uri)));
+    }
+    // Scraped in parallel; a host that does not answer in time is down
+    let scrapes = join_all(targets.into_iter().map(|(host, uri)| async move {
+        let scraped = tokio::time::timeout(
+            SCRAPE_TIMEOUT,
+            tokio::task::spawn_blocking(move || scrape(&uri)),
+        )
+        .await;
+        let scrape = match scraped {
+            Ok(Ok(Ok(scrape))) => scrape,
+            _ => HostScrape::default(),
+        };
+        HostScrape { host, ..scrape }
+    }))
+    .await;
+
+    let mut out = Text::default();
+    render_hosts(&mut out, &scrapes);
+    telemetry::render(&mut out);
+    let sessions: Option<(i64,)> =
+        sqlx::query_as("SELECT COUNT(*) FROM sessions_table WHERE expires IS NULL OR expires > ?")
+            .bind(scheduler::now())
+            .fetch_one(&state.pool)
+            .await
+            .ok();
+    if let Some((sessions,)) = sessions {
+        out.family(
+            "rust_manager_sessions_active",
+            "gauge",
+            "Unexpired sessions in the session store.",
+        );
+        out.sample("rust_manager_sessions_active", &[], sessions as f64);
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        out.into_string(),
+    )
+        .into_response()
+}
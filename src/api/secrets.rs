@@ -0,0 +1,274 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use virt::secret::Secret;
+
+use super::{ApiResult, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Secret information returned as JSON – never the value itself
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct SecretInfo {
+    pub uuid: String,
+    pub usage_type: SecretUsage,
+    pub usage_id: Option<String>,
+    pub description: Option<String>,
+    pub ephemeral: bool,
+    pub private: bool,
+}
+
+/// What a secret is used for (`virSecretUsageType`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretUsage {
+    None,
+    Volume,
+    Ceph,
+    Iscsi,
+    Tls,
+    Vtpm,
+}
+
+impl SecretUsage {
+    fn from_raw(raw: u32) -> SecretUsage {
+        match raw {
+            1 => SecretUsage::Volume,
+            2 => SecretUsage::Ceph,
+            3 => SecretUsage::Iscsi,
+            4 => SecretUsage::Tls,
+            5 => SecretUsage::Vtpm,
+            _ => SecretUsage::None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SecretUsage::None => "none",
+            SecretUsage::Volume => "volume",
+            SecretUsage::Ceph => "ceph",
+            SecretUsage::Iscsi => "iscsi",
+            SecretUsage::Tls => "tls",
+            SecretUsage::Vtpm => "vtpm",
+        }
+    }
+
+    /// Element of `<usage>` that holds the usage ID.
+    fn id_element(self) -> Option<&'static str> {
+        match self {
+            SecretUsage::None => None,
+            SecretUsage::Volume => Some("volume"),
+            SecretUsage::Iscsi => Some("target"),
+            SecretUsage::Ceph | SecretUsage::Tls | SecretUsage::Vtpm => Some("name"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DefineSecret {
+    /// Fixed UUID; libvirt picks one when omitted
+    #[serde(default)]
+    uuid: Option<String>,
+    usage_type: SecretUsage,
+    /// Volume path, Ceph/TLS/vTPM name or iSCSI target
+    #[serde(default)]
+    usage_id: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    /// Keep the secret in memory only
+    #[serde(default)]
+    ephemeral: bool,
+    /// Never reveal the value, not even to libvirt clients
+    #[serde(default = "default_private")]
+    private: bool,
+}
+
+fn default_private() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct SecretValue {
+    value: String,
+    /// `value` is base64 encoded binary data (e.g. a Ceph key)
+    #[serde(default)]
+    base64: bool,
+}
+
+fn yes_no(flag: bool) -> &'static str {
+    if flag { "yes" } else { "no" }
+}
+
+fn secret_xml(req: &DefineSecret) -> ApiResult<String> {
+    reject_nul(req.uuid.as_deref().unwrap_or_default(), "secret UUID")?;
+    reject_nul(
+        req.description.as_deref().unwrap_or_default(),
+        "description",
+    )?;
+    reject_nul(req.usage_id.as_deref().unwrap_or_default(), "usage_id")?;
+    let mut xml = format!(
+        "<secret ephemeral='{}' private='{}'>",
+        yes_no(req.ephemeral),
+        yes_no(req.private)
+    );
+    if let Some(uuid) = &req.uuid {
+        xml.push_str(&format!("<uuid>{}</uuid>", xml_escape(uuid)));
+    }
+    if let Some(description) = &req.description {
+        xml.push_str(&format!(
+            "<description>{}</description>",
+            xml_escape(description)
+        ));
+    }
+    match (req.usage_type.id_element(), &req.usage_id) {
+        (None, _) => {}
+        (Some(element), Some(id)) if !id.is_empty() => xml.push_str(&format!(
+            "<usage type='{}'><{element}>{}</{element}></usage>",
+            req.usage_type.name(),
+            xml_escape(id)
+        )),
+        (Some(_), _) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("usage_id is required for {} secrets", req.usage_type.name()),
+            ));
+        }
+    }
+    xml.push_str("</secret>");
+    Ok(xml)
+}
+
+/// Flags and description from the secret's XML.
+fn parse_secret_xml(xml: &str) -> Result<(bool, bool, Option<String>), roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let flag = |name: &str| root.attribute(name) == Some("yes");
+    let description = root
+        .children()
+        .find(|n| n.has_tag_name("description"))
+        .and_then(|n| n.text())
+        .map(str::to_string);
+    Ok((flag("ephemeral"), flag("private"), description))
+}
+
+pub(crate) fn secret_info(secret: &Secret) -> ApiResult<SecretInfo> {
+    let xml = secret.get_xml_desc(0).map_err(libvirt_error)?;
+    let (ephemeral, private, description) = parse_secret_xml(&xml).map_err(internal_error)?;
+    let usage_type = SecretUsage::from_raw(secret.get_usage_type().map_err(libvirt_error)?);
+    Ok(SecretInfo {
+        uuid: secret.get_uuid_string().map_err(libvirt_error)?,
+        usage_type,
+        usage_id: match usage_type {
+            SecretUsage::None => None,
+            _ => secret.get_usage_id().ok(),
+        },
+        description,
+        ephemeral,
+        private,
+    })
+}
+
+pub(crate) fn lookup(conn: &Libvirt, uuid: &str) -> ApiResult<Secret> {
+    reject_nul(uuid, "secret UUID")?;
+    Secret::lookup_by_uuid_string(conn, uuid).map_err(libvirt_error)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/secrets
+// ---------------------------------------------------------------------
+pub async fn list_secrets(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<SecretInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let secrets = conn.list_all_secrets(0).map_err(libvirt_error)?;
+    let out = secrets
+        .iter()
+        .map(secret_info)
+        .collect::<ApiResult<Vec<_>>>()?;
+    Ok(Json(out))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/secrets/{uuid}
+// ---------------------------------------------------------------------
+pub async fn get_secret(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<SecretInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(secret_info(&lookup(&conn, &uuid)?)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/secrets – define a secret (without a value)
+// ---------------------------------------------------------------------
+pub async fn define_secret(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<DefineSecret>,
+) -> ApiResult<(StatusCode, Json<SecretInfo>)> {
+    let xml = secret_xml(&req)?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let secret = Secret::define_xml(&conn, &xml, 0).map_err(libvirt_error)?;
+    let info = secret_info(&secret)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "secret.define",
+        &format!(
+            "{} {} {}",
+            info.uuid,
+            info.usage_type.name(),
+            info.usage_id.as_deref().unwrap_or("")
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/secrets/{uuid}/value – administrators only; the value is
+// write-only and never returned or audited
+// ---------------------------------------------------------------------
+pub async fn set_secret_value(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<SecretValue>,
+) -> ApiResult<StatusCode> {
+    user.require_admin()?;
+    let value = if req.base64 {
+        BASE64
+            .decode(req.value.trim())
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid base64: {}", e)))?
+    } else {
+        req.value.into_bytes()
+    };
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &uuid)?
+        .set_value(&value, 0)
+        .map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "secret.set_value", &uuid).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/secrets/{uuid}
+// ---------------------------------------------------------------------
+pub async fn undefine_secret(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &uuid)?.undefine().map_err(libvirt_error)?;
+    audit::record(&state.pool, &user.username, "secret.undefine", &uuid).await;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,280 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use virt::{domain::Domain, domain_snapshot::DomainSnapshot, sys};
+
+use super::domains::lookup as lookup_domain;
+use super::{ApiResult, Libvirt, libvirt_error, reject_nul, xml_escape};
+use crate::{AppState, audit, auth::CurrentUser};
+
+// ---------------------------------------------------------------------
+// Snapshot tree returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct SnapshotTree {
+    /// Name of the snapshot the domain currently runs from
+    pub current: Option<String>,
+    pub roots: Vec<SnapshotNode>,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotNode {
+    pub name: String,
+    pub description: String,
+    /// Domain state when the snapshot was taken, e.g. `running`, `shutoff`
+    pub state: String,
+    /// Creation time as seconds since the unix epoch
+    pub created: i64,
+    /// `internal`, `external` or `no`
+    pub memory: String,
+    /// Disks (or memory) were saved to separate files
+    pub external: bool,
+    pub current: bool,
+    pub children: Vec<SnapshotNode>,
+}
+
+/// Fields of one `<domainsnapshot>` definition.
+struct ParsedSnapshot {
+    name: String,
+    parent: Option<String>,
+    description: String,
+    state: String,
+    created: i64,
+    memory: String,
+    external: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CreateSnapshot {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Save disks to new overlay files instead of inside the images
+    #[serde(default)]
+    external: bool,
+    /// Include the memory state of a running domain
+    #[serde(default)]
+    memory: bool,
+    /// Where the memory state goes for external snapshots
+    #[serde(default)]
+    memory_file: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteSnapshot {
+    /// Also delete every descendant of the snapshot
+    #[serde(default)]
+    children: bool,
+}
+
+fn parse_snapshot_xml(xml: &str) -> Result<ParsedSnapshot, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let child = |name: &str| root.children().find(|n| n.has_tag_name(name));
+    let text = |name: &str| {
+        child(name)
+            .and_then(|n| n.text())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let memory = child("memory")
+        .and_then(|n| n.attribute("snapshot"))
+        .unwrap_or("no")
+        .to_string();
+    let external_disks = child("disks").is_some_and(|disks| {
+        disks
+            .children()
+            .any(|d| d.attribute("snapshot") == Some("external"))
+    });
+    Ok(ParsedSnapshot {
+        name: text("name"),
+        parent: child("parent")
+            .and_then(|p| p.children().find(|n| n.has_tag_name("name")))
+            .and_then(|n| n.text())
+            .map(str::to_string),
+        description: text("description"),
+        state: text("state"),
+        created: text("creationTime").parse().unwrap_or(0),
+        external: external_disks || memory == "external",
+        memory,
+    })
+}
+
+/// Build the subtree below `parent` (`None` for the roots), oldest first.
+fn subtree(
+    parent: Option<&str>,
+    snapshots: &[ParsedSnapshot],
+    current: Option<&str>,
+) -> Vec<SnapshotNode> {
+    let mut nodes: Vec<SnapshotNode> = snapshots
+        .iter()
+        .filter(|s| s.parent.as_deref() == parent)
+        .map(|s| SnapshotNode {
+            name: s.name.clone(),
+            description: s.description.clone(),
+            state: s.state.clone(),
+            created: s.created,
+            memory: s.memory.clone(),
+            external: s.external,
+            current: current == Some(s.name.as_str()),
+            children: subtree(Some(&s.name), snapshots, current),
+        })
+        .collect();
+    nodes.sort_by_key(|n| n.created);
+    nodes
+}
+
+/// All snapshots of `dom` arranged by parent.
+pub fn snapshot_tree(dom: &Domain) -> ApiResult<SnapshotTree> {
+    let mut snapshots = Vec::new();
+    let mut current = None;
+    for snap in dom.list_all_snapshots(0).map_err(libvirt_error)? {
+        let xml = snap.get_xml_desc(0).map_err(libvirt_error)?;
+        let parsed = parse_snapshot_xml(&xml)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if snap.is_current(0).unwrap_or(false) {
+            current = Some(parsed.name.clone());
+        }
+        snapshots.push(parsed);
+    }
+    Ok(SnapshotTree {
+        roots: subtree(None, &snapshots, current.as_deref()),
+        current,
+    })
+}
+
+fn lookup(dom: &Domain, name: &str) -> ApiResult<DomainSnapshot> {
+    reject_nul(name, "name")?;
+    DomainSnapshot::lookup_by_name(dom, name, 0).map_err(libvirt_error)
+}
+
+fn snapshot_xml(req: &CreateSnapshot) -> ApiResult<String> {
+    reject_nul(&req.name, "name")?;
+    reject_nul(&req.description, "description")?;
+    reject_nul(
+        req.memory_file.as_deref().unwrap_or_default(),
+        "memory file",
+    )?;
+    let memory = match (req.memory, req.external, &req.memory_file) {
+        (false, _, _) => "<memory snapshot='no'/>".to_string(),
+        (true, false, _) => "<memory snapshot='internal'/>".to_string(),
+        (true, true, Some(file)) => {
+            format!("<memory snapshot='external' file='{}'/>", xml_escape(file))
+        }
+        (true, true, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "memory_file is required for external snapshots with memory".into(),
+            ));
+        }
+    };
+    Ok(format!(
+        "<domainsnapshot><name>{}</name><description>{}</description>{}</domainsnapshot>",
+        xml_escape(&req.name),
+        xml_escape(&req.description),
+        memory
+    ))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/snapshots – snapshot tree
+// ---------------------------------------------------------------------
+pub async fn list_snapshots(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<SnapshotTree>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    Ok(Json(snapshot_tree(&dom)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/snapshots – internal or external snapshot
+// ---------------------------------------------------------------------
+pub async fn create_snapshot(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<CreateSnapshot>,
+) -> ApiResult<(StatusCode, Json<SnapshotTree>)> {
+    let xml = snapshot_xml(&req)?;
+    let mut flags = sys::VIR_DOMAIN_SNAPSHOT_CREATE_ATOMIC;
+    if req.external && !req.memory {
+        // libvirt picks overlay file names next to each disk image
+        flags |= sys::VIR_DOMAIN_SNAPSHOT_CREATE_DISK_ONLY;
+    }
+
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    DomainSnapshot::create_xml(&dom, &xml, flags).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "snapshot.create",
+        &format!(
+            "{} {} {}{}",
+            uuid,
+            req.name,
+            if req.external { "external" } else { "internal" },
+            if req.memory { "+memory" } else { "" }
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(snapshot_tree(&dom)?)))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/domains/{uuid}/snapshots/{name}/revert
+// ---------------------------------------------------------------------
+pub async fn revert_snapshot(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, name)): Path<(String, String)>,
+) -> ApiResult<Json<SnapshotTree>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    lookup(&dom, &name)?.revert(0).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "snapshot.revert",
+        &format!("{} {}", uuid, name),
+    )
+    .await;
+    Ok(Json(snapshot_tree(&dom)?))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/domains/{uuid}/snapshots/{name}[?children=true]
+// ---------------------------------------------------------------------
+pub async fn delete_snapshot(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((uuid, name)): Path<(String, String)>,
+    Query(query): Query<DeleteSnapshot>,
+) -> ApiResult<StatusCode> {
+    let flags = if query.children {
+        sys::VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN
+    } else {
+        0
+    };
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let dom = lookup_domain(&conn, &uuid)?;
+    lookup(&dom, &name)?.delete(flags).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        if query.children {
+            "snapshot.delete_tree"
+        } else {
+            "snapshot.delete"
+        },
+        &format!("{} {}", uuid, name),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
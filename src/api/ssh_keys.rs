@@ -0,0 +1,90 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+
+use super::{ApiResult, internal_error};
+use crate::ssh_keys::{self, SshKey};
+use crate::{AppState, audit, auth::CurrentUser};
+
+#[derive(Deserialize)]
+pub struct AddKey {
+    name: String,
+    /// One `authorized_keys` line, e.g. the contents of `id_ed25519.pub`
+    public_key: String,
+}
+
+// ---------------------------------------------------------------------
+// GET /api/ssh-keys – the requesting user's keys
+// ---------------------------------------------------------------------
+pub async fn list_keys(
+    user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<SshKey>>> {
+    let keys = ssh_keys::list_for_user(&state.pool, user.id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(keys))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/ssh-keys – store a public key
+// ---------------------------------------------------------------------
+pub async fn add_key(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Json(req): Json<AddKey>,
+) -> ApiResult<(StatusCode, Json<SshKey>)> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "name is required".into()));
+    }
+    let public_key = ssh_keys::normalize(&req.public_key).ok_or((
+        StatusCode::BAD_REQUEST,
+        "not an OpenSSH public key".to_string(),
+    ))?;
+    let existing = ssh_keys::list_for_user(&state.pool, user.id)
+        .await
+        .map_err(internal_error)?;
+    if existing.iter().any(|k| k.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("a key named {} already exists", name),
+        ));
+    }
+    let id = ssh_keys::add(&state.pool, user.id, name, &public_key)
+        .await
+        .map_err(internal_error)?;
+    audit::record(&state.pool, &user.username, "ssh_key.add", name).await;
+    ssh_keys::get(&state.pool, user.id, id)
+        .await
+        .map_err(internal_error)?
+        .map(|key| (StatusCode::CREATED, Json(key)))
+        .ok_or((StatusCode::NOT_FOUND, "key not found".into()))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/ssh-keys/{id}
+// ---------------------------------------------------------------------
+pub async fn delete_key(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    if !ssh_keys::delete(&state.pool, user.id, id)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err((StatusCode::NOT_FOUND, "key not found".into()));
+    }
+    audit::record(
+        &state.pool,
+        &user.username,
+        "ssh_key.delete",
+        &id.to_string(),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
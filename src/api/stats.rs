@@ -0,0 +1,139 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use virt::{error::Error, sys};
+
+use super::domains::lookup;
+use super::{ApiResult, Libvirt, libvirt_error};
+use crate::sampler::Sample;
+use crate::{AppState, auth::CurrentUser};
+
+/// Bulk statistics of one domain, keyed by libvirt's field names such as
+/// `cpu.time` or `block.0.rd.bytes`.
+#[derive(Debug, Default)]
+pub struct DomainStats {
+    pub uuid: String,
+    pub name: String,
+    /// Integer and boolean fields; negative values read as 0
+    pub numbers: BTreeMap<String, u64>,
+    /// String fields, e.g. `block.0.name`
+    pub strings: BTreeMap<String, String>,
+}
+
+impl DomainStats {
+    pub fn number(&self, field: &str) -> Option<u64> {
+        self.numbers.get(field).copied()
+    }
+
+    /// Sum of `<group>.<n>.<field>` over every device of a group, e.g. the
+    /// bytes read by all disks with `sum("block", "rd.bytes")`.
+    pub fn sum(&self, group: &str, field: &str) -> u64 {
+        (0..self.number(&format!("{}.count", group)).unwrap_or(0))
+            .filter_map(|i| self.number(&format!("{}.{}.{}", group, i, field)))
+            .sum()
+    }
+}
+
+/// `virConnectGetAllDomainStats` – the virt crate returns the records
+/// without reading or ever freeing them, so call it through the raw bindings.
+pub fn all_domain_stats(conn: &Libvirt, stats: u32, flags: u32) -> Result<Vec<DomainStats>, Error> {
+    let mut records: *mut sys::virDomainStatsRecordPtr = std::ptr::null_mut();
+    let size =
+        unsafe { sys::virConnectGetAllDomainStats(conn.as_ptr(), stats, &mut records, flags) };
+    if size == -1 {
+        return Err(Error::last_error());
+    }
+
+    // SAFETY: libvirt returned `size` valid records; the list and everything
+    // it points to is released at once by virDomainStatsRecordListFree.
+    let mut out = Vec::with_capacity(size as usize);
+    unsafe {
+        for i in 0..size as isize {
+            let record = *records.offset(i);
+            let mut uuid = [0 as libc::c_char; sys::VIR_UUID_STRING_BUFLEN as usize];
+            sys::virDomainGetUUIDString((*record).dom, uuid.as_mut_ptr());
+            let mut entry = DomainStats {
+                uuid: c_str(uuid.as_ptr()).unwrap_or_default(),
+                name: c_str(sys::virDomainGetName((*record).dom)).unwrap_or_default(),
+                ..DomainStats::default()
+            };
+            for j in 0..(*record).nparams as isize {
+                let param = &*(*record).params.offset(j);
+                let field = c_str(param.field.as_ptr()).unwrap_or_default();
+                let number = match param.type_ as u32 {
+                    sys::VIR_TYPED_PARAM_INT => u64::try_from(param.value.i).ok(),
+                    sys::VIR_TYPED_PARAM_UINT => Some(param.value.ui as u64),
+                    sys::VIR_TYPED_PARAM_LLONG => u64::try_from(param.value.l).ok(),
+                    sys::VIR_TYPED_PARAM_ULLONG => Some(param.value.ul),
+                    sys::VIR_TYPED_PARAM_BOOLEAN => Some((param.value.b != 0) as u64),
+                    sys::VIR_TYPED_PARAM_STRING => {
+                        if let Some(value) = c_str(param.value.s) {
+                            entry.strings.insert(field, value);
+                        }
+                        continue;
+                    }
+                    // Doubles only appear in perf and dirty rate stats
+                    _ => continue,
+                };
+                entry.numbers.insert(field, number.unwrap_or(0));
+            }
+            out.push(entry);
+        }
+        if !records.is_null() {
+            sys::virDomainStatsRecordListFree(records);
+        }
+    }
+    Ok(out)
+}
+
+/// Copy a nullable C string owned by libvirt.
+unsafe fn c_str(ptr: *const libc::c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { CStr::from_ptr(ptr) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------
+// Sampled metrics of one domain
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct DomainMetrics {
+    pub uuid: String,
+    /// Seconds between samples
+    pub interval: u64,
+    /// Oldest first
+    pub samples: Vec<Sample>,
+}
+
+// ---------------------------------------------------------------------
+// GET /api/domains/{uuid}/metrics – the rolling window of samples
+// ---------------------------------------------------------------------
+pub async fn get_metrics(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<Json<DomainMetrics>> {
+    // Look the domain up so unknown UUIDs are a 404 and the samples are
+    // found under libvirt's spelling of the UUID
+    let uuid = {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        lookup(&conn, &uuid)?
+            .get_uuid_string()
+            .map_err(libvirt_error)?
+    };
+    Ok(Json(DomainMetrics {
+        samples: state.metrics.samples(&uuid),
+        interval: state.config.metrics_interval,
+        uuid,
+    }))
+}
@@ -0,0 +1,540 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use virt::{domain::Domain, storage_pool::StoragePool, storage_vol::StorageVol, sys};
+
+use super::cloudinit::{SeedRequest, build_seed, free_sata_target, seed_disk_xml, seed_volume};
+use super::domains::lookup;
+use super::pools::lookup as lookup_pool;
+use super::volumes::{volume_info, volume_xml};
+use super::{ApiResult, Libvirt, internal_error, libvirt_error, xml_escape};
+use crate::cloudinit::Seed;
+use crate::templates::{self, Template};
+use crate::{AppState, audit, auth::CurrentUser};
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct MarkTemplate {
+    description: String,
+}
+
+#[derive(Deserialize)]
+pub struct CloneTemplate {
+    /// Name of the new domain
+    name: String,
+    /// qcow2 overlays backed by the template's disks instead of full copies
+    #[serde(default)]
+    linked: bool,
+    /// Pool for the new disks – each disk's own pool when absent
+    #[serde(default)]
+    pool: Option<String>,
+    /// Hostname handed to cloud-init through a NoCloud seed CD-ROM
+    #[serde(default)]
+    hostname: Option<String>,
+    /// Further cloud-init settings for the seed
+    #[serde(default)]
+    cloud_init: Option<SeedRequest>,
+    /// Start the new domain right away
+    #[serde(default)]
+    start: bool,
+}
+
+#[derive(Serialize)]
+pub struct ClonedDomain {
+    pub uuid: String,
+    pub name: String,
+    pub linked: bool,
+    /// Paths of the new disk images
+    pub disks: Vec<String>,
+    /// Path of the cloud-init seed image
+    pub seed: Option<String>,
+}
+
+/// Byte range of the domain XML and its replacement.
+type Edit = (Range<usize>, String);
+
+/// Storage volume behind a disk's `<source>`.
+fn disk_volume(
+    conn: &Libvirt,
+    kind: &str,
+    source: roxmltree::Node<'_, '_>,
+) -> ApiResult<StorageVol> {
+    let found = match kind {
+        "file" => source
+            .attribute("file")
+            .map(|path| StorageVol::lookup_by_path(conn, path)),
+        "block" => source
+            .attribute("dev")
+            .map(|path| StorageVol::lookup_by_path(conn, path)),
+        "volume" => source
+            .attribute("pool")
+            .zip(source.attribute("volume"))
+            .map(|(pool, volume)| {
+                StoragePool::lookup_by_name(conn, pool)
+                    .and_then(|pool| StorageVol::lookup_by_name(&pool, volume))
+            }),
+        _ => None,
+    };
+    match found {
+        Some(Ok(vol)) => Ok(vol),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            "only disks that are volumes of a storage pool can be cloned".into(),
+        )),
+    }
+}
+
+/// Volume definition of a qcow2 overlay on top of `backing`.
+fn linked_volume_xml(name: &str, capacity: u64, backing: &str, backing_format: &str) -> String {
+    format!(
+        "<volume><name>{}</name><capacity unit='bytes'>{}</capacity>\
+         <target><format type='qcow2'/></target>\
+         <backingStore><path>{}</path><format type='{}'/></backingStore></volume>",
+        xml_escape(name),
+        capacity,
+        xml_escape(backing),
+        xml_escape(backing_format)
+    )
+}
+
+/// `<driver>` element with its attributes, `type` replaced by qcow2.
+fn qcow2_driver(driver: Option<roxmltree::Node<'_, '_>>) -> String {
+    let mut out = String::from("<driver");
+    let mut named = false;
+    for attr in driver.iter().flat_map(|d| d.attributes()) {
+        if attr.name() == "type" {
+            continue;
+        }
+        named |= attr.name() == "name";
+        out.push_str(&format!(" {}='{}'", attr.name(), xml_escape(attr.value())));
+    }
+    if !named {
+        out.push_str(" name='qemu'");
+    }
+    out.push_str(" type='qcow2'/>");
+    out
+}
+
+/// Paths of the template's writable disk images – what linked clones are
+/// backed by.
+fn template_images(conn: &Libvirt, dom: &Domain) -> ApiResult<Vec<String>> {
+    let xml = dom
+        .get_xml_desc(sys::VIR_DOMAIN_XML_INACTIVE)
+        .map_err(libvirt_error)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(internal_error)?;
+    let mut paths = Vec::new();
+    for disk in doc.descendants().filter(|n| n.has_tag_name("disk")) {
+        let child = |name: &str| disk.children().find(|n| n.has_tag_name(name));
+        if disk.attribute("device").unwrap_or("disk") != "disk"
+            || child("readonly").is_some()
+            || child("shareable").is_some()
+        {
+            continue;
+        }
+        let kind = disk.attribute("type").unwrap_or("file");
+        if let Some(vol) = child("source").and_then(|s| disk_volume(conn, kind, s).ok()) {
+            paths.push(vol.get_path().map_err(libvirt_error)?);
+        }
+    }
+    Ok(paths)
+}
+
+/// Paths of the volumes backed by one of the template's disk images, i.e.
+/// the overlays of its linked clones.
+fn linked_overlays(conn: &Libvirt, dom: &Domain) -> ApiResult<Vec<String>> {
+    let images = template_images(conn, dom)?;
+    let mut overlays = Vec::new();
+    if images.is_empty() {
+        return Ok(overlays);
+    }
+    for pool in conn.list_all_storage_pools(0).map_err(libvirt_error)? {
+        if !pool.is_active().map_err(libvirt_error)? {
+            continue;
+        }
+        for vol in pool.list_all_volumes(0).map_err(libvirt_error)? {
+            let Ok(xml) = vol.get_xml_desc(0) else {
+                continue;
+            };
+            let Ok(doc) = roxmltree::Document::parse(&xml) else {
+                continue;
+            };
+            let backing = doc
+                .descendants()
+                .find(|n| n.has_tag_name("backingStore"))
+                .and_then(|b| b.children().find(|n| n.has_tag_name("path")))
+                .and_then(|p| p.text());
+            if backing.is_some_and(|path| images.iter().any(|image| image == path)) {
+                overlays.push(vol.get_path().map_err(libvirt_error)?);
+            }
+        }
+    }
+    Ok(overlays)
+}
+
+/// Linked clones write to qcow2 overlays on top of the template's disk
+/// images, so the images must not change while any clone exists: the
+/// template may then neither run nor stop being a template.
+pub(crate) fn ensure_no_linked_clones(conn: &Libvirt, dom: &Domain, action: &str) -> ApiResult<()> {
+    let overlays = linked_overlays(conn, dom)?;
+    if overlays.is_empty() {
+        return Ok(());
+    }
+    Err((
+        StatusCode::CONFLICT,
+        format!(
+            "cannot {} the template while linked clones use its disks ({}); delete them first",
+            action,
+            overlays.join(", ")
+        ),
+    ))
+}
+
+/// Copy (or overlay) every writable disk of the template into new volumes,
+/// recording the XML edits that point the clone at them. Created volumes
+/// are pushed to `created` as they appear so a failure can remove them.
+/// Returns the name of the first pool a disk went to.
+fn clone_disks(
+    conn: &Libvirt,
+    doc: &roxmltree::Document<'_>,
+    req: &CloneTemplate,
+    target_pool: Option<&StoragePool>,
+    created: &mut Vec<StorageVol>,
+    edits: &mut Vec<Edit>,
+) -> ApiResult<Option<String>> {
+    let mut first_pool = None;
+    for disk in doc.descendants().filter(|n| n.has_tag_name("disk")) {
+        let child = |name: &str| disk.children().find(|n| n.has_tag_name(name));
+        // CD-ROMs and shared disks stay shared with the template
+        if disk.attribute("device").unwrap_or("disk") != "disk"
+            || child("readonly").is_some()
+            || child("shareable").is_some()
+        {
+            continue;
+        }
+        let Some(source) = child("source") else {
+            continue;
+        };
+        let target = child("target")
+            .and_then(|t| t.attribute("dev"))
+            .unwrap_or("disk");
+        let kind = disk.attribute("type").unwrap_or("file");
+        let vol = disk_volume(conn, kind, source)?;
+        let own_pool;
+        let pool = match target_pool {
+            Some(pool) => pool,
+            None => {
+                own_pool = StoragePool::lookup_by_volume(&vol).map_err(libvirt_error)?;
+                &own_pool
+            }
+        };
+        let pool_name = pool.get_name().map_err(libvirt_error)?;
+        let info = volume_info(&vol)?;
+        let format = info.format.clone().unwrap_or_else(|| "raw".into());
+
+        let clone = if req.linked {
+            let name = format!("{}-{}.qcow2", req.name, target);
+            let xml = linked_volume_xml(&name, info.capacity, &info.path, &format);
+            StorageVol::create_xml(pool, &xml, 0)
+        } else {
+            let extension = if format == "qcow2" { "qcow2" } else { "img" };
+            let name = format!("{}-{}.{}", req.name, target, extension);
+            let xml = volume_xml(&name, &format, info.capacity);
+            StorageVol::create_xml_from(pool, &xml, &vol, 0)
+        }
+        .map_err(libvirt_error)?;
+        created.push(clone);
+        let clone_info = volume_info(created.last().unwrap())?;
+
+        let mut source_xml = match kind {
+            "volume" => format!(
+                "<source pool='{}' volume='{}'/>",
+                xml_escape(&pool_name),
+                xml_escape(&clone_info.name)
+            ),
+            "block" => format!("<source dev='{}'/>", xml_escape(&clone_info.path)),
+            _ => format!("<source file='{}'/>", xml_escape(&clone_info.path)),
+        };
+        // An overlay is qcow2 whatever the template's disk is
+        let driver = child("driver");
+        if req.linked && driver.and_then(|d| d.attribute("type")) != Some("qcow2") {
+            match driver {
+                Some(driver) => edits.push((driver.range(), qcow2_driver(Some(driver)))),
+                None => source_xml.insert_str(0, &qcow2_driver(None)),
+            }
+        }
+        edits.push((source.range(), source_xml));
+        // The template's backing chain does not describe the new image
+        if let Some(backing) = child("backingStore") {
+            edits.push((backing.range(), String::new()));
+        }
+        if first_pool.is_none() {
+            first_pool = Some(pool_name);
+        }
+    }
+    Ok(first_pool)
+}
+
+/// Create the clone's volumes and seed, then define it. Returns the new
+/// domain and the path of its seed image, if any.
+fn define_clone(
+    conn: &Libvirt,
+    xml: &str,
+    req: &CloneTemplate,
+    seed: Option<&Seed>,
+    created: &mut Vec<StorageVol>,
+) -> ApiResult<(Domain, Option<String>)> {
+    let doc = roxmltree::Document::parse(xml).map_err(internal_error)?;
+    let target_pool = req
+        .pool
+        .as_deref()
+        .map(|name| lookup_pool(conn, name))
+        .transpose()?;
+    let mut edits: Vec<Edit> = Vec::new();
+    let first_pool = clone_disks(conn, &doc, req, target_pool.as_ref(), created, &mut edits)?;
+
+    let root = doc.root_element();
+    if let Some(name) = root.children().find(|n| n.has_tag_name("name")) {
+        edits.push((
+            name.range(),
+            format!("<name>{}</name>", xml_escape(&req.name)),
+        ));
+    }
+    // libvirt generates a new UUID, MAC addresses and NVRAM file
+    if let Some(uuid) = root.children().find(|n| n.has_tag_name("uuid")) {
+        edits.push((uuid.range(), String::new()));
+    }
+    for mac in doc
+        .descendants()
+        .filter(|n| n.has_tag_name("mac"))
+        .filter(|n| n.parent().is_some_and(|p| p.has_tag_name("interface")))
+    {
+        edits.push((mac.range(), String::new()));
+    }
+    if let Some(nvram) = doc.descendants().find(|n| n.has_tag_name("nvram")) {
+        edits.push((nvram.range(), String::new()));
+    }
+
+    let mut seed_path = None;
+    if let Some(seed) = seed {
+        let pool = match (target_pool, first_pool) {
+            (Some(pool), _) => pool,
+            (None, Some(name)) => lookup_pool(conn, &name)?,
+            (None, None) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "pool is required for the cloud-init seed".into(),
+                ));
+            }
+        };
+        let vol = seed_volume(conn, &pool, &req.name, seed)?;
+        let path = vol.get_path().map_err(libvirt_error)?;
+        created.push(vol);
+        // The template's own seed would compete with the clone's
+        for disk in doc.descendants().filter(|n| n.has_tag_name("disk")) {
+            let source = disk
+                .children()
+                .find(|n| n.has_tag_name("source"))
+                .and_then(|s| s.attribute("file"));
+            if disk.attribute("device") == Some("cdrom")
+                && source.is_some_and(|file| file.ends_with("-cidata.iso"))
+            {
+                edits.push((disk.range(), String::new()));
+            }
+        }
+        let devices = root.children().find(|n| n.has_tag_name("devices")).ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unexpected domain XML".to_string(),
+        ))?;
+        let end = xml[..devices.range().end].rfind("</devices>").ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unexpected domain XML".to_string(),
+        ))?;
+        edits.push((
+            end..end,
+            seed_disk_xml(&path, &free_sata_target(&doc)?, "sata"),
+        ));
+        seed_path = Some(path);
+    }
+
+    // Apply back to front so earlier ranges stay valid
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut clone_xml = xml.to_string();
+    for (range, replacement) in edits {
+        clone_xml.replace_range(range, &replacement);
+    }
+    let dom = Domain::define_xml(conn, &clone_xml).map_err(libvirt_error)?;
+    Ok((dom, seed_path))
+}
+
+fn clone_domain(
+    uri: &str,
+    uuid: &str,
+    req: &CloneTemplate,
+    seed: Option<Seed>,
+) -> ApiResult<ClonedDomain> {
+    if req.name.is_empty() || req.name.contains(['\0', '/']) {
+        return Err((StatusCode::BAD_REQUEST, "invalid name".into()));
+    }
+    let conn = Libvirt::open(uri)?;
+    let template = lookup(&conn, uuid)?;
+    if template.is_active().map_err(libvirt_error)? {
+        return Err((
+            StatusCode::CONFLICT,
+            "shut the template down before cloning it".into(),
+        ));
+    }
+    if Domain::lookup_by_name(&conn, &req.name).is_ok() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("a domain named {} already exists", req.name),
+        ));
+    }
+    let xml = template
+        .get_xml_desc(sys::VIR_DOMAIN_XML_INACTIVE | sys::VIR_DOMAIN_XML_SECURE)
+        .map_err(libvirt_error)?;
+
+    let mut created = Vec::new();
+    let (dom, seed) = match define_clone(&conn, &xml, req, seed.as_ref(), &mut created) {
+        Ok(defined) => defined,
+        Err(e) => {
+            for vol in &created {
+                let _ = vol.delete(0);
+            }
+            return Err(e);
+        }
+    };
+    let disks = created
+        .iter()
+        .filter_map(|vol| vol.get_path().ok())
+        .filter(|path| Some(path) != seed.as_ref())
+        .collect();
+    if req.start {
+        dom.create().map_err(libvirt_error)?;
+    }
+    Ok(ClonedDomain {
+        uuid: dom.get_uuid_string().map_err(libvirt_error)?,
+        name: req.name.clone(),
+        linked: req.linked,
+        disks,
+        seed,
+    })
+}
+
+// ---------------------------------------------------------------------
+// GET /api/templates – templates whose domain still exists
+// ---------------------------------------------------------------------
+pub async fn list_templates(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<Template>>> {
+    let all = templates::list(&state.pool).await.map_err(internal_error)?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(
+        all.into_iter()
+            .filter(|t| lookup(&conn, &t.domain_uuid).is_ok())
+            .collect(),
+    ))
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/domains/{uuid}/template – mark as template (turns autostart off)
+// DELETE /api/domains/{uuid}/template – back to a plain domain, refused
+// while linked clones exist
+// ---------------------------------------------------------------------
+pub async fn mark_template(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(req): Json<MarkTemplate>,
+) -> ApiResult<Json<Template>> {
+    let (name, uuid) = {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        let dom = lookup(&conn, &uuid)?;
+        // libvirt must not boot it behind our back once linked clones exist
+        if dom.get_autostart().unwrap_or(false) {
+            dom.set_autostart(false).map_err(libvirt_error)?;
+        }
+        (
+            dom.get_name().map_err(libvirt_error)?,
+            dom.get_uuid_string().map_err(libvirt_error)?,
+        )
+    };
+    templates::mark(&state.pool, &uuid, &name, &req.description, &user.username)
+        .await
+        .map_err(internal_error)?;
+    audit::record(&state.pool, &user.username, "template.mark", &name).await;
+    templates::get(&state.pool, &uuid)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "template not found".into()))
+}
+
+pub async fn unmark_template(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> ApiResult<StatusCode> {
+    // A template whose domain is gone can always be removed
+    {
+        let conn = Libvirt::open(&state.config.libvirt_uri)?;
+        if let Ok(dom) = lookup(&conn, &uuid) {
+            ensure_no_linked_clones(&conn, &dom, "unmark")?;
+        }
+    }
+    if !templates::unmark(&state.pool, &uuid)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err((StatusCode::NOT_FOUND, "domain is not a template".into()));
+    }
+    audit::record(&state.pool, &user.username, "template.unmark", &uuid).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// POST /api/templates/{uuid}/clone – new domain from a template
+// ---------------------------------------------------------------------
+pub async fn clone_template(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+    Json(mut req): Json<CloneTemplate>,
+) -> ApiResult<(StatusCode, Json<ClonedDomain>)> {
+    let template = templates::get(&state.pool, &uuid)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "template not found".to_string()))?;
+    // A hostname alone is enough for a seed
+    let seed = match (req.cloud_init.take(), req.hostname.clone()) {
+        (None, None) => None,
+        (cloud_init, hostname) => {
+            let mut seed = cloud_init.unwrap_or_default();
+            seed.hostname = seed.hostname.or(hostname);
+            Some(build_seed(&state.pool, &user, &req.name, seed).await?)
+        }
+    };
+    // Copying disks can take a while
+    let uri = state.config.libvirt_uri.clone();
+    let cloned =
+        tokio::task::spawn_blocking(move || clone_domain(&uri, &template.domain_uuid, &req, seed))
+            .await
+            .map_err(internal_error)??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "template.clone",
+        &format!(
+            "{} -> {} ({})",
+            uuid,
+            cloned.name,
+            if cloned.linked { "linked" } else { "full" }
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(cloned)))
+}
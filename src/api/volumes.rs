@@ -0,0 +1,491 @@
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use virt::{storage_pool::StoragePool, storage_vol::StorageVol, stream::Stream, sys};
+
+use super::pools::lookup as lookup_pool;
+use super::{ApiResult, Libvirt, internal_error, libvirt_error, reject_nul, xml_escape};
+use crate::{AppState, audit, auth::CurrentUser};
+
+/// Bytes moved per libvirt stream call during upload/download.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+// ---------------------------------------------------------------------
+// Storage volume information returned as JSON
+// ---------------------------------------------------------------------
+#[derive(Serialize)]
+pub struct VolumeInfo {
+    pub name: String,
+    pub key: String,
+    pub path: String,
+    pub kind: &'static str,
+    /// `target/format` from the volume XML, e.g. `qcow2`, `raw` or `iso`
+    pub format: Option<String>,
+    pub capacity: u64,
+    pub allocation: u64,
+}
+
+/// Disk image formats a new volume can be created with.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeFormat {
+    Qcow2,
+    Raw,
+}
+
+impl VolumeFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            VolumeFormat::Qcow2 => "qcow2",
+            VolumeFormat::Raw => "raw",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateVolume {
+    name: String,
+    format: VolumeFormat,
+    /// Size in bytes
+    capacity: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CloneVolume {
+    name: String,
+    /// Format of the copy; defaults to the format of the source volume
+    #[serde(default)]
+    format: Option<VolumeFormat>,
+}
+
+#[derive(Deserialize)]
+pub struct ResizeVolume {
+    /// New size in bytes
+    capacity: u64,
+    /// Allow a smaller capacity – data past the new end is lost
+    #[serde(default)]
+    shrink: bool,
+}
+
+/// Human readable name of a `virStorageVolType`.
+fn volume_kind_name(kind: u32) -> &'static str {
+    match kind {
+        0 => "file",
+        1 => "block",
+        2 => "dir",
+        3 => "network",
+        4 => "netdir",
+        5 => "ploop",
+        _ => "unknown",
+    }
+}
+
+pub(crate) fn volume_xml(name: &str, format: &str, capacity: u64) -> String {
+    format!(
+        "<volume><name>{}</name><capacity unit='bytes'>{}</capacity>\
+         <target><format type='{}'/></target></volume>",
+        xml_escape(name),
+        capacity,
+        xml_escape(format)
+    )
+}
+
+/// `target/format/@type` of a volume definition.
+pub(crate) fn volume_format(xml: &str) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    doc.root_element()
+        .children()
+        .find(|n| n.has_tag_name("target"))?
+        .children()
+        .find(|n| n.has_tag_name("format"))?
+        .attribute("type")
+        .map(str::to_string)
+}
+
+pub(crate) fn volume_info(vol: &StorageVol) -> ApiResult<VolumeInfo> {
+    let info = vol.get_info().map_err(libvirt_error)?;
+    Ok(VolumeInfo {
+        name: vol.get_name().map_err(libvirt_error)?,
+        key: vol.get_key().map_err(libvirt_error)?,
+        path: vol.get_path().map_err(libvirt_error)?,
+        kind: volume_kind_name(info.kind),
+        format: vol.get_xml_desc(0).ok().and_then(|xml| volume_format(&xml)),
+        capacity: info.capacity,
+        allocation: info.allocation,
+    })
+}
+
+/// Find the volume `name` in `pool`.
+pub(crate) fn lookup(conn: &Libvirt, pool: &str, name: &str) -> ApiResult<StorageVol> {
+    reject_nul(name, "volume name")?;
+    let pool = lookup_pool(conn, pool)?;
+    StorageVol::lookup_by_name(&pool, name).map_err(libvirt_error)
+}
+
+/// Create the volume `name` in `pool` holding `data`, e.g. a generated ISO
+/// image. The volume is removed again when writing fails.
+pub(crate) fn create_volume_with(
+    conn: &Libvirt,
+    pool: &StoragePool,
+    name: &str,
+    data: &[u8],
+) -> ApiResult<StorageVol> {
+    let xml = volume_xml(name, "raw", data.len() as u64);
+    let vol = StorageVol::create_xml(pool, &xml, 0).map_err(libvirt_error)?;
+    let written = Stream::new(conn, 0).and_then(|stream| {
+        vol.upload(&stream, 0, data.len() as u64, 0)?;
+        let mut rest = data;
+        while !rest.is_empty() {
+            rest = &rest[stream.send(rest)?..];
+        }
+        stream.finish()
+    });
+    if let Err(e) = written {
+        let _ = vol.delete(0);
+        return Err(libvirt_error(e));
+    }
+    Ok(vol)
+}
+
+fn pool_volumes(pool: &StoragePool) -> ApiResult<Vec<VolumeInfo>> {
+    let mut out = pool
+        .list_all_volumes(0)
+        .map_err(libvirt_error)?
+        .iter()
+        .map(volume_info)
+        .collect::<ApiResult<Vec<_>>>()?;
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/pools/{name}/volumes – volumes of an active pool
+// ---------------------------------------------------------------------
+pub async fn list_volumes(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<Vec<VolumeInfo>>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(pool_volumes(&lookup_pool(&conn, &name)?)?))
+}
+
+// ---------------------------------------------------------------------
+// GET /api/pools/{name}/volumes/{vol}
+// ---------------------------------------------------------------------
+pub async fn get_volume(
+    _user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+) -> ApiResult<Json<VolumeInfo>> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    Ok(Json(volume_info(&lookup(&conn, &name, &vol)?)?))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools/{name}/volumes – new empty qcow2/raw volume
+// ---------------------------------------------------------------------
+pub async fn create_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<CreateVolume>,
+) -> ApiResult<(StatusCode, Json<VolumeInfo>)> {
+    reject_nul(&req.name, "volume name")?;
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    let pool = lookup_pool(&conn, &name)?;
+    let xml = volume_xml(&req.name, req.format.as_str(), req.capacity);
+    let vol = StorageVol::create_xml(&pool, &xml, 0).map_err(libvirt_error)?;
+    let info = volume_info(&vol)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.create",
+        &format!(
+            "{}/{} {} {}",
+            name,
+            info.name,
+            req.format.as_str(),
+            req.capacity
+        ),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools/{name}/volumes/{vol}/clone – full copy in the same pool
+// ---------------------------------------------------------------------
+pub async fn clone_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+    Json(req): Json<CloneVolume>,
+) -> ApiResult<(StatusCode, Json<VolumeInfo>)> {
+    reject_nul(&req.name, "volume name")?;
+    let uri = state.config.libvirt_uri.clone();
+    let (pool_name, vol_name) = (name.clone(), vol.clone());
+    // Copying the data takes as long as the volume is large
+    let info = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        let pool = lookup_pool(&conn, &pool_name)?;
+        let source = lookup(&conn, &pool_name, &vol_name)?;
+        let source_info = volume_info(&source)?;
+        let format = match req.format {
+            Some(format) => format.as_str().to_string(),
+            None => source_info.format.unwrap_or_else(|| "raw".into()),
+        };
+        let xml = volume_xml(&req.name, &format, source_info.capacity);
+        let clone = StorageVol::create_xml_from(&pool, &xml, &source, 0).map_err(libvirt_error)?;
+        volume_info(&clone)
+    })
+    .await
+    .map_err(internal_error)??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.clone",
+        &format!("{}/{} -> {}", name, vol, info.name),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools/{name}/volumes/{vol}/resize – {"capacity": bytes}
+// ---------------------------------------------------------------------
+pub async fn resize_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+    Json(req): Json<ResizeVolume>,
+) -> ApiResult<Json<VolumeInfo>> {
+    let uri = state.config.libvirt_uri.clone();
+    let (pool_name, vol_name) = (name.clone(), vol.clone());
+    let flags = if req.shrink {
+        sys::VIR_STORAGE_VOL_RESIZE_SHRINK
+    } else {
+        0
+    };
+    // Preallocated volumes are filled while resizing
+    let info = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        let volume = lookup(&conn, &pool_name, &vol_name)?;
+        volume.resize(req.capacity, flags).map_err(libvirt_error)?;
+        volume_info(&volume)
+    })
+    .await
+    .map_err(internal_error)??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.resize",
+        &format!("{}/{} {}", name, vol, req.capacity),
+    )
+    .await;
+    Ok(Json(info))
+}
+
+// ---------------------------------------------------------------------
+// POST /api/pools/{name}/volumes/{vol}/wipe – overwrite with zeroes
+// ---------------------------------------------------------------------
+pub async fn wipe_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+) -> ApiResult<Json<VolumeInfo>> {
+    let uri = state.config.libvirt_uri.clone();
+    let (pool_name, vol_name) = (name.clone(), vol.clone());
+    // Writes the whole volume
+    let info = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        let volume = lookup(&conn, &pool_name, &vol_name)?;
+        volume.wipe(0).map_err(libvirt_error)?;
+        volume_info(&volume)
+    })
+    .await
+    .map_err(internal_error)??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.wipe",
+        &format!("{}/{}", name, vol),
+    )
+    .await;
+    Ok(Json(info))
+}
+
+// ---------------------------------------------------------------------
+// DELETE /api/pools/{name}/volumes/{vol}
+// ---------------------------------------------------------------------
+pub async fn delete_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    let conn = Libvirt::open(&state.config.libvirt_uri)?;
+    lookup(&conn, &name, &vol)?
+        .delete(0)
+        .map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.delete",
+        &format!("{}/{}", name, vol),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------
+// GET /api/pools/{name}/volumes/{vol}/content – stream the volume out
+// ---------------------------------------------------------------------
+pub async fn download_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+) -> ApiResult<Response> {
+    let conn = Libvirt::open_long_lived(&state.config.libvirt_uri)?;
+    let volume = lookup(&conn, &name, &vol)?;
+    let stream = Stream::new(&conn, 0).map_err(libvirt_error)?;
+    // offset 0 and length 0 transfer the whole volume
+    volume.download(&stream, 0, 0, 0).map_err(libvirt_error)?;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.download",
+        &format!("{}/{}", name, vol),
+    )
+    .await;
+
+    // libvirt streams block, so a worker thread pumps chunks into a channel
+    // that backs the response body.
+    let (tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(4);
+    tokio::task::spawn_blocking(move || {
+        let _conn = conn;
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            match stream.recv(&mut buf) {
+                Ok(0) => {
+                    let _ = stream.finish();
+                    break;
+                }
+                Ok(n) => {
+                    if tx
+                        .blocking_send(Ok(Bytes::copy_from_slice(&buf[..n])))
+                        .is_err()
+                    {
+                        // The client went away
+                        let _ = stream.abort();
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(std::io::Error::other(e.message().to_string())));
+                    let _ = stream.abort();
+                    break;
+                }
+            }
+        }
+    });
+    let body = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    let filename = vol.replace(['"', '\\', '\r', '\n'], "_");
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+// ---------------------------------------------------------------------
+// PUT /api/pools/{name}/volumes/{vol}/content – replace the volume's
+// contents with the request body (e.g. an ISO image)
+// ---------------------------------------------------------------------
+pub async fn upload_volume(
+    user: CurrentUser,
+    State(state): State<AppState>,
+    Path((name, vol)): Path<(String, String)>,
+    body: Body,
+) -> ApiResult<Json<VolumeInfo>> {
+    let conn = Libvirt::open_long_lived(&state.config.libvirt_uri)?;
+    let volume = lookup(&conn, &name, &vol)?;
+    let stream = Stream::new(&conn, 0).map_err(libvirt_error)?;
+    volume.upload(&stream, 0, 0, 0).map_err(libvirt_error)?;
+
+    // `None` marks the end of the body; if the sender disappears without
+    // it the upload was interrupted and the stream is aborted.
+    let (tx, mut rx) = mpsc::channel::<Option<Bytes>>(4);
+    let worker = tokio::task::spawn_blocking(move || -> ApiResult<u64> {
+        let mut written = 0u64;
+        loop {
+            match rx.blocking_recv() {
+                Some(Some(chunk)) => {
+                    let mut data = &chunk[..];
+                    while !data.is_empty() {
+                        match stream.send(data) {
+                            Ok(n) => data = &data[n..],
+                            Err(e) => {
+                                let _ = stream.abort();
+                                return Err(libvirt_error(e));
+                            }
+                        }
+                    }
+                    written += chunk.len() as u64;
+                }
+                Some(None) => {
+                    stream.finish().map_err(libvirt_error)?;
+                    return Ok(written);
+                }
+                None => {
+                    let _ = stream.abort();
+                    return Err((StatusCode::BAD_REQUEST, "upload interrupted".into()));
+                }
+            }
+        }
+    });
+
+    let mut chunks = body.into_data_stream();
+    let mut complete = true;
+    while let Some(chunk) = chunks.next().await {
+        let sent = match chunk {
+            Ok(chunk) => tx.send(Some(chunk)).await.is_ok(),
+            Err(_) => false,
+        };
+        if !sent {
+            complete = false;
+            break;
+        }
+    }
+    if complete {
+        let _ = tx.send(None).await;
+    }
+    drop(tx);
+
+    let written = worker
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+    audit::record(
+        &state.pool,
+        &user.username,
+        "volume.upload",
+        &format!("{}/{} {} bytes", name, vol, written),
+    )
+    .await;
+    Ok(Json(volume_info(&volume)?))
+}
@@ -0,0 +1,41 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// audit.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// One row of the audit log.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: String,
+    pub username: String,
+    pub action: String,
+    pub detail: String,
+}
+
+/// Append an entry to the audit log.  Failures are reported but never abort
+/// the operation that is being audited.
+pub async fn record(pool: &SqlitePool, username: &str, action: &str, detail: &str) {
+    let result = sqlx::query("INSERT INTO audit_log (username, action, detail) VALUES (?, ?, ?)")
+        .bind(username)
+        .bind(action)
+        .bind(detail)
+        .execute(pool)
+        .await;
+    if let Err(e) = result {
+        eprintln!("⚠️  Could not write audit record `{}`: {}", action, e);
+    }
+}
+
+/// Entries recorded at or after `since` (an SQLite timestamp such as
+/// `2025-10-01` or `2025-10-01 12:00:00`), oldest first.
+pub async fn export(pool: &SqlitePool, since: Option<&str>) -> anyhow::Result<Vec<AuditEntry>> {
+    let entries = sqlx::query_as(
+        "SELECT id, at, username, action, detail FROM audit_log WHERE at >= ? ORDER BY id",
+    )
+    .bind(since.unwrap_or(""))
+    .fetch_all(pool)
+    .await?;
+    Ok(entries)
+}
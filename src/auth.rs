@@ -0,0 +1,70 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// auth.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use axum::{extract::FromRequestParts, http::StatusCode, http::request::Parts};
+use axum_session::Session;
+use axum_session_sqlx::SessionSqlitePool;
+
+use crate::{AppState, users};
+
+/// The logged‑in user.  Using it as a handler argument rejects anonymous
+/// requests (and users that were disabled since logging in) with 401.
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub id: i64,
+    pub username: String,
+    pub admin: bool,
+}
+
+impl CurrentUser {
+    /// Reject the request with 403 unless the user is an administrator.
+    pub fn require_admin(&self) -> Result<(), (StatusCode, String)> {
+        if self.admin {
+            Ok(())
+        } else {
+            Err((
+                StatusCode::FORBIDDEN,
+                "administrator rights required".to_string(),
+            ))
+        }
+    }
+}
+
+/// Identity of a request the server dispatches to itself on a user's behalf
+/// (scheduled jobs).  It takes the place of the session cookie and can only be
+/// set from inside the process.
+#[derive(Debug, Clone, Copy)]
+pub struct RunAs(pub i64);
+
+impl FromRequestParts<AppState> for CurrentUser {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let unauthorized = || (StatusCode::UNAUTHORIZED, "login required".to_string());
+
+        let user_id = match parts.extensions.get::<RunAs>() {
+            Some(RunAs(id)) => *id,
+            None => {
+                let session = Session::<SessionSqlitePool>::from_request_parts(parts, state)
+                    .await
+                    .map_err(|(code, msg)| (code, msg.to_string()))?;
+                session.get::<i64>("user_id").ok_or_else(unauthorized)?
+            }
+        };
+
+        let user = users::find_by_id(&state.pool, user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .filter(|u| !u.disabled)
+            .ok_or_else(unauthorized)?;
+
+        Ok(CurrentUser {
+            id: user.id,
+            username: user.username,
+            admin: user.admin,
+        })
+    }
+}
@@ -0,0 +1,203 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// backups.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const COLUMNS: &str = "id, domain_uuid, domain_name, kind, checkpoint, parent_checkpoint, \
+    target_dir, files, status, bytes_total, bytes_processed, error, started_by, started_at, \
+    finished_at";
+
+/// One backup job and its outcome.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Backup {
+    pub id: i64,
+    pub domain_uuid: String,
+    pub domain_name: String,
+    pub kind: String,
+    pub checkpoint: String,
+    pub parent_checkpoint: Option<String>,
+    pub target_dir: String,
+    pub files: String,
+    pub status: String,
+    pub bytes_total: Option<i64>,
+    pub bytes_processed: Option<i64>,
+    pub error: Option<String>,
+    pub started_by: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Everything known about a backup when it is started.
+pub struct NewBackup<'a> {
+    pub domain_uuid: &'a str,
+    pub domain_name: &'a str,
+    pub kind: &'a str,
+    pub checkpoint: &'a str,
+    pub parent_checkpoint: Option<&'a str>,
+    pub target_dir: &'a str,
+    pub files: &'a [String],
+    pub started_by: &'a str,
+}
+
+/// Record a backup that has just been started; returns its id.
+pub async fn insert(pool: &SqlitePool, backup: &NewBackup<'_>) -> anyhow::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO backups (domain_uuid, domain_name, kind, checkpoint, parent_checkpoint, \
+         target_dir, files, status, started_by) VALUES (?, ?, ?, ?, ?, ?, ?, 'running', ?)",
+    )
+    .bind(backup.domain_uuid)
+    .bind(backup.domain_name)
+    .bind(backup.kind)
+    .bind(backup.checkpoint)
+    .bind(backup.parent_checkpoint)
+    .bind(backup.target_dir)
+    .bind(backup.files.join("\n"))
+    .bind(backup.started_by)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn update_progress(
+    pool: &SqlitePool,
+    id: i64,
+    processed: Option<u64>,
+    total: Option<u64>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE backups SET bytes_processed = COALESCE(?, bytes_processed), \
+         bytes_total = COALESCE(?, bytes_total) WHERE id = ?",
+    )
+    .bind(processed.map(|v| v as i64))
+    .bind(total.map(|v| v as i64))
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark a backup as `completed`, `failed` or `cancelled`.
+pub async fn finish(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    error: Option<&str>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE backups SET status = ?, error = ?, finished_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(status)
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> anyhow::Result<Option<Backup>> {
+    let backup = sqlx::query_as(&format!("SELECT {} FROM backups WHERE id = ?", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(backup)
+}
+
+/// Backup history of a domain, newest first.
+pub async fn list_for_domain(pool: &SqlitePool, uuid: &str) -> anyhow::Result<Vec<Backup>> {
+    let backups = sqlx::query_as(&format!(
+        "SELECT {} FROM backups WHERE domain_uuid = ? ORDER BY id DESC",
+        COLUMNS
+    ))
+    .bind(uuid)
+    .fetch_all(pool)
+    .await?;
+    Ok(backups)
+}
+
+/// Most recent successful backup – the base for the next incremental one.
+pub async fn latest_completed(pool: &SqlitePool, uuid: &str) -> anyhow::Result<Option<Backup>> {
+    let backup = sqlx::query_as(&format!(
+        "SELECT {} FROM backups WHERE domain_uuid = ? AND status = 'completed' \
+         ORDER BY id DESC LIMIT 1",
+        COLUMNS
+    ))
+    .bind(uuid)
+    .fetch_optional(pool)
+    .await?;
+    Ok(backup)
+}
+
+/// Backups still marked as running, e.g. after a restart.
+pub async fn running(pool: &SqlitePool) -> anyhow::Result<Vec<Backup>> {
+    let backups = sqlx::query_as(&format!(
+        "SELECT {} FROM backups WHERE status = 'running' ORDER BY id",
+        COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(backups)
+}
+
+/// Number of full backups to keep for a domain, if a policy is set.
+pub async fn retention(pool: &SqlitePool, uuid: &str) -> anyhow::Result<Option<i64>> {
+    let keep: Option<(i64,)> =
+        sqlx::query_as("SELECT keep_full FROM backup_retention WHERE domain_uuid = ?")
+            .bind(uuid)
+            .fetch_optional(pool)
+            .await?;
+    Ok(keep.map(|(keep,)| keep))
+}
+
+/// Set (or with `None` remove) the retention policy of a domain.
+pub async fn set_retention(
+    pool: &SqlitePool,
+    uuid: &str,
+    keep_full: Option<i64>,
+) -> anyhow::Result<()> {
+    match keep_full {
+        Some(keep) => {
+            sqlx::query(
+                "INSERT INTO backup_retention (domain_uuid, keep_full) VALUES (?, ?) \
+                 ON CONFLICT (domain_uuid) DO UPDATE SET keep_full = excluded.keep_full",
+            )
+            .bind(uuid)
+            .bind(keep)
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM backup_retention WHERE domain_uuid = ?")
+                .bind(uuid)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Finished backups older than the `keep_full`-th newest completed full
+/// backup. Incrementals after that full backup stay, since they need it.
+pub async fn expired(pool: &SqlitePool, uuid: &str, keep_full: i64) -> anyhow::Result<Vec<Backup>> {
+    let backups = sqlx::query_as(&format!(
+        "SELECT {} FROM backups WHERE domain_uuid = ? AND status != 'running' AND id < ( \
+           SELECT id FROM backups WHERE domain_uuid = ? AND kind = 'full' \
+           AND status = 'completed' ORDER BY id DESC LIMIT 1 OFFSET ? \
+         ) ORDER BY id",
+        COLUMNS
+    ))
+    .bind(uuid)
+    .bind(uuid)
+    .bind(keep_full.max(1) - 1)
+    .fetch_all(pool)
+    .await?;
+    Ok(backups)
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM backups WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,310 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// cli.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use clap::{Parser, Subcommand, ValueEnum};
+use sqlx::SqlitePool;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use crate::{audit, db, hosts, users};
+
+/// rust-manager – web based libvirt manager
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Config file (overrides RUST_MANAGER_CONFIG)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web server (the default)
+    Serve,
+    /// Manage users
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Inspect and apply database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Manage registered hypervisors
+    #[command(subcommand)]
+    Hosts(HostsCommand),
+    /// Manage web sessions
+    #[command(subcommand)]
+    Sessions(SessionsCommand),
+    /// Read the audit log
+    #[command(subcommand)]
+    Audit(AuditCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserCommand {
+    /// Create a user (password read from stdin unless --password is given)
+    Add {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password (read from stdin unless --password is given)
+    Passwd {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Disable a user so they can no longer log in
+    Disable {
+        username: String,
+        /// Re-enable the user instead
+        #[arg(long)]
+        enable: bool,
+    },
+    /// Grant administrator rights, e.g. to set secret values
+    Admin {
+        username: String,
+        /// Revoke the rights instead
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// List all users
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Show applied and pending migrations
+    Status,
+    /// Apply all pending migrations
+    Up,
+    /// Revert the newest migration, or everything after --target
+    Down {
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HostsCommand {
+    /// Register a hypervisor, e.g. `hosts add kvm2 qemu+ssh://kvm2/system`
+    Add { name: String, uri: String },
+    /// List registered hypervisors
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SessionsCommand {
+    /// Delete expired sessions (all sessions with --all)
+    Purge {
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Write the audit log to stdout
+    Export {
+        /// Only entries at or after this timestamp (e.g. 2025-10-01)
+        #[arg(long)]
+        since: Option<String>,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Json,
+    Csv,
+}
+
+/// Audit name used for changes made from the command line.
+const CLI_ACTOR: &str = "cli";
+
+/// Run an administrative subcommand against the database.
+pub async fn run(command: Command, pool: &SqlitePool) -> anyhow::Result<()> {
+    match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::User(cmd) => user(cmd, pool).await,
+        Command::Migrate(cmd) => migrate(cmd, pool).await,
+        Command::Hosts(cmd) => hosts(cmd, pool).await,
+        Command::Sessions(cmd) => sessions(cmd, pool).await,
+        Command::Audit(cmd) => audit(cmd, pool).await,
+    }
+}
+
+async fn user(cmd: UserCommand, pool: &SqlitePool) -> anyhow::Result<()> {
+    match cmd {
+        UserCommand::Add { username, password } => {
+            let password = password_or_stdin(password)?;
+            users::add(pool, &username, &password).await?;
+            audit::record(pool, CLI_ACTOR, "user.add", &username).await;
+            println!("✅ Created user `{}`", username);
+        }
+        UserCommand::Passwd { username, password } => {
+            let password = password_or_stdin(password)?;
+            users::set_password(pool, &username, &password).await?;
+            audit::record(pool, CLI_ACTOR, "user.passwd", &username).await;
+            println!("🔑 Password changed for `{}`", username);
+        }
+        UserCommand::Disable { username, enable } => {
+            users::set_disabled(pool, &username, !enable).await?;
+            let action = if enable {
+                "user.enable"
+            } else {
+                "user.disable"
+            };
+            audit::record(pool, CLI_ACTOR, action, &username).await;
+            println!(
+                "{} `{}`",
+                if enable {
+                    "✅ Enabled"
+                } else {
+                    "🚫 Disabled"
+                },
+                username
+            );
+        }
+        UserCommand::Admin { username, revoke } => {
+            users::set_admin(pool, &username, !revoke).await?;
+            let action = if revoke {
+                "user.admin_revoke"
+            } else {
+                "user.admin_grant"
+            };
+            audit::record(pool, CLI_ACTOR, action, &username).await;
+            println!(
+                "{} `{}`",
+                if revoke {
+                    "⬇️ Revoked administrator rights from"
+                } else {
+                    "⭐ Granted administrator rights to"
+                },
+                username
+            );
+        }
+        UserCommand::List => {
+            for u in users::list(pool).await? {
+                let state = if u.disabled { "disabled" } else { "enabled" };
+                let role = if u.admin { "admin" } else { "" };
+                println!("{:>4}  {:<24}  {:<8}  {}", u.id, u.username, state, role);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn migrate(cmd: MigrateCommand, pool: &SqlitePool) -> anyhow::Result<()> {
+    match cmd {
+        MigrateCommand::Status => {
+            for m in db::migration_status(pool).await? {
+                let mark = if m.applied { "applied" } else { "pending" };
+                println!("{:>16}  {:<8}  {}", m.version, mark, m.description);
+            }
+        }
+        MigrateCommand::Up => {
+            db::MIGRATOR.run(pool).await?;
+            println!("✅ Database is up to date");
+        }
+        MigrateCommand::Down { target } => {
+            db::migrate_down(pool, target).await?;
+            println!("⏪ Migrations reverted");
+        }
+    }
+    Ok(())
+}
+
+async fn hosts(cmd: HostsCommand, pool: &SqlitePool) -> anyhow::Result<()> {
+    match cmd {
+        HostsCommand::Add { name, uri } => {
+            hosts::add(pool, &name, &uri).await?;
+            audit::record(pool, CLI_ACTOR, "host.add", &format!("{} {}", name, uri)).await;
+            println!("✅ Registered host `{}` ({})", name, uri);
+        }
+        HostsCommand::List => {
+            for h in hosts::list(pool).await? {
+                println!("{:>4}  {:<24}  {}", h.id, h.name, h.uri);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn sessions(cmd: SessionsCommand, pool: &SqlitePool) -> anyhow::Result<()> {
+    let SessionsCommand::Purge { all } = cmd;
+    // axum_session stores the expiry as a unix timestamp in `expires`
+    let result = if all {
+        sqlx::query("DELETE FROM sessions_table")
+            .execute(pool)
+            .await?
+    } else {
+        sqlx::query("DELETE FROM sessions_table WHERE expires < strftime('%s', 'now')")
+            .execute(pool)
+            .await?
+    };
+    audit::record(
+        pool,
+        CLI_ACTOR,
+        "sessions.purge",
+        &format!("{} removed", result.rows_affected()),
+    )
+    .await;
+    println!("🧹 Removed {} session(s)", result.rows_affected());
+    Ok(())
+}
+
+async fn audit(cmd: AuditCommand, pool: &SqlitePool) -> anyhow::Result<()> {
+    let AuditCommand::Export { since, format } = cmd;
+    let entries = audit::export(pool, since.as_deref()).await?;
+    match format {
+        ExportFormat::Json => {
+            for e in entries {
+                println!("{}", serde_json::to_string(&e)?);
+            }
+        }
+        ExportFormat::Csv => {
+            println!("id,at,username,action,detail");
+            for e in entries {
+                println!(
+                    "{},{},{},{},{}",
+                    e.id,
+                    csv_field(&e.at),
+                    csv_field(&e.username),
+                    csv_field(&e.action),
+                    csv_field(&e.detail)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Use the `--password` value or read a single line from stdin so passwords
+/// can be piped in without showing up in the process list.
+fn password_or_stdin(password: Option<String>) -> anyhow::Result<String> {
+    let password = match password {
+        Some(p) => p,
+        None => {
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    if password.is_empty() {
+        anyhow::bail!("password must not be empty");
+    }
+    Ok(password)
+}
@@ -0,0 +1,524 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// cloudinit.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::Deserialize;
+
+/// ISO 9660 logical sector size.
+const SECTOR: usize = 2048;
+
+/// Volume label cloud-init's NoCloud datasource looks for.
+const LABEL: &str = "cidata";
+
+// Fixed layout: system area, primary and Joliet volume descriptors, the
+// terminator, both path tables of both trees, then both root directories.
+const PVD: usize = 16;
+const JOLIET_SVD: usize = 17;
+const TERMINATOR: usize = 18;
+const PATH_TABLES: usize = 19;
+const ROOT: usize = 23;
+const JOLIET_ROOT: usize = 24;
+const FIRST_FILE: usize = 25;
+
+/// A NoCloud seed: the files of a `cidata` volume.
+#[derive(Debug, Clone, Default)]
+pub struct Seed {
+    /// Changing it makes cloud-init run its per-instance modules again
+    pub instance_id: String,
+    pub hostname: Option<String>,
+    /// `#cloud-config` document; an empty one when absent
+    pub user_data: Option<String>,
+    /// Network configuration (version 1 or 2)
+    pub network_config: Option<String>,
+}
+
+impl Seed {
+    /// File names and contents of the seed.
+    pub fn files(&self) -> Vec<(&'static str, Vec<u8>)> {
+        let mut meta_data = format!("instance-id: {}\n", yaml_string(&self.instance_id));
+        if let Some(hostname) = &self.hostname {
+            meta_data.push_str(&format!("local-hostname: {}\n", yaml_string(hostname)));
+        }
+        let mut files = vec![
+            ("meta-data", meta_data.into_bytes()),
+            (
+                "user-data",
+                self.user_data
+                    .clone()
+                    .unwrap_or_else(|| "#cloud-config\n".into())
+                    .into_bytes(),
+            ),
+        ];
+        if let Some(network_config) = &self.network_config {
+            files.push(("network-config", network_config.clone().into_bytes()));
+        }
+        files
+    }
+
+    /// The seed as an ISO 9660 image labelled `cidata`.
+    pub fn to_iso(&self) -> Vec<u8> {
+        build_iso(LABEL, &self.files())
+    }
+}
+
+/// A user cloud-init creates in the guest.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CloudUser {
+    pub name: String,
+    /// Password-less sudo
+    pub sudo: bool,
+    pub groups: Vec<String>,
+    pub shell: Option<String>,
+    /// The seed's keys when empty
+    pub ssh_authorized_keys: Vec<String>,
+}
+
+/// The `#cloud-config` user-data generated from the seed form.
+#[derive(Debug, Clone, Default)]
+pub struct CloudConfig {
+    /// Keys of the image's default user
+    pub ssh_authorized_keys: Vec<String>,
+    /// Created next to the default user
+    pub users: Vec<CloudUser>,
+    pub packages: Vec<String>,
+    pub package_upgrade: bool,
+}
+
+impl CloudConfig {
+    pub fn to_user_data(&self) -> String {
+        let list = |out: &mut String, indent: &str, key: &str, items: &[String]| {
+            if !items.is_empty() {
+                out.push_str(&format!("{}{}:\n", indent, key));
+                for item in items {
+                    out.push_str(&format!("{}  - {}\n", indent, yaml_string(item)));
+                }
+            }
+        };
+        let mut out = String::from("#cloud-config\n");
+        list(
+            &mut out,
+            "",
+            "ssh_authorized_keys",
+            &self.ssh_authorized_keys,
+        );
+        if !self.users.is_empty() {
+            // Listing `default` keeps the image's own user
+            out.push_str("users:\n  - default\n");
+            for user in &self.users {
+                out.push_str(&format!("  - name: {}\n", yaml_string(&user.name)));
+                if user.sudo {
+                    out.push_str("    sudo: \"ALL=(ALL) NOPASSWD:ALL\"\n");
+                }
+                if !user.groups.is_empty() {
+                    out.push_str(&format!(
+                        "    groups: {}\n",
+                        yaml_string(&user.groups.join(", "))
+                    ));
+                }
+                if let Some(shell) = &user.shell {
+                    out.push_str(&format!("    shell: {}\n", yaml_string(shell)));
+                }
+                let keys = if user.ssh_authorized_keys.is_empty() {
+                    &self.ssh_authorized_keys
+                } else {
+                    &user.ssh_authorized_keys
+                };
+                list(&mut out, "    ", "ssh_authorized_keys", keys);
+            }
+        }
+        list(&mut out, "", "packages", &self.packages);
+        if self.package_upgrade {
+            out.push_str("package_update: true\npackage_upgrade: true\n");
+        }
+        out
+    }
+}
+
+/// Fresh instance id for a domain; a new one makes cloud-init apply the
+/// seed again on the next boot.
+pub fn instance_id(name: &str) -> String {
+    let mut id = [0u8; 8];
+    OsRng.fill_bytes(&mut id);
+    let hex: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", name, hex)
+}
+
+/// Double-quoted YAML scalar.
+pub fn yaml_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn both_u16(out: &mut [u8], value: u16) {
+    out[..2].copy_from_slice(&value.to_le_bytes());
+    out[2..4].copy_from_slice(&value.to_be_bytes());
+}
+
+fn both_u32(out: &mut [u8], value: u32) {
+    out[..4].copy_from_slice(&value.to_le_bytes());
+    out[4..8].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Big-endian UCS-2 as used by Joliet.
+fn ucs2(value: &str) -> Vec<u8> {
+    value
+        .encode_utf16()
+        .flat_map(|unit| unit.to_be_bytes())
+        .collect()
+}
+
+/// Directory record for an extent; `id` is the encoded file identifier.
+fn dir_record(id: &[u8], extent: usize, size: usize, directory: bool) -> Vec<u8> {
+    let len = 33 + id.len() + (id.len() + 1) % 2;
+    let mut record = vec![0u8; len];
+    record[0] = len as u8;
+    both_u32(&mut record[2..10], extent as u32);
+    both_u32(&mut record[10..18], size as u32);
+    // Recording date (18..25) left unspecified
+    record[25] = if directory { 2 } else { 0 };
+    both_u16(&mut record[28..32], 1);
+    record[32] = id.len() as u8;
+    record[33..33 + id.len()].copy_from_slice(id);
+    record
+}
+
+/// Root directory: `.`, `..` and one record per file.
+fn root_directory(root: usize, files: &[(Vec<u8>, usize, usize)]) -> Vec<u8> {
+    let mut dir = dir_record(&[0], root, SECTOR, true);
+    dir.extend(dir_record(&[1], root, SECTOR, true));
+    for (id, extent, size) in files {
+        dir.extend(dir_record(id, *extent, *size, false));
+    }
+    dir
+}
+
+/// Little- and big-endian path tables of a tree holding only the root.
+fn path_tables(root: usize) -> (Vec<u8>, Vec<u8>) {
+    let mut little = vec![1, 0];
+    little.extend((root as u32).to_le_bytes());
+    little.extend(1u16.to_le_bytes());
+    little.extend([0, 0]);
+    let mut big = vec![1, 0];
+    big.extend((root as u32).to_be_bytes());
+    big.extend(1u16.to_be_bytes());
+    big.extend([0, 0]);
+    (little, big)
+}
+
+/// Primary (`joliet == false`) or Joliet supplementary volume descriptor.
+fn volume_descriptor(label: &str, sectors: usize, joliet: bool) -> Vec<u8> {
+    let mut vd = vec![0u8; SECTOR];
+    vd[0] = if joliet { 2 } else { 1 };
+    vd[1..6].copy_from_slice(b"CD001");
+    vd[6] = 1;
+    // Identifier fields are padded with spaces in their own encoding
+    let text = |vd: &mut Vec<u8>, range: std::ops::Range<usize>, value: &str| {
+        let field = &mut vd[range];
+        if joliet {
+            for pair in field.chunks_mut(2) {
+                pair.copy_from_slice(&[0, b' '][..pair.len()]);
+            }
+            let encoded = ucs2(value);
+            let n = encoded.len().min(field.len() & !1);
+            field[..n].copy_from_slice(&encoded[..n]);
+        } else {
+            field.fill(b' ');
+            let n = value.len().min(field.len());
+            field[..n].copy_from_slice(&value.as_bytes()[..n]);
+        }
+    };
+    text(&mut vd, 8..40, "");
+    let label = if joliet {
+        label.to_string()
+    } else {
+        label.to_ascii_uppercase()
+    };
+    text(&mut vd, 40..72, &label);
+    both_u32(&mut vd[80..88], sectors as u32);
+    if joliet {
+        // UCS-2 level 3
+        vd[88..91].copy_from_slice(b"%/E");
+    }
+    both_u16(&mut vd[120..124], 1);
+    both_u16(&mut vd[124..128], 1);
+    both_u16(&mut vd[128..132], SECTOR as u16);
+    both_u32(&mut vd[132..140], 10);
+    let tables = PATH_TABLES + if joliet { 2 } else { 0 };
+    vd[140..144].copy_from_slice(&(tables as u32).to_le_bytes());
+    vd[148..152].copy_from_slice(&((tables + 1) as u32).to_be_bytes());
+    let root = if joliet { JOLIET_ROOT } else { ROOT };
+    vd[156..190].copy_from_slice(&dir_record(&[0], root, SECTOR, true));
+    for range in [
+        190..318,
+        318..446,
+        446..574,
+        574..702,
+        702..739,
+        739..776,
+        776..813,
+    ] {
+        text(&mut vd, range, "");
+    }
+    // Creation, modification, expiration and effective dates: unspecified
+    for start in [813, 830, 847, 864] {
+        vd[start..start + 16].fill(b'0');
+    }
+    vd[881] = 1;
+    vd
+}
+
+/// A single-directory ISO 9660 image with Joliet names, enough for a
+/// NoCloud seed. Plain ISO 9660 readers see the names upper-cased.
+pub fn build_iso(label: &str, files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut sorted: Vec<&(&str, Vec<u8>)> = files.iter().collect();
+    sorted.sort_by_key(|(name, _)| name.to_ascii_uppercase());
+    let mut extent = FIRST_FILE;
+    let mut primary = Vec::new();
+    let mut joliet = Vec::new();
+    for (name, data) in &sorted {
+        let id = format!("{};1", name.to_ascii_uppercase());
+        primary.push((id.into_bytes(), extent, data.len()));
+        joliet.push((ucs2(&format!("{};1", name)), extent, data.len()));
+        extent += data.len().div_ceil(SECTOR);
+    }
+    let sectors = extent;
+
+    let mut iso = vec![0u8; sectors * SECTOR];
+    let mut put = |sector: usize, data: &[u8]| {
+        iso[sector * SECTOR..sector * SECTOR + data.len()].copy_from_slice(data);
+    };
+    put(PVD, &volume_descriptor(label, sectors, false));
+    put(JOLIET_SVD, &volume_descriptor(label, sectors, true));
+    let mut terminator = vec![255u8];
+    terminator.extend(b"CD001\x01");
+    put(TERMINATOR, &terminator);
+    let (little, big) = path_tables(ROOT);
+    put(PATH_TABLES, &little);
+    put(PATH_TABLES + 1, &big);
+    let (little, big) = path_tables(JOLIET_ROOT);
+    put(PATH_TABLES + 2, &little);
+    put(PATH_TABLES + 3, &big);
+    put(ROOT, &root_directory(ROOT, &primary));
+    put(JOLIET_ROOT, &root_directory(JOLIET_ROOT, &joliet));
+    for ((_, data), (_, extent, _)) in sorted.iter().zip(&primary) {
+        put(*extent, data);
+    }
+    iso
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn le_u16(data: &[u8]) -> u16 {
+        u16::from_le_bytes([data[0], data[1]])
+    }
+
+    fn le_u32(data: &[u8]) -> u32 {
+        u32::from_le_bytes(data[..4].try_into().unwrap())
+    }
+
+    fn be_u32(data: &[u8]) -> u32 {
+        u32::from_be_bytes(data[..4].try_into().unwrap())
+    }
+
+    /// Both-endian 32-bit field; both halves have to agree.
+    fn both(data: &[u8]) -> usize {
+        assert_eq!(le_u32(data), be_u32(&data[4..]));
+        le_u32(data) as usize
+    }
+
+    fn sector(iso: &[u8], n: usize) -> &[u8] {
+        &iso[n * SECTOR..(n + 1) * SECTOR]
+    }
+
+    /// Records of a directory extent: identifier, extent, size, flags.
+    fn read_dir(iso: &[u8], extent: usize, size: usize) -> Vec<(Vec<u8>, usize, usize, u8)> {
+        let dir = &iso[extent * SECTOR..extent * SECTOR + size];
+        let mut records = Vec::new();
+        let mut at = 0;
+        while at < dir.len() && dir[at] != 0 {
+            let record = &dir[at..at + dir[at] as usize];
+            let id_len = record[32] as usize;
+            records.push((
+                record[33..33 + id_len].to_vec(),
+                both(&record[2..10]),
+                both(&record[10..18]),
+                record[25],
+            ));
+            at += record.len();
+        }
+        records
+    }
+
+    fn joliet_name(id: &[u8]) -> String {
+        let units: Vec<u16> = id
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).unwrap()
+    }
+
+    /// Walk one tree from its volume descriptor the way a reader would and
+    /// return the file names with their contents.
+    fn read_tree(iso: &[u8], descriptor: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let vd = sector(iso, descriptor);
+        assert_eq!(&vd[1..6], b"CD001");
+        assert_eq!(both(&vd[80..88]) * SECTOR, iso.len());
+        assert_eq!(le_u16(&vd[128..130]), SECTOR as u16);
+        // Path tables: just the root, whose extent matches the root record
+        let table_size = both(&vd[132..140]);
+        assert_eq!(table_size, 10);
+        let root = &vd[156..190];
+        let root_extent = both(&root[2..10]);
+        let root_size = both(&root[10..18]);
+        assert_eq!(root[25], 2);
+        let little = &sector(iso, le_u32(&vd[140..144]) as usize)[..table_size];
+        assert_eq!(little[0], 1);
+        assert_eq!(le_u32(&little[2..6]) as usize, root_extent);
+        assert_eq!(le_u16(&little[6..8]), 1);
+        let big = &sector(iso, be_u32(&vd[148..152]) as usize)[..table_size];
+        assert_eq!(big[0], 1);
+        assert_eq!(be_u32(&big[2..6]) as usize, root_extent);
+        assert_eq!(u16::from_be_bytes([big[6], big[7]]), 1);
+
+        let records = read_dir(iso, root_extent, root_size);
+        assert_eq!(records[0].0, [0]);
+        assert_eq!(records[1].0, [1]);
+        assert!(records[..2].iter().all(|r| r.1 == root_extent && r.3 == 2));
+        records[2..]
+            .iter()
+            .map(|(id, extent, size, flags)| {
+                assert_eq!(*flags, 0);
+                let data = iso[extent * SECTOR..extent * SECTOR + size].to_vec();
+                (id.clone(), data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn iso_round_trips_through_both_trees() {
+        let big = vec![b'x'; SECTOR + 1];
+        let files = vec![
+            ("user-data", b"#cloud-config\n".to_vec()),
+            ("network-config", big.clone()),
+            ("meta-data", b"instance-id: \"i\"\n".to_vec()),
+        ];
+        let iso = build_iso(LABEL, &files);
+        assert_eq!(iso.len() % SECTOR, 0);
+        assert_eq!(sector(&iso, PVD)[0], 1);
+        assert_eq!(sector(&iso, JOLIET_SVD)[0], 2);
+        assert_eq!(&sector(&iso, JOLIET_SVD)[88..91], b"%/E");
+        assert_eq!(&sector(&iso, TERMINATOR)[..7], b"\xffCD001\x01");
+        assert_eq!(&sector(&iso, PVD)[40..46], b"CIDATA");
+        assert_eq!(joliet_name(&sector(&iso, JOLIET_SVD)[40..52]), "cidata");
+
+        // Directories are sorted by identifier
+        let expected = [
+            ("meta-data", files[2].1.clone()),
+            ("network-config", big),
+            ("user-data", files[0].1.clone()),
+        ];
+        let primary = read_tree(&iso, PVD);
+        let joliet = read_tree(&iso, JOLIET_SVD);
+        assert_eq!(primary.len(), expected.len());
+        assert_eq!(joliet.len(), expected.len());
+        for (((name, data), (id, p_data)), (j_id, j_data)) in
+            expected.iter().zip(&primary).zip(&joliet)
+        {
+            assert_eq!(
+                String::from_utf8(id.clone()).unwrap(),
+                format!("{};1", name.to_ascii_uppercase())
+            );
+            assert_eq!(joliet_name(j_id), format!("{};1", name));
+            assert_eq!(p_data, data);
+            assert_eq!(j_data, data);
+        }
+    }
+
+    #[test]
+    fn yaml_strings_are_quoted_and_escaped() {
+        assert_eq!(yaml_string("plain"), "\"plain\"");
+        assert_eq!(
+            yaml_string("a \"b\" \\ c\nd\te\r\u{1}"),
+            "\"a \\\"b\\\" \\\\ c\\nd\\te\\r\\u0001\""
+        );
+        assert_eq!(
+            yaml_string("key: value # no comment"),
+            "\"key: value # no comment\""
+        );
+    }
+
+    #[test]
+    fn meta_data_escapes_the_hostname() {
+        let seed = Seed {
+            instance_id: "vm-1".into(),
+            hostname: Some("web\"\nuser-data: x".into()),
+            ..Seed::default()
+        };
+        let files = seed.files();
+        let names: Vec<&str> = files.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["meta-data", "user-data"]);
+        assert_eq!(
+            String::from_utf8(files[0].1.clone()).unwrap(),
+            "instance-id: \"vm-1\"\nlocal-hostname: \"web\\\"\\nuser-data: x\"\n"
+        );
+        assert_eq!(files[1].1, b"#cloud-config\n");
+    }
+
+    #[test]
+    fn user_data_lists_users_keys_and_packages() {
+        let config = CloudConfig {
+            ssh_authorized_keys: vec!["ssh-ed25519 AAAA a@b".into()],
+            users: vec![
+                CloudUser {
+                    name: "ops".into(),
+                    sudo: true,
+                    groups: vec!["wheel".into(), "adm".into()],
+                    shell: Some("/bin/bash".into()),
+                    ..CloudUser::default()
+                },
+                CloudUser {
+                    name: "x\"y".into(),
+                    ssh_authorized_keys: vec!["ssh-rsa BBBB".into()],
+                    ..CloudUser::default()
+                },
+            ],
+            packages: vec!["qemu-guest-agent".into()],
+            package_upgrade: true,
+        };
+        assert_eq!(
+            config.to_user_data(),
+            "#cloud-config\n\
+             ssh_authorized_keys:\n  - \"ssh-ed25519 AAAA a@b\"\n\
+             users:\n  - default\n\
+             \x20 - name: \"ops\"\n\
+             \x20   sudo: \"ALL=(ALL) NOPASSWD:ALL\"\n\
+             \x20   groups: \"wheel, adm\"\n\
+             \x20   shell: \"/bin/bash\"\n\
+             \x20   ssh_authorized_keys:\n\
+             \x20     - \"ssh-ed25519 AAAA a@b\"\n\
+             \x20 - name: \"x\\\"y\"\n\
+             \x20   ssh_authorized_keys:\n\
+             \x20     - \"ssh-rsa BBBB\"\n\
+             packages:\n  - \"qemu-guest-agent\"\n\
+             package_update: true\npackage_upgrade: true\n"
+        );
+        assert_eq!(CloudConfig::default().to_user_data(), "#cloud-config\n");
+    }
+}
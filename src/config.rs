@@ -0,0 +1,105 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// config.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the location of the config file.
+pub const CONFIG_ENV: &str = "RUST_MANAGER_CONFIG";
+/// Config file that is read when `RUST_MANAGER_CONFIG` is not set.
+pub const DEFAULT_CONFIG_FILE: &str = "rust-manager.toml";
+
+/// Runtime configuration – every field has a sensible default so the
+/// server still starts without a config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Address the web server listens on
+    pub listen: String,
+    /// Path of the SQLite database file
+    pub database: String,
+    /// libvirt connection URI of the local hypervisor
+    pub libvirt_uri: String,
+    /// Seconds in‑flight requests get to finish after SIGTERM/SIGINT
+    pub shutdown_timeout: u64,
+    /// Seconds between domain metrics samples – 0 turns sampling off
+    pub metrics_interval: u64,
+    /// Seconds of metrics history kept per running domain
+    pub metrics_window: u64,
+    /// Bearer token Prometheus must send to `/metrics` – open when absent
+    pub metrics_token: Option<String>,
+    /// Optional TLS termination – plain HTTP when absent
+    pub tls: Option<TlsConfig>,
+}
+
+/// `[tls]` section of the config file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    /// PEM encoded certificate chain
+    pub cert: PathBuf,
+    /// PEM encoded private key
+    pub key: PathBuf,
+    /// When set, a plain HTTP listener on this address redirects to HTTPS
+    #[serde(default)]
+    pub redirect_from: Option<String>,
+    /// Generate a self‑signed certificate when `cert`/`key` do not exist
+    #[serde(default)]
+    pub self_signed: bool,
+    /// Host names put in the generated self‑signed certificate; the first
+    /// is where the HTTP redirect points for unknown hosts
+    #[serde(default = "default_self_signed_names")]
+    pub self_signed_names: Vec<String>,
+    /// How often (seconds) the certificate files are checked for changes
+    #[serde(default = "default_reload_interval")]
+    pub reload_interval: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            listen: "0.0.0.0:3302".into(),
+            database: "data.db".into(),
+            libvirt_uri: "qemu:///system".into(),
+            shutdown_timeout: 30,
+            metrics_interval: 10,
+            metrics_window: 3600,
+            metrics_token: None,
+            tls: None,
+        }
+    }
+}
+
+fn default_self_signed_names() -> Vec<String> {
+    vec!["localhost".into(), "127.0.0.1".into()]
+}
+
+fn default_reload_interval() -> u64 {
+    30
+}
+
+impl Config {
+    /// Load the config from `path`, `RUST_MANAGER_CONFIG` or
+    /// `rust-manager.toml`, in that order.  A missing default file is not an
+    /// error – the defaults are used.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Config> {
+        if let Some(path) = path {
+            return Config::from_file(path);
+        }
+        match std::env::var(CONFIG_ENV) {
+            Ok(path) => Config::from_file(Path::new(&path)),
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::from_file(Path::new(DEFAULT_CONFIG_FILE))
+            }
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    /// Parse a TOML config file.
+    pub fn from_file(path: &Path) -> anyhow::Result<Config> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read config {}: {}", path.display(), e))?;
+        let config = toml::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+}
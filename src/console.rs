@@ -0,0 +1,231 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// console.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use axum::body::Bytes;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+
+/// How long a console ticket can be redeemed after it was issued.
+pub const TICKET_TTL: Duration = Duration::from_secs(30);
+
+/// Console output buffered for a viewer that falls behind.
+const SERIAL_BACKLOG: usize = 256;
+
+/// Source of viewer ids for shared serial consoles.
+static NEXT_VIEWER: AtomicU64 = AtomicU64::new(1);
+
+/// Where a console proxy connects to.
+#[derive(Debug, Clone)]
+pub enum Target {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    Unix(PathBuf),
+    /// A character device opened through `virDomainOpenConsole`
+    Serial {
+        /// Console alias such as `serial0` – the first console when absent
+        device: Option<String>,
+        /// Browser input is dropped
+        read_only: bool,
+        /// Take the console over from another session
+        force: bool,
+        /// Save the session output to the audit log
+        transcript: bool,
+    },
+}
+
+/// Permission to open one console connection.
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub user_id: i64,
+    pub username: String,
+    pub domain_uuid: String,
+    pub domain_name: String,
+    /// `vnc` or `serial`
+    pub kind: &'static str,
+    pub target: Target,
+    expires: Instant,
+}
+
+impl Ticket {
+    pub fn new(
+        user_id: i64,
+        username: String,
+        domain_uuid: String,
+        domain_name: String,
+        kind: &'static str,
+        target: Target,
+    ) -> Ticket {
+        Ticket {
+            user_id,
+            username,
+            domain_uuid,
+            domain_name,
+            kind,
+            target,
+            expires: Instant::now() + TICKET_TTL,
+        }
+    }
+}
+
+/// Outstanding console tickets by token. Tokens are random, short-lived and
+/// can be redeemed once.
+#[derive(Clone, Default)]
+pub struct Tickets(Arc<Mutex<HashMap<String, Ticket>>>);
+
+impl Tickets {
+    /// Store `ticket` and return its token.
+    pub fn issue(&self, ticket: Ticket) -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let mut tickets = self.0.lock().unwrap();
+        let now = Instant::now();
+        tickets.retain(|_, t| t.expires > now);
+        tickets.insert(token.clone(), ticket);
+        token
+    }
+
+    /// Redeem `token` – it is gone afterwards, whether it was still valid or
+    /// not.
+    pub fn take(&self, token: &str) -> Option<Ticket> {
+        self.0
+            .lock()
+            .unwrap()
+            .remove(token)
+            .filter(|t| t.expires > Instant::now())
+    }
+}
+
+/// What a shared serial console sends to its viewers.
+#[derive(Debug, Clone)]
+pub enum SerialEvent {
+    Output(Bytes),
+    /// The console is closed; with the error that closed it, if any
+    Closed(Option<String>),
+}
+
+/// One open serial console, shared by everyone watching it. libvirt
+/// allows a single stream per console device, so one worker reads the
+/// device and fans the output out to all viewers.
+pub struct SerialSession {
+    /// Console output for every viewer
+    pub output: broadcast::Sender<SerialEvent>,
+    /// Keystrokes of the interactive viewer. The worker stops once the
+    /// last viewer drops its handle, and with it this sender.
+    pub input: mpsc::Sender<Bytes>,
+    /// The viewer allowed to type
+    writer: Mutex<Option<u64>>,
+    closed: AtomicBool,
+}
+
+impl SerialSession {
+    /// Make `viewer` the one typing into the console. Only succeeds while
+    /// nobody else holds the console, unless `force` takes it over.
+    pub fn claim_writer(&self, viewer: u64, force: bool) -> bool {
+        let mut writer = self.writer.lock().unwrap();
+        match *writer {
+            Some(other) if other != viewer && !force => false,
+            _ => {
+                *writer = Some(viewer);
+                true
+            }
+        }
+    }
+
+    pub fn is_writer(&self, viewer: u64) -> bool {
+        *self.writer.lock().unwrap() == Some(viewer)
+    }
+
+    /// `viewer` leaves; the console stays open while others watch.
+    pub fn release(&self, viewer: u64) {
+        let mut writer = self.writer.lock().unwrap();
+        if *writer == Some(viewer) {
+            *writer = None;
+        }
+    }
+
+    /// Called by the worker when the console is gone, so the next viewer
+    /// opens it again.
+    pub fn close(&self, error: Option<String>) {
+        self.closed.store(true, Ordering::Relaxed);
+        let _ = self.output.send(SerialEvent::Closed(error));
+    }
+}
+
+/// A viewer of a shared serial console.
+pub struct SerialViewer {
+    pub id: u64,
+    pub session: Arc<SerialSession>,
+    pub events: broadcast::Receiver<SerialEvent>,
+}
+
+/// Handed to the worker of a new serial session, which pumps the console
+/// between `input` and `output`.
+pub struct SerialWorker {
+    /// Weak, so the session ends with its last viewer
+    pub session: Weak<SerialSession>,
+    pub output: broadcast::Sender<SerialEvent>,
+    pub input: mpsc::Receiver<Bytes>,
+}
+
+/// Domain UUID and console device of a serial session.
+type SerialKey = (String, Option<String>);
+
+/// Open serial consoles by domain UUID and device.
+#[derive(Clone, Default)]
+pub struct SerialConsoles(Arc<Mutex<HashMap<SerialKey, Weak<SerialSession>>>>);
+
+impl SerialConsoles {
+    /// Join the open console `device` of domain `uuid`. When none is open,
+    /// `start` has to start the worker of a new session.
+    pub fn join(
+        &self,
+        uuid: &str,
+        device: Option<&str>,
+        start: impl FnOnce(SerialWorker),
+    ) -> SerialViewer {
+        let id = NEXT_VIEWER.fetch_add(1, Ordering::Relaxed);
+        let key = (uuid.to_string(), device.map(str::to_string));
+        let mut sessions = self.0.lock().unwrap();
+        sessions.retain(|_, session| session.strong_count() > 0);
+        let open = sessions
+            .get(&key)
+            .and_then(Weak::upgrade)
+            .filter(|session| !session.closed.load(Ordering::Relaxed));
+        if let Some(session) = open {
+            let events = session.output.subscribe();
+            return SerialViewer {
+                id,
+                session,
+                events,
+            };
+        }
+        let (output, events) = broadcast::channel(SERIAL_BACKLOG);
+        let (input, input_rx) = mpsc::channel(16);
+        let session = Arc::new(SerialSession {
+            output: output.clone(),
+            input,
+            writer: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        });
+        sessions.insert(key, Arc::downgrade(&session));
+        start(SerialWorker {
+            session: Arc::downgrade(&session),
+            output,
+            input: input_rx,
+        });
+        SerialViewer {
+            id,
+            session,
+            events,
+        }
+    }
+}
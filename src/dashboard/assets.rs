@@ -0,0 +1,51 @@
+use axum::{
+    extract::Path,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+/// Files below `assets/vendor`, embedded by `build.rs`.
+static ASSETS: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+
+/// noVNC's entry module, loaded by the VNC console page.
+pub const NOVNC_RFB: &str = "novnc/core/rfb.js";
+pub const XTERM_JS: &str = "xterm/lib/xterm.js";
+pub const XTERM_CSS: &str = "xterm/css/xterm.css";
+
+/// Contents of the vendored file at `path`.
+pub fn get(path: &str) -> Option<&'static [u8]> {
+    ASSETS
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, data)| *data)
+}
+
+/// Whether this build bundles the vendored files at `paths`.
+pub fn bundled(paths: &[&str]) -> bool {
+    paths.iter().all(|path| get(path).is_some())
+}
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit('.').next() {
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("map") | Some("json") => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+// GET /assets/{*path} – vendored libraries; the versions are part of the
+// build, so browsers may keep them for a day
+pub async fn serve_asset(Path(path): Path<String>) -> Response {
+    match get(&path) {
+        Some(data) => (
+            [
+                (header::CONTENT_TYPE, content_type(&path)),
+                (header::CACHE_CONTROL, "public, max-age=86400"),
+            ],
+            data,
+        )
+            .into_response(),
+        None => (StatusCode::NOT_FOUND, "no such asset").into_response(),
+    }
+}
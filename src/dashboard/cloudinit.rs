@@ -0,0 +1,206 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+};
+use dioxus::prelude::*;
+
+use super::{ErrorPage, Layout, PageUser, render_page};
+use crate::AppState;
+use crate::api::domains::lookup;
+use crate::api::pools::pool_info;
+use crate::api::{Libvirt, libvirt_error};
+use crate::ssh_keys::{self, SshKey};
+
+/// Forms with `data-seed` post to that URL. Fields inside the
+/// `data-seed-fields` fieldset become a seed request – nested under
+/// `data-seed-key` (and left out while empty) when that is set – and the
+/// other fields are sent as they are.
+pub const SEED_JS: &str = r#"
+document.addEventListener('submit', async (ev) => {
+  const form = ev.target;
+  if (!form.dataset.seed) return;
+  ev.preventDefault();
+  const lines = (text) => text.split('\n').map((l) => l.trim()).filter((l) => l);
+  const body = {};
+  const seed = { ssh_key_ids: [], ssh_keys: [], users: [], packages: [] };
+  for (const el of form.elements) {
+    if (!el.name) continue;
+    const target = el.closest('[data-seed-fields]') ? seed : body;
+    if (el.name === 'ssh_key_id') { if (el.checked) seed.ssh_key_ids.push(Number(el.value)); }
+    else if (el.name === 'ssh_keys') seed.ssh_keys = lines(el.value);
+    else if (el.name === 'packages') seed.packages = el.value.split(/\s+/).filter((p) => p);
+    else if (el.type === 'checkbox') target[el.name] = el.checked;
+    else if (el.value !== '') target[el.name] = el.value;
+  }
+  // The user_* fields describe one extra user
+  if (seed.user_name) {
+    seed.users.push({
+      name: seed.user_name,
+      sudo: !!seed.user_sudo,
+      groups: (seed.user_groups || '').split(',').map((g) => g.trim()).filter((g) => g),
+      shell: seed.user_shell,
+    });
+  }
+  for (const field of ['user_name', 'user_sudo', 'user_groups', 'user_shell']) delete seed[field];
+  const empty = !seed.ssh_key_ids.length && !seed.ssh_keys.length && !seed.users.length
+    && !seed.packages.length && !seed.package_upgrade && !seed.hostname
+    && !seed.user_data && !seed.network_config;
+  if (!form.dataset.seedKey) Object.assign(body, seed);
+  else if (!empty) body[form.dataset.seedKey] = seed;
+  const res = await fetch(form.dataset.seed, {
+    method: 'POST',
+    headers: { 'content-type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+});
+"#;
+
+/// cloud-init inputs of a `data-seed` form.
+#[component]
+pub fn SeedFields(keys: Vec<SshKey>, hostname: bool) -> Element {
+    rsx! {
+      fieldset { "data-seed-fields": "true",
+        legend { "cloud-init" }
+        if hostname {
+          input { name: "hostname", placeholder: "hostname" }
+        }
+        div {
+          "SSH keys: "
+          if keys.is_empty() {
+            "none stored – add them on your "
+            a { href: "/dashboard/profile", "profile" }
+          }
+          for key in keys.iter() {
+            label {
+              input { name: "ssh_key_id", r#type: "checkbox", value: "{key.id}" }
+              " {key.name} "
+            }
+          }
+        }
+        textarea {
+          name: "ssh_keys",
+          rows: "2",
+          cols: "80",
+          placeholder: "further public keys, one per line",
+        }
+        div {
+          "Extra user: "
+          input { name: "user_name", placeholder: "login name" }
+          input { name: "user_groups", placeholder: "groups, comma separated" }
+          input { name: "user_shell", placeholder: "shell" }
+          label {
+            input { name: "user_sudo", r#type: "checkbox" }
+            " sudo"
+          }
+        }
+        textarea {
+          name: "packages",
+          rows: "2",
+          cols: "80",
+          placeholder: "packages to install",
+        }
+        label {
+          input { name: "package_upgrade", r#type: "checkbox" }
+          " upgrade all packages on first boot"
+        }
+        details {
+          summary { "Network configuration and custom user-data" }
+          textarea {
+            name: "network_config",
+            rows: "8",
+            cols: "80",
+            placeholder: "network-config (version 1 or 2) – DHCP on the first NIC when empty",
+          }
+          textarea {
+            name: "user_data",
+            rows: "8",
+            cols: "80",
+            placeholder: "#cloud-config – replaces the generated user-data",
+          }
+        }
+      }
+    }
+}
+
+#[component]
+fn CloudInitPage(
+    name: String,
+    uuid: String,
+    running: bool,
+    pools: Vec<String>,
+    keys: Vec<SshKey>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "cloud-init – {name}" }
+        a { href: "/dashboard/domains/{uuid}",
+          button { "Back" }
+        }
+        p {
+          "Builds a NoCloud seed image ("
+          code { "{name}-cidata.iso" }
+          ") in the pool and puts it in a CD-ROM of the persistent definition. "
+          "Every seed has a new instance id, so cloud-init applies it on the next boot."
+        }
+        if running {
+          p { "The domain is running – it reads the seed when it boots next." }
+        }
+        form { "data-seed": "/api/domains/{uuid}/cloud-init",
+          "Pool: "
+          select { name: "pool",
+            for pool in pools.iter() {
+              option { value: "{pool}", "{pool}" }
+            }
+          }
+          SeedFields { keys, hostname: true }
+          button { r#type: "submit", "Build seed and attach" }
+        }
+      }
+      script { dangerous_inner_html: SEED_JS }
+    }
+}
+
+/// Name, running state and active pools for the seed form.
+fn load_target(uri: &str, uuid: &str) -> Result<(String, bool, Vec<String>), (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup(&conn, uuid)?;
+    let mut pools = Vec::new();
+    for pool in conn.list_all_storage_pools(0).map_err(libvirt_error)? {
+        let info = pool_info(&pool)?;
+        if info.active {
+            pools.push(info.name);
+        }
+    }
+    pools.sort();
+    Ok((
+        dom.get_name().map_err(libvirt_error)?,
+        dom.is_active().map_err(libvirt_error)?,
+        pools,
+    ))
+}
+
+// GET /dashboard/domains/{uuid}/cloud-init
+pub async fn cloud_init_page(
+    PageUser(user): PageUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> (StatusCode, Html<String>) {
+    let keys = ssh_keys::list_for_user(&state.pool, user.id)
+        .await
+        .unwrap_or_default();
+    match load_target(&state.config.libvirt_uri, &uuid) {
+        Ok((name, running, pools)) => (
+            StatusCode::OK,
+            render_page(rsx!(CloudInitPage {
+                name,
+                uuid,
+                running,
+                pools,
+                keys
+            })),
+        ),
+        Err((code, message)) => (code, render_page(rsx!(ErrorPage { message }))),
+    }
+}
@@ -0,0 +1,233 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+};
+use dioxus::prelude::*;
+
+use super::assets::{self, NOVNC_RFB, XTERM_CSS, XTERM_JS};
+use super::{ErrorPage, Layout, PageUser, render_page};
+use crate::AppState;
+use crate::api::domains::lookup;
+use crate::api::{Libvirt, libvirt_error};
+
+/// Fetches a console ticket and connects noVNC through the WebSocket proxy;
+/// every reconnect needs a fresh ticket since tickets are single-use.
+const VNC_JS: &str = r#"
+import RFB from '/assets/novnc/core/rfb.js';
+
+const screen = document.getElementById('screen');
+const status = document.getElementById('console-status');
+let rfb = null;
+
+async function connect() {
+  if (rfb) rfb.disconnect();
+  status.textContent = 'Connecting…';
+  const res = await fetch('/api/domains/' + screen.dataset.uuid + '/console', { method: 'POST' });
+  if (!res.ok) { status.textContent = await res.text(); return; }
+  const ticket = await res.json();
+  const scheme = location.protocol === 'https:' ? 'wss:' : 'ws:';
+  rfb = new RFB(screen, scheme + '//' + location.host + ticket.path);
+  rfb.scaleViewport = true;
+  rfb.addEventListener('connect', () => { status.textContent = 'Connected'; });
+  rfb.addEventListener('disconnect', (ev) => {
+    status.textContent = ev.detail.clean ? 'Disconnected' : 'Connection lost';
+  });
+  rfb.addEventListener('credentialsrequired', () => {
+    rfb.sendCredentials({ password: prompt('VNC password') || '' });
+  });
+}
+
+document.getElementById('console-cad').addEventListener('click', () => rfb && rfb.sendCtrlAltDel());
+document.getElementById('console-reconnect').addEventListener('click', connect);
+connect();
+"#;
+
+/// Fetches a serial ticket with the chosen options and attaches xterm.js to
+/// the WebSocket; text frames carry status messages, binary frames output.
+const SERIAL_JS: &str = r#"
+const box = document.getElementById('terminal');
+const status = document.getElementById('serial-status');
+const options = document.getElementById('serial-options');
+const term = new Terminal({ cursorBlink: true, scrollback: 5000 });
+const encoder = new TextEncoder();
+let socket = null;
+term.open(box);
+term.onData((data) => {
+  if (socket && socket.readyState === WebSocket.OPEN && !term.options.disableStdin) {
+    socket.send(encoder.encode(data));
+  }
+});
+
+async function connect() {
+  if (socket) socket.close();
+  const body = {
+    read_only: options.read_only.checked,
+    force: options.force.checked,
+    transcript: options.transcript.checked,
+  };
+  if (options.device.value !== '') body.device = options.device.value;
+  status.textContent = 'Connecting…';
+  const res = await fetch('/api/domains/' + box.dataset.uuid + '/serial', {
+    method: 'POST',
+    headers: { 'content-type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+  if (!res.ok) { status.textContent = await res.text(); return; }
+  const ticket = await res.json();
+  const scheme = location.protocol === 'https:' ? 'wss:' : 'ws:';
+  const ws = new WebSocket(scheme + '//' + location.host + ticket.path);
+  ws.binaryType = 'arraybuffer';
+  ws.onopen = () => {
+    term.options.disableStdin = body.read_only;
+    status.textContent = body.read_only ? 'Connected (read-only)' : 'Connected';
+    term.focus();
+  };
+  ws.onmessage = (ev) => {
+    if (typeof ev.data === 'string') status.textContent = ev.data;
+    else term.write(new Uint8Array(ev.data));
+  };
+  ws.onclose = () => {
+    if (socket === ws) status.textContent += ' – closed';
+  };
+  socket = ws;
+}
+
+options.addEventListener('submit', (ev) => { ev.preventDefault(); connect(); });
+connect();
+"#;
+
+/// Shown when this build was made with `RUST_MANAGER_WITHOUT_ASSETS`.
+#[component]
+fn MissingClient(library: &'static str) -> Element {
+    rsx! {
+      p { style: "color:red;",
+        "{library} is not bundled with this build – run contrib/fetch-assets.sh and rebuild."
+      }
+    }
+}
+
+#[component]
+fn SerialPage(name: String, uuid: String, admin: bool) -> Element {
+    let bundled = assets::bundled(&[XTERM_JS, XTERM_CSS]);
+    rsx! {
+      Layout {
+        link { rel: "stylesheet", href: "/assets/{XTERM_CSS}" }
+        h1 { "Serial console – {name}" }
+        if !bundled {
+          MissingClient { library: "xterm.js" }
+        }
+        form { id: "serial-options",
+          a { href: "/dashboard/domains/{uuid}",
+            button { r#type: "button", "Back" }
+          }
+          input { name: "device", placeholder: "device (e.g. serial0)" }
+          label {
+            // Only administrators may type into the console
+            input {
+              name: "read_only",
+              r#type: "checkbox",
+              checked: !admin,
+              disabled: !admin,
+            }
+            " read-only"
+          }
+          label {
+            input { name: "force", r#type: "checkbox", disabled: !admin }
+            " take over"
+          }
+          label {
+            input { name: "transcript", r#type: "checkbox" }
+            " save transcript to audit log"
+          }
+          button { r#type: "submit", "Connect" }
+          span { id: "serial-status", style: "margin-left:10px;" }
+        }
+        p { style: "color:#666;",
+          "Everyone watching a console shares one session; one viewer at a time can type, and take over hands the keyboard to you."
+        }
+        div {
+          id: "terminal",
+          "data-uuid": "{uuid}",
+          style: "width:100%;height:70vh;background:black;margin-top:8px;",
+        }
+      }
+      script { src: "/assets/{XTERM_JS}" }
+      script { dangerous_inner_html: SERIAL_JS }
+    }
+}
+
+#[component]
+fn ConsolePage(name: String, uuid: String) -> Element {
+    let bundled = assets::bundled(&[NOVNC_RFB]);
+    rsx! {
+      Layout {
+        h1 { "Console – {name}" }
+        if !bundled {
+          MissingClient { library: "noVNC" }
+        }
+        div {
+          a { href: "/dashboard/domains/{uuid}",
+            button { "Back" }
+          }
+          button { id: "console-cad", "Ctrl+Alt+Del" }
+          button { id: "console-reconnect", "Reconnect" }
+          span { id: "console-status", style: "margin-left:10px;" }
+        }
+        div {
+          id: "screen",
+          "data-uuid": "{uuid}",
+          style: "width:100%;height:75vh;background:black;margin-top:8px;",
+        }
+      }
+      script { r#type: "module", dangerous_inner_html: VNC_JS }
+    }
+}
+
+// GET /dashboard/domains/{uuid}/console
+pub async fn console_page(
+    _user: PageUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> (StatusCode, Html<String>) {
+    let found = Libvirt::open(&state.config.libvirt_uri).and_then(|conn| {
+        let dom = lookup(&conn, &uuid)?;
+        Ok((
+            dom.get_name().map_err(libvirt_error)?,
+            dom.get_uuid_string().map_err(libvirt_error)?,
+        ))
+    });
+    match found {
+        Ok((name, uuid)) => (
+            StatusCode::OK,
+            render_page(rsx!(ConsolePage { name, uuid })),
+        ),
+        Err((code, message)) => (code, render_page(rsx!(ErrorPage { message }))),
+    }
+}
+
+// GET /dashboard/domains/{uuid}/serial
+pub async fn serial_page(
+    PageUser(user): PageUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> (StatusCode, Html<String>) {
+    let found = Libvirt::open(&state.config.libvirt_uri).and_then(|conn| {
+        let dom = lookup(&conn, &uuid)?;
+        Ok((
+            dom.get_name().map_err(libvirt_error)?,
+            dom.get_uuid_string().map_err(libvirt_error)?,
+        ))
+    });
+    match found {
+        Ok((name, uuid)) => (
+            StatusCode::OK,
+            render_page(rsx!(SerialPage {
+                name,
+                uuid,
+                admin: user.admin
+            })),
+        ),
+        Err((code, message)) => (code, render_page(rsx!(ErrorPage { message }))),
+    }
+}
@@ -0,0 +1,738 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+};
+use dioxus::prelude::*;
+
+use super::{ErrorPage, Layout, PageUser, format_bytes, format_timestamp, render_page};
+use crate::AppState;
+use crate::api::devices::{DeviceList, parse_devices};
+use crate::api::domains::{leased_addresses, lookup};
+use crate::api::snapshots::{SnapshotNode, snapshot_tree};
+use crate::api::{Libvirt, domain_info, internal_error, libvirt_error};
+use crate::backups::{self, Backup};
+use crate::templates;
+
+/// Lifecycle buttons on the detail page: endpoint suffix and label.
+const DOMAIN_ACTIONS: [(&str, &str); 6] = [
+    ("start", "Start"),
+    ("shutdown", "Shut down"),
+    ("reboot", "Reboot"),
+    ("suspend", "Pause"),
+    ("resume", "Resume"),
+    ("destroy", "Force off"),
+];
+
+/// Metric charts on the detail page: chart name and title.
+const METRIC_CHARTS: [(&str, &str); 4] = [
+    ("cpu", "CPU"),
+    ("memory", "Memory"),
+    ("disk", "Disk I/O"),
+    ("net", "Network I/O"),
+];
+
+/// Polls the sampled metrics and redraws the charts as SVG polylines; the
+/// poll interval follows the server's sampling interval.
+const METRICS_JS: &str = r#"
+(() => {
+  const box = document.getElementById('metrics');
+  if (!box) return;
+  const units = ['B', 'KiB', 'MiB', 'GiB', 'TiB'];
+  const bytes = (v) => {
+    let u = 0;
+    while (v >= 1024 && u < units.length - 1) { v /= 1024; u++; }
+    return v.toFixed(1) + ' ' + units[u];
+  };
+  const rate = (v) => bytes(v) + '/s';
+  const charts = {
+    cpu: { max: 100, format: (v) => v.toFixed(1) + ' %', series: [['cpu_percent', 'busy']] },
+    memory: { format: bytes, series: [['memory', 'balloon'], ['memory_used', 'used'], ['memory_rss', 'RSS']] },
+    disk: { format: rate, series: [['block_read', 'read'], ['block_write', 'write']] },
+    net: { format: rate, series: [['net_rx', 'received'], ['net_tx', 'sent']] },
+  };
+  const colours = ['#1e88e5', '#e53935', '#43a047'];
+  async function refresh() {
+    let interval = 10;
+    try {
+      const res = await fetch('/api/domains/' + box.dataset.uuid + '/metrics');
+      if (res.ok) {
+        const data = await res.json();
+        interval = data.interval || interval;
+        draw(data.samples);
+      }
+    } finally {
+      setTimeout(refresh, interval * 1000);
+    }
+  }
+  function draw(samples) {
+    const n = samples.length;
+    box.querySelector('.empty').hidden = n > 1;
+    for (const [name, chart] of Object.entries(charts)) {
+      const values = chart.series.map(([key]) => samples.map((s) => s[key]));
+      const max = chart.max || Math.max(1, ...values.flat().filter((v) => v != null));
+      box.querySelector('svg[data-chart=' + name + ']').innerHTML = values.map((vals, i) => {
+        const points = vals
+          .map((v, x) => v == null ? null : (n > 1 ? x * 300 / (n - 1) : 0) + ',' + (100 - v * 100 / max))
+          .filter((p) => p)
+          .join(' ');
+        return '<polyline fill="none" stroke-width="1.5" vector-effect="non-scaling-stroke" stroke="'
+          + colours[i] + '" points="' + points + '"/>';
+      }).join('');
+      box.querySelector('[data-legend=' + name + ']').innerHTML = chart.series.map(([, label], i) => {
+        const last = values[i][n - 1];
+        return '<span style="color:' + colours[i] + ';">' + label + ': '
+          + (last == null ? '–' : chart.format(last)) + '</span>';
+      }).join(' ');
+    }
+  }
+  refresh();
+})();
+"#;
+
+/// Follows domain events: state cells (`data-state-of`) are updated in
+/// place, a domain list reloads when domains are added or removed.
+/// Device editor buttons and forms take their scope from the editor's scope
+/// picker; this runs in the capture phase, before the generic `data-api`
+/// handlers send the request.
+const DEVICES_JS: &str = r#"
+const scopePicker = document.getElementById('device-scope');
+if (scopePicker) {
+  document.addEventListener('click', (ev) => {
+    const btn = ev.target.closest('[data-device]');
+    if (!btn) return;
+    const sep = btn.dataset.device.includes('?') ? '&' : '?';
+    btn.dataset.api = btn.dataset.device + sep + 'scope=' + scopePicker.value;
+  }, true);
+  document.addEventListener('submit', (ev) => {
+    const scope = ev.target.querySelector('input[name="scope"]');
+    if (scope) scope.value = scopePicker.value;
+  }, true);
+}
+"#;
+
+const EVENTS_JS: &str = r#"
+(() => {
+  const source = new EventSource('/api/events');
+  source.addEventListener('domain', (ev) => {
+    const event = JSON.parse(ev.data);
+    if (event.state) {
+      document.querySelectorAll('[data-state-of="' + event.uuid + '"]')
+        .forEach((cell) => { cell.textContent = event.state; });
+    }
+    const listed = document.querySelector('[data-domain-list]');
+    if (listed && ((event.event === 'defined' && event.detail !== 'updated')
+        || event.event === 'undefined')) {
+      location.reload();
+    }
+  });
+  source.addEventListener('resync', () => location.reload());
+})();
+"#;
+
+/// Row of the domain list.
+#[derive(Clone, PartialEq)]
+pub struct DomainRow {
+    pub name: String,
+    pub uuid: String,
+    pub state: String,
+    pub memory_mib: u64,
+}
+
+/// Everything shown on the domain detail page.
+#[derive(Clone, PartialEq)]
+struct DomainDetail {
+    row: DomainRow,
+    max_mem_mib: u64,
+    cpu_seconds: u64,
+    addresses: Vec<(String, String, String)>, // interface, mac, ip/prefix
+    snapshots: Vec<SnapshotRow>,
+    devices: DeviceList,
+}
+
+/// Snapshot tree flattened in display order; `depth` drives the indent.
+#[derive(Clone, PartialEq)]
+struct SnapshotRow {
+    depth: usize,
+    name: String,
+    description: String,
+    state: String,
+    created: String,
+    kind: String,
+    current: bool,
+    has_children: bool,
+}
+
+fn flatten_snapshots(nodes: Vec<SnapshotNode>, depth: usize, out: &mut Vec<SnapshotRow>) {
+    for node in nodes {
+        let kind = match (node.external, node.memory.as_str()) {
+            (true, "no") => "external, disk only",
+            (true, _) => "external with memory",
+            (false, "no") => "internal, disk only",
+            (false, _) => "internal with memory",
+        };
+        out.push(SnapshotRow {
+            depth,
+            name: node.name,
+            description: node.description,
+            state: node.state,
+            created: format_timestamp(node.created),
+            kind: kind.to_string(),
+            current: node.current,
+            has_children: !node.children.is_empty(),
+        });
+        flatten_snapshots(node.children, depth + 1, out);
+    }
+}
+
+/// All domains, sorted by name.
+pub fn load_domains(uri: &str) -> Result<Vec<DomainRow>, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let domains = conn.list_all_domains(0).map_err(libvirt_error)?;
+    let mut rows = domains
+        .iter()
+        .map(domain_info)
+        .map(|info| {
+            info.map(|info| DomainRow {
+                name: info.name,
+                uuid: info.uuid,
+                state: info.state,
+                memory_mib: info.memory / 1024,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(libvirt_error)?;
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(rows)
+}
+
+fn load_detail(uri: &str, uuid: &str) -> Result<DomainDetail, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let dom = lookup(&conn, uuid)?;
+    let info = domain_info(&dom).map_err(libvirt_error)?;
+    // Leases are only known for running guests on libvirt managed networks
+    let addresses = leased_addresses(&dom)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|iface| {
+            iface.addresses.into_iter().map(move |a| {
+                (
+                    iface.name.clone(),
+                    iface.mac.clone(),
+                    format!("{}/{}", a.ip, a.prefix),
+                )
+            })
+        })
+        .collect();
+    let mut snapshots = Vec::new();
+    flatten_snapshots(snapshot_tree(&dom)?.roots, 0, &mut snapshots);
+    // The running configuration while the domain runs
+    let devices =
+        parse_devices(&dom.get_xml_desc(0).map_err(libvirt_error)?).map_err(internal_error)?;
+    Ok(DomainDetail {
+        row: DomainRow {
+            name: info.name,
+            uuid: info.uuid,
+            state: info.state,
+            memory_mib: info.memory / 1024,
+        },
+        max_mem_mib: info.max_mem / 1024,
+        cpu_seconds: info.time / 1_000_000_000,
+        addresses,
+        snapshots,
+        devices,
+    })
+}
+
+#[component]
+pub fn DomainTable(domains: Vec<DomainRow>) -> Element {
+    rsx! {
+      table { style: "width:100%;background:white;", "data-domain-list": "true",
+        thead {
+          tr {
+            th { "Name" }
+            th { "State" }
+            th { "Memory (MiB)" }
+            th { "UUID" }
+          }
+        }
+        tbody {
+          for dom in domains {
+            tr {
+              td {
+                a { href: "/dashboard/domains/{dom.uuid}", "{dom.name}" }
+              }
+              td { "data-state-of": "{dom.uuid}", "{dom.state}" }
+              td { "{dom.memory_mib}" }
+              td { "{dom.uuid}" }
+            }
+          }
+        }
+      }
+      script { dangerous_inner_html: EVENTS_JS }
+    }
+}
+
+/// Progress column of the backup history.
+fn backup_progress(backup: &Backup) -> String {
+    match (backup.bytes_processed, backup.bytes_total) {
+        (Some(done), Some(total)) if total > 0 => format!(
+            "{} of {} ({}%)",
+            format_bytes(done as u64),
+            format_bytes(total as u64),
+            done * 100 / total
+        ),
+        _ => String::new(),
+    }
+}
+
+#[component]
+fn DomainPage(
+    detail: DomainDetail,
+    backups: Vec<Backup>,
+    keep_full: Option<i64>,
+    template: bool,
+) -> Element {
+    let dom = &detail.row;
+    let memory_mib = detail.devices.memory_kib / 1024;
+    let max_memory_mib = detail.devices.max_memory_kib / 1024;
+    rsx! {
+      Layout {
+        h1 { "{dom.name}" }
+        div {
+          for (action , label) in DOMAIN_ACTIONS {
+            button {
+              "data-api": "/api/domains/{dom.uuid}/{action}",
+              "data-method": "POST",
+              "{label}"
+            }
+          }
+          a { href: "/dashboard/domains/{dom.uuid}/console",
+            button { "Console" }
+          }
+          a { href: "/dashboard/domains/{dom.uuid}/serial",
+            button { "Serial" }
+          }
+          a { href: "/dashboard/domains/{dom.uuid}/migrate",
+            button { "Migrate" }
+          }
+          a { href: "/dashboard/domains/{dom.uuid}/cloud-init",
+            button { "cloud-init" }
+          }
+          if template {
+            button {
+              "data-api": "/api/domains/{dom.uuid}/template",
+              "data-method": "DELETE",
+              "Unmark template"
+            }
+          } else {
+            button {
+              "data-api": "/api/domains/{dom.uuid}/template",
+              "data-method": "PUT",
+              "data-body": "{{}}",
+              "Mark as template"
+            }
+          }
+        }
+        table { style: "background:white;",
+          tr {
+            th { "State" }
+            td { "data-state-of": "{dom.uuid}", "{dom.state}" }
+          }
+          tr {
+            th { "UUID" }
+            td { "{dom.uuid}" }
+          }
+          tr {
+            th { "Memory" }
+            td { "{dom.memory_mib} MiB of {detail.max_mem_mib} MiB" }
+          }
+          tr {
+            th { "CPU time" }
+            td { "{detail.cpu_seconds} s" }
+          }
+        }
+        h2 { "Devices" }
+        p {
+          "Apply changes to "
+          select { id: "device-scope",
+            option { value: "current", "the domain as it is now (running or saved)" }
+            option { value: "live", "the running domain only" }
+            option { value: "config", "the saved definition only (next boot)" }
+            option { value: "both", "the running domain and the saved definition" }
+          }
+        }
+        form {
+          "data-api": "/api/domains/{dom.uuid}/vcpus",
+          "data-method": "PUT",
+          input { r#type: "hidden", name: "scope" }
+          "vCPUs: "
+          input {
+            name: "count",
+            r#type: "number",
+            min: "1",
+            max: "{detail.devices.max_vcpus}",
+            value: "{detail.devices.vcpus}",
+            required: true,
+          }
+          " of {detail.devices.max_vcpus} "
+          button { r#type: "submit", "Set vCPUs" }
+        }
+        form {
+          "data-api": "/api/domains/{dom.uuid}/memory",
+          "data-method": "PUT",
+          input { r#type: "hidden", name: "scope" }
+          "Memory (balloon): "
+          input {
+            name: "memory_mib",
+            r#type: "number",
+            min: "1",
+            max: "{max_memory_mib}",
+            value: "{memory_mib}",
+            required: true,
+          }
+          " MiB of {max_memory_mib} MiB "
+          button { r#type: "submit", "Set memory" }
+        }
+        h3 { "Disks" }
+        table { style: "background:white;",
+          thead {
+            tr {
+              th { "Target" }
+              th { "Device" }
+              th { "Bus" }
+              th { "Source" }
+              th { "Format" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for disk in detail.devices.disks.iter() {
+              tr {
+                td { "{disk.target}" }
+                td {
+                  "{disk.device}"
+                  if disk.readonly {
+                    " (read-only)"
+                  }
+                }
+                td { "{disk.bus}" }
+                td { {disk.source.clone().unwrap_or_else(|| "empty".into())} }
+                td { {disk.format.clone().unwrap_or_default()} }
+                td {
+                  if disk.device == "cdrom" || disk.device == "floppy" {
+                    form {
+                      "data-api": "/api/domains/{dom.uuid}/disks/{disk.target}/media",
+                      "data-method": "PUT",
+                      input { r#type: "hidden", name: "scope" }
+                      input { name: "pool", placeholder: "pool", size: "8" }
+                      input { name: "volume", placeholder: "volume", size: "12" }
+                      " or "
+                      input { name: "path", placeholder: "path", size: "16" }
+                      button { r#type: "submit", "Change media" }
+                    }
+                    if disk.source.is_some() {
+                      button {
+                        "data-device": "/api/domains/{dom.uuid}/disks/{disk.target}/media",
+                        "data-method": "DELETE",
+                        "Eject"
+                      }
+                    }
+                  }
+                  button {
+                    "data-device": "/api/domains/{dom.uuid}/disks/{disk.target}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Detach {disk.target} from {dom.name}?",
+                    "Detach"
+                  }
+                }
+              }
+            }
+          }
+        }
+        form { "data-api": "/api/domains/{dom.uuid}/disks",
+          input { r#type: "hidden", name: "scope" }
+          "Attach "
+          select { name: "device",
+            option { value: "disk", "disk" }
+            option { value: "cdrom", "CD-ROM" }
+          }
+          " on "
+          select { name: "bus",
+            option { value: "virtio", "virtio" }
+            option { value: "scsi", "SCSI" }
+            option { value: "sata", "SATA" }
+            option { value: "usb", "USB" }
+          }
+          ": "
+          input { name: "pool", placeholder: "pool", size: "8" }
+          input { name: "volume", placeholder: "volume", size: "12" }
+          " or "
+          input { name: "path", placeholder: "path", size: "16" }
+          input { name: "target", placeholder: "target (auto)", size: "8" }
+          label {
+            input { name: "readonly", r#type: "checkbox" }
+            " read-only"
+          }
+          button { r#type: "submit", "Attach" }
+        }
+        h3 { "Network interfaces" }
+        table { style: "background:white;",
+          thead {
+            tr {
+              th { "MAC" }
+              th { "Type" }
+              th { "Source" }
+              th { "Model" }
+              th { "" }
+            }
+          }
+          tbody {
+            for nic in detail.devices.interfaces.iter() {
+              tr {
+                td { "{nic.mac}" }
+                td { "{nic.kind}" }
+                td { {nic.source.clone().unwrap_or_default()} }
+                td { {nic.model.clone().unwrap_or_default()} }
+                td {
+                  button {
+                    "data-device": "/api/domains/{dom.uuid}/interfaces/{nic.mac}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Remove interface {nic.mac} from {dom.name}?",
+                    "Remove"
+                  }
+                }
+              }
+            }
+          }
+        }
+        form { "data-api": "/api/domains/{dom.uuid}/interfaces",
+          input { r#type: "hidden", name: "scope" }
+          "Add interface on network "
+          input { name: "network", placeholder: "network", size: "10" }
+          " or bridge "
+          input { name: "bridge", placeholder: "bridge", size: "10" }
+          select { name: "model",
+            option { value: "virtio", "virtio" }
+            option { value: "e1000e", "e1000e" }
+            option { value: "rtl8139", "rtl8139" }
+          }
+          input { name: "mac", placeholder: "MAC (auto)", size: "17" }
+          button { r#type: "submit", "Add" }
+        }
+        h2 { "Performance" }
+        div { id: "metrics", "data-uuid": "{dom.uuid}",
+          p { class: "empty", "No samples yet – metrics are collected while the domain runs." }
+          for (chart , title) in METRIC_CHARTS {
+            div { style: "display:inline-block;margin:4px;padding:4px;background:white;",
+              strong { "{title}" }
+              svg {
+                "data-chart": chart,
+                "viewBox": "0 0 300 100",
+                "preserveAspectRatio": "none",
+                width: "300",
+                height: "100",
+              }
+              div { "data-legend": chart, style: "font-size:small;" }
+            }
+          }
+        }
+        h2 { "Leased IP addresses" }
+        if detail.addresses.is_empty() {
+          p { "No DHCP leases – the domain is not running or not on a libvirt network." }
+        } else {
+          table { style: "background:white;",
+            thead {
+              tr {
+                th { "Interface" }
+                th { "MAC" }
+                th { "Address" }
+              }
+            }
+            tbody {
+              for (iface , mac , ip) in detail.addresses.iter() {
+                tr {
+                  td { "{iface}" }
+                  td { "{mac}" }
+                  td { "{ip}" }
+                }
+              }
+            }
+          }
+        }
+        h2 { "Snapshots" }
+        if detail.snapshots.is_empty() {
+          p { "No snapshots." }
+        } else {
+          table { style: "background:white;",
+            thead {
+              tr {
+                th { "Name" }
+                th { "Created" }
+                th { "State" }
+                th { "Type" }
+                th { "Description" }
+                th { "Actions" }
+              }
+            }
+            tbody {
+              for snap in detail.snapshots.iter() {
+                tr {
+                  td { style: format!("padding-left:{}px;", 8 + snap.depth * 20),
+                    if snap.depth > 0 {
+                      "└ "
+                    }
+                    "{snap.name}"
+                    if snap.current {
+                      strong { " (current)" }
+                    }
+                  }
+                  td { "{snap.created}" }
+                  td { "{snap.state}" }
+                  td { "{snap.kind}" }
+                  td { "{snap.description}" }
+                  td {
+                    button {
+                      "data-api": "/api/domains/{dom.uuid}/snapshots/{snap.name}/revert",
+                      "data-method": "POST",
+                      "data-confirm": "Revert {dom.name} to snapshot {snap.name}? Changes since then are lost.",
+                      "Revert"
+                    }
+                    button {
+                      "data-api": "/api/domains/{dom.uuid}/snapshots/{snap.name}",
+                      "data-method": "DELETE",
+                      "data-confirm": "Delete snapshot {snap.name}?",
+                      "Delete"
+                    }
+                    if snap.has_children {
+                      button {
+                        "data-api": "/api/domains/{dom.uuid}/snapshots/{snap.name}?children=true",
+                        "data-method": "DELETE",
+                        "data-confirm": "Delete snapshot {snap.name} and all of its children?",
+                        "Delete with children"
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+        form { "data-api": "/api/domains/{dom.uuid}/snapshots",
+          "New snapshot: "
+          input { name: "name", placeholder: "name", required: true }
+          input { name: "description", placeholder: "description" }
+          label {
+            input { name: "memory", r#type: "checkbox" }
+            " memory state"
+          }
+          label {
+            input { name: "external", r#type: "checkbox" }
+            " external"
+          }
+          input { name: "memory_file", placeholder: "memory file (external with memory)" }
+          button { r#type: "submit", "Create snapshot" }
+        }
+        h2 { "Backups" }
+        if backups.is_empty() {
+          p { "No backups yet." }
+        } else {
+          table { style: "background:white;",
+            thead {
+              tr {
+                th { "Started" }
+                th { "Kind" }
+                th { "Checkpoint" }
+                th { "Status" }
+                th { "Progress" }
+                th { "Target" }
+                th { "" }
+              }
+            }
+            tbody {
+              for backup in backups.iter() {
+                tr {
+                  td { "{backup.started_at}" }
+                  td { "{backup.kind}" }
+                  td { "{backup.checkpoint}" }
+                  td {
+                    "{backup.status}"
+                    if let Some(error) = &backup.error {
+                      div { style: "color:red;", "{error}" }
+                    }
+                  }
+                  td { {backup_progress(backup)} }
+                  td { "{backup.target_dir}" }
+                  td {
+                    if backup.status == "running" {
+                      button {
+                        "data-api": "/api/backups/{backup.id}/cancel",
+                        "data-method": "POST",
+                        "data-confirm": "Cancel this backup?",
+                        "Cancel"
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+        form { "data-api": "/api/domains/{dom.uuid}/backups",
+          "Start backup: "
+          input { name: "target_dir", placeholder: "target directory" }
+          " or "
+          input { name: "pool", placeholder: "storage pool" }
+          label {
+            input { name: "incremental", r#type: "checkbox" }
+            " incremental"
+          }
+          button { r#type: "submit", "Start backup" }
+        }
+        form {
+          "data-api": "/api/domains/{dom.uuid}/backup-retention",
+          "data-method": "PUT",
+          "Keep the last "
+          input {
+            name: "keep_full",
+            r#type: "number",
+            min: "1",
+            value: keep_full.map(|k| k.to_string()).unwrap_or_default(),
+            placeholder: "all",
+          }
+          " full backups "
+          button { r#type: "submit", "Save retention" }
+        }
+      }
+      script { dangerous_inner_html: METRICS_JS }
+      script { dangerous_inner_html: EVENTS_JS }
+      script { dangerous_inner_html: DEVICES_JS }
+    }
+}
+
+// GET /dashboard/domains/{uuid}
+pub async fn domain_page(
+    _user: PageUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> (StatusCode, Html<String>) {
+    let history = backups::list_for_domain(&state.pool, &uuid)
+        .await
+        .unwrap_or_default();
+    let keep_full = backups::retention(&state.pool, &uuid)
+        .await
+        .unwrap_or_default();
+    let template = matches!(templates::get(&state.pool, &uuid).await, Ok(Some(_)));
+    match load_detail(&state.config.libvirt_uri, &uuid) {
+        Ok(detail) => (
+            StatusCode::OK,
+            render_page(rsx!(DomainPage {
+                detail,
+                backups: history,
+                keep_full,
+                template
+            })),
+        ),
+        Err((code, message)) => (code, render_page(rsx!(ErrorPage { message }))),
+    }
+}
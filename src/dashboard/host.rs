@@ -0,0 +1,208 @@
+use axum::{extract::State, response::Html};
+use dioxus::prelude::*;
+
+use super::{ErrorPage, Layout, PageUser, format_bytes, render_page};
+use crate::AppState;
+use crate::api::Libvirt;
+use crate::api::host::{HostOverview, host_overview};
+
+/// A guest architecture with its machine types joined for display.
+#[derive(Clone, PartialEq)]
+struct GuestRow {
+    arch: String,
+    os_type: String,
+    domain_types: String,
+    emulator: String,
+    machines: Vec<String>,
+}
+
+/// One NUMA cell with sizes already formatted.
+#[derive(Clone, PartialEq)]
+struct CellRow {
+    id: u32,
+    memory: String,
+    free: String,
+    cpus: String,
+}
+
+/// `(label, value)` pairs of the summary table.
+fn summary_rows(host: &HostOverview) -> Vec<(&'static str, String)> {
+    let cpu = &host.cpu;
+    vec![
+        ("Hostname", host.hostname.clone()),
+        ("Hypervisor", host.hypervisor.clone()),
+        (
+            "Hypervisor version",
+            host.hypervisor_version.clone().unwrap_or_default(),
+        ),
+        (
+            "libvirt version",
+            host.libvirt_version.clone().unwrap_or_default(),
+        ),
+        ("Architecture", cpu.arch.clone()),
+        (
+            "CPU model",
+            match &cpu.vendor {
+                Some(vendor) => format!("{} ({})", cpu.model, vendor),
+                None => cpu.model.clone(),
+            },
+        ),
+        ("CPUs", format!("{} @ {} MHz", cpu.cpus, cpu.mhz)),
+        (
+            "Topology",
+            format!(
+                "{} socket(s) × {} die(s) × {} core(s) × {} thread(s)",
+                cpu.sockets, cpu.dies, cpu.cores, cpu.threads
+            ),
+        ),
+        ("Memory", format_bytes(host.memory)),
+        (
+            "Free memory",
+            host.free_memory.map(format_bytes).unwrap_or_default(),
+        ),
+    ]
+}
+
+#[component]
+fn HostPage(
+    rows: Vec<(&'static str, String)>,
+    cells: Vec<CellRow>,
+    guests: Vec<GuestRow>,
+    usable_models: Vec<String>,
+    unusable_models: Vec<String>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Host" }
+        table { style: "background:white;",
+          tbody {
+            for (label , value) in rows {
+              tr {
+                th { style: "text-align:left;", "{label}" }
+                td { "{value}" }
+              }
+            }
+          }
+        }
+        h2 { "NUMA cells" }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Cell" }
+              th { "Memory" }
+              th { "Free" }
+              th { "CPUs" }
+            }
+          }
+          tbody {
+            for cell in cells {
+              tr {
+                td { "{cell.id}" }
+                td { "{cell.memory}" }
+                td { "{cell.free}" }
+                td { "{cell.cpus}" }
+              }
+            }
+          }
+        }
+        h2 { "Guest architectures" }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Architecture" }
+              th { "OS type" }
+              th { "Domain types" }
+              th { "Emulator" }
+              th { "Machine types" }
+            }
+          }
+          tbody {
+            for guest in guests {
+              tr {
+                td { "{guest.arch}" }
+                td { "{guest.os_type}" }
+                td { "{guest.domain_types}" }
+                td {
+                  code { "{guest.emulator}" }
+                }
+                td {
+                  for machine in guest.machines.iter() {
+                    div { "{machine}" }
+                  }
+                }
+              }
+            }
+          }
+        }
+        h2 { "CPU models" }
+        p { {usable_models.join(", ")} }
+        if !unusable_models.is_empty() {
+          details {
+            summary { "Not usable on this host ({unusable_models.len()})" }
+            p { style: "color:#555;", {unusable_models.join(", ")} }
+          }
+        }
+      }
+    }
+}
+
+// GET /dashboard/host
+pub async fn host_page(_user: PageUser, State(state): State<AppState>) -> Html<String> {
+    let uri = state.config.libvirt_uri.clone();
+    let loaded = tokio::task::spawn_blocking(move || {
+        let conn = Libvirt::open(&uri)?;
+        host_overview(&conn)
+    })
+    .await;
+    let host = match loaded {
+        Ok(Ok(host)) => host,
+        Ok(Err((_, message))) => return render_page(rsx!(ErrorPage { message })),
+        Err(e) => {
+            return render_page(rsx!(ErrorPage {
+                message: e.to_string()
+            }));
+        }
+    };
+
+    let cells: Vec<CellRow> = host
+        .numa_cells
+        .iter()
+        .map(|cell| CellRow {
+            id: cell.id,
+            memory: format_bytes(cell.memory),
+            free: cell.free_memory.map(format_bytes).unwrap_or_default(),
+            cpus: cell
+                .cpus
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        })
+        .collect();
+    let guests: Vec<GuestRow> = host
+        .guests
+        .iter()
+        .map(|g| GuestRow {
+            arch: g.arch.clone(),
+            os_type: g.os_type.clone(),
+            domain_types: g.domain_types.join(", "),
+            emulator: g.emulator.clone().unwrap_or_default(),
+            machines: g
+                .machines
+                .iter()
+                .map(|m| match &m.canonical {
+                    Some(canonical) => format!("{} → {}", m.name, canonical),
+                    None => m.name.clone(),
+                })
+                .collect(),
+        })
+        .collect();
+    let (usable, unusable): (Vec<_>, Vec<_>) = host.cpu_models.iter().partition(|m| m.usable);
+    render_page(rsx!(HostPage {
+        rows: summary_rows(&host),
+        cells,
+        guests,
+        usable_models: usable.iter().map(|m| m.name.clone()).collect(),
+        unusable_models: unusable.iter().map(|m| m.name.clone()).collect(),
+    }))
+}
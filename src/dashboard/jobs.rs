@@ -0,0 +1,162 @@
+use axum::{extract::State, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, format_timestamp, render_page};
+use crate::AppState;
+use crate::jobs::{self, Job, JobRun};
+
+/// Runs shown under the job table.
+const RECENT_RUNS: i64 = 25;
+
+/// `{"enabled":…}` body that flips a job's state.
+fn enabled_body(enabled: bool) -> &'static str {
+    if enabled {
+        r#"{"enabled":true}"#
+    } else {
+        r#"{"enabled":false}"#
+    }
+}
+
+fn next_run_label(job: &Job) -> String {
+    match (job.enabled, job.next_run) {
+        (false, _) => "disabled".into(),
+        (true, Some(next)) => format_timestamp(next),
+        (true, None) => "never".into(),
+    }
+}
+
+#[component]
+fn JobPage(username: String, jobs: Vec<Job>, runs: Vec<JobRun>, error: Option<String>) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Scheduled jobs" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Name" }
+              th { "Owner" }
+              th { "Schedule" }
+              th { "Action" }
+              th { "Next run (UTC)" }
+              th { "Last run" }
+              th { "Last outcome" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for job in jobs {
+              tr {
+                td { "{job.name}" }
+                td { "{job.owner}" }
+                td {
+                  code { "{job.schedule}" }
+                }
+                td {
+                  code { "{job.method} {job.path}" }
+                }
+                td { {next_run_label(&job)} }
+                td { {job.last_run.clone().unwrap_or_default()} }
+                td {
+                  if let Some(status) = job.last_status {
+                    strong { style: format!("color:{};", if status < 400 { "green" } else { "red" }),
+                      "{status} "
+                    }
+                  }
+                  {job.last_outcome.clone().unwrap_or_default()}
+                }
+                td {
+                  if job.owner == username {
+                    button {
+                      "data-api": "/api/jobs/{job.id}/run",
+                      "data-method": "POST",
+                      disabled: !job.enabled,
+                      "Run now"
+                    }
+                    button {
+                      "data-api": "/api/jobs/{job.id}/enabled",
+                      "data-method": "PUT",
+                      "data-body": enabled_body(!job.enabled),
+                      if job.enabled { "Disable" } else { "Enable" }
+                    }
+                    button {
+                      "data-api": "/api/jobs/{job.id}",
+                      "data-method": "DELETE",
+                      "data-confirm": "Delete job {job.name} and its history?",
+                      "Delete"
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+        h2 { "New job" }
+        form { "data-api": "/api/jobs",
+          input { name: "name", placeholder: "name", required: true }
+          input {
+            name: "schedule",
+            placeholder: "cron, e.g. 0 19 * * 1-5",
+            required: true,
+          }
+          select { name: "method",
+            option { value: "POST", "POST" }
+            option { value: "PUT", "PUT" }
+            option { value: "DELETE", "DELETE" }
+          }
+          input {
+            name: "path",
+            placeholder: "/api/domains/<uuid>/shutdown",
+            size: "40",
+            required: true,
+          }
+          input { name: "body", placeholder: "JSON body (optional)", size: "30" }
+          button { r#type: "submit", "Create job" }
+        }
+        h2 { "Recent runs" }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Job" }
+              th { "Started" }
+              th { "Finished" }
+              th { "Status" }
+              th { "Outcome" }
+            }
+          }
+          tbody {
+            for run in runs {
+              tr {
+                td { "{run.job_name}" }
+                td { "{run.started_at}" }
+                td { {run.finished_at.clone().unwrap_or_else(|| "running".into())} }
+                td { {run.status.map(|s| s.to_string()).unwrap_or_default()} }
+                td { {run.outcome.clone().unwrap_or_default()} }
+              }
+            }
+          }
+        }
+      }
+    }
+}
+
+// GET /dashboard/jobs
+pub async fn job_page(PageUser(user): PageUser, State(state): State<AppState>) -> Html<String> {
+    let loaded = async {
+        let list = jobs::list(&state.pool).await?;
+        let runs = jobs::runs(&state.pool, None, RECENT_RUNS).await?;
+        anyhow::Ok((list, runs))
+    };
+    let (jobs, runs, error) = match loaded.await {
+        Ok((jobs, runs)) => (jobs, runs, None),
+        Err(e) => (Vec::new(), Vec::new(), Some(e.to_string())),
+    };
+    render_page(rsx!(JobPage {
+        username: user.username,
+        jobs,
+        runs,
+        error
+    }))
+}
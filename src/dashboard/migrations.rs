@@ -0,0 +1,226 @@
+use axum::{
+    extract::{Path, State},
+    response::Html,
+};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, format_bytes, render_page};
+use crate::AppState;
+use crate::api::domains::lookup;
+use crate::api::{Libvirt, domain_state_name, libvirt_error};
+use crate::hosts;
+use crate::migrations::{self, Migration};
+
+/// Polls running migrations and updates their progress cells; the page is
+/// reloaded once one of them finishes.
+const PROGRESS_JS: &str = r#"
+const rows = document.querySelectorAll('[data-migration][data-status="running"]');
+if (rows.length > 0) {
+  const mib = (bytes) => (bytes / 1048576).toFixed(0) + ' MiB';
+  setInterval(async () => {
+    for (const row of rows) {
+      const res = await fetch('/api/migrations/' + row.dataset.migration);
+      if (!res.ok) continue;
+      const m = await res.json();
+      if (m.status !== 'running') { location.reload(); return; }
+      const progress = row.querySelector('.progress');
+      if (m.bytes_total) {
+        const percent = Math.floor(m.bytes_processed * 100 / m.bytes_total);
+        progress.textContent = mib(m.bytes_processed) + ' of ' + mib(m.bytes_total)
+          + ' (' + percent + '%)' + (m.post_copy ? ' post-copy' : '');
+      }
+    }
+  }, 2000);
+}
+"#;
+
+/// Progress column of the migration history.
+fn migration_progress(migration: &Migration) -> String {
+    let mut out = match (migration.bytes_processed, migration.bytes_total) {
+        (Some(done), Some(total)) if total > 0 => format!(
+            "{} of {} ({}%)",
+            format_bytes(done as u64),
+            format_bytes(total as u64),
+            done * 100 / total
+        ),
+        _ => String::new(),
+    };
+    if migration.post_copy {
+        out.push_str(" post-copy");
+    }
+    out
+}
+
+#[component]
+fn MigrationPage(
+    name: String,
+    uuid: String,
+    /// `None` once the domain is no longer defined on this host
+    state: Option<String>,
+    hosts: Vec<String>,
+    history: Vec<Migration>,
+) -> Element {
+    let current = state.as_deref().unwrap_or_default();
+    rsx! {
+      Layout {
+        h1 { "Migrate – {name}" }
+        a { href: "/dashboard/domains/{uuid}",
+          button { "Back" }
+        }
+        if state.is_none() {
+          p { "The domain is not defined on this host any more." }
+        } else if hosts.is_empty() {
+          p {
+            "No other hosts are registered – add one with "
+            code { "rust-manager hosts add" }
+            "."
+          }
+        } else {
+          form { "data-api": "/api/domains/{uuid}/migrate",
+            fieldset {
+              legend { "1. Destination" }
+              select { name: "host",
+                for host in hosts.iter() {
+                  option { value: "{host}", "{host}" }
+                }
+              }
+              label {
+                input { name: "peer_to_peer", r#type: "checkbox" }
+                " peer-to-peer (the source libvirtd must reach the host's URI)"
+              }
+            }
+            fieldset {
+              legend { "2. Mode" }
+              p {
+                "The domain is {current}; stopped domains are migrated offline."
+              }
+              label {
+                input { name: "live", r#type: "checkbox", checked: true }
+                " live (otherwise the guest is paused during the transfer)"
+              }
+              label {
+                input { name: "keep_source", r#type: "checkbox" }
+                " keep the definition on this host"
+              }
+            }
+            fieldset {
+              legend { "3. Options" }
+              "Copy storage: "
+              select { name: "copy_storage",
+                option { value: "", "no – shared storage" }
+                option { value: "all", "all disks" }
+                option { value: "incremental", "incremental (backing images exist on the host)" }
+              }
+              input {
+                name: "bandwidth",
+                r#type: "number",
+                min: "1",
+                placeholder: "bandwidth MiB/s",
+              }
+              label {
+                input { name: "auto_converge", r#type: "checkbox" }
+                " auto-converge"
+              }
+              label {
+                input { name: "post_copy", r#type: "checkbox" }
+                " post-copy after the first pass"
+              }
+            }
+            button { r#type: "submit", "Migrate" }
+          }
+        }
+        h2 { "History" }
+        if history.is_empty() {
+          p { "No migrations yet." }
+        } else {
+          table { style: "background:white;",
+            thead {
+              tr {
+                th { "Started" }
+                th { "Destination" }
+                th { "Mode" }
+                th { "Options" }
+                th { "Status" }
+                th { "Progress" }
+                th { "" }
+              }
+            }
+            tbody {
+              for migration in history.iter() {
+                tr {
+                  "data-migration": "{migration.id}",
+                  "data-status": "{migration.status}",
+                  td { "{migration.started_at}" }
+                  td { "{migration.destination}" }
+                  td { "{migration.mode}" }
+                  td { "{migration.options}" }
+                  td {
+                    "{migration.status}"
+                    if let Some(error) = &migration.error {
+                      div { style: "color:red;", "{error}" }
+                    }
+                  }
+                  td { class: "progress", {migration_progress(migration)} }
+                  td {
+                    if migration.status == "running" && !migration.post_copy {
+                      button {
+                        "data-api": "/api/migrations/{migration.id}/cancel",
+                        "data-method": "POST",
+                        "data-confirm": "Cancel this migration?",
+                        "Cancel"
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+      script { dangerous_inner_html: PROGRESS_JS }
+    }
+}
+
+// GET /dashboard/domains/{uuid}/migrate
+pub async fn migration_page(
+    _user: PageUser,
+    State(state): State<AppState>,
+    Path(uuid): Path<String>,
+) -> Html<String> {
+    let history = migrations::list_for_domain(&state.pool, &uuid)
+        .await
+        .unwrap_or_default();
+    let hosts: Vec<String> = hosts::list(&state.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|h| h.uri != state.config.libvirt_uri)
+        .map(|h| h.name)
+        .collect();
+    let found = Libvirt::open(&state.config.libvirt_uri).and_then(|conn| {
+        let dom = lookup(&conn, &uuid)?;
+        let (dom_state, _) = dom.get_state().map_err(libvirt_error)?;
+        Ok((
+            dom.get_name().map_err(libvirt_error)?,
+            domain_state_name(dom_state),
+        ))
+    });
+    // After a migration the domain is gone here but its history is not
+    let (name, dom_state) = match found {
+        Ok((name, dom_state)) => (name, Some(dom_state.to_lowercase())),
+        Err(_) => (
+            history
+                .first()
+                .map(|m| m.domain_name.clone())
+                .unwrap_or_else(|| uuid.clone()),
+            None,
+        ),
+    };
+    render_page(rsx!(MigrationPage {
+        name,
+        uuid,
+        state: dom_state,
+        hosts,
+        history
+    }))
+}
@@ -0,0 +1,297 @@
+use axum::{
+    extract::{FromRequestParts, State},
+    http::{StatusCode, request::Parts},
+    response::{Html, Redirect},
+};
+
+use dioxus::prelude::*;
+use dioxus_ssr::render_element;
+
+use crate::AppState;
+use crate::api::{Libvirt, libvirt_error};
+use crate::auth::CurrentUser;
+
+pub mod assets;
+pub mod cloudinit;
+pub mod console;
+pub mod domains;
+pub mod host;
+pub mod jobs;
+pub mod migrations;
+pub mod networks;
+pub mod nodedevs;
+pub mod nwfilters;
+pub mod pools;
+pub mod profile;
+pub mod secrets;
+pub mod templates;
+pub mod volumes;
+
+/// Side menu entries – `None` while the section has no page yet.
+const SIDE_ITEMS: [(&str, &str, Option<&str>); 10] = [
+    ("Domain", "🗂️", Some("/dashboard")),
+    ("Templates", "🧬", Some("/dashboard/templates")),
+    ("Host", "🏠", Some("/dashboard/host")),
+    ("Devices", "🔌", Some("/dashboard/devices")),
+    ("Network", "🌐", Some("/dashboard/networks")),
+    ("Firewall", "🛡️", Some("/dashboard/nwfilters")),
+    ("Secret", "🔑", Some("/dashboard/secrets")),
+    ("Pool", "🔋", Some("/dashboard/pools")),
+    ("Volume", "📦", Some("/dashboard/volumes")),
+    ("Jobs", "⏰", Some("/dashboard/jobs")),
+];
+
+/// Buttons carrying `data-api` call that endpoint with `data-method` (and the
+/// optional JSON `data-body`), then reload the page or show the error. Forms
+/// with `data-api` send their named fields as a JSON object the same way.
+const API_BUTTONS_JS: &str = r#"
+document.addEventListener('click', async (ev) => {
+  const btn = ev.target.closest('[data-api]');
+  if (!btn) return;
+  if (btn.dataset.confirm && !confirm(btn.dataset.confirm)) return;
+  const opts = { method: btn.dataset.method || 'POST', headers: {} };
+  if (btn.dataset.body) {
+    opts.headers['content-type'] = 'application/json';
+    opts.body = btn.dataset.body;
+  }
+  const res = await fetch(btn.dataset.api, opts);
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+});
+document.addEventListener('submit', async (ev) => {
+  const form = ev.target;
+  if (!form.dataset.api) return;
+  ev.preventDefault();
+  const body = {};
+  for (const el of form.elements) {
+    if (!el.name) continue;
+    if (el.type === 'checkbox') body[el.name] = el.checked;
+    else if (el.type === 'number') { if (el.value !== '') body[el.name] = Number(el.value); }
+    else if (el.value !== '') body[el.name] = el.value;
+  }
+  const res = await fetch(form.dataset.api, {
+    method: form.dataset.method || 'POST',
+    headers: { 'content-type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+});
+"#;
+
+/// Page chrome shared by every dashboard section: side menu, top bar and a
+/// content area holding `children`.
+#[component]
+pub fn Layout(children: Element) -> Element {
+    // Reactive signals
+    let mut collapsed = use_signal(|| false); // side‑menu collapse state
+    let mut user_menu_open = use_signal(|| false); // user avatar drop‑down state
+
+    // Dynamic CSS values
+    let side_menu_width = if collapsed() { "60px" } else { "200px" };
+    let text_display = if collapsed() { "none" } else { "block" };
+
+    // Icons (Unicode placeholders)
+    let top_icons = [("file", "📄"), ("edit", "✏️"), ("help", "❓")];
+
+    rsx! {
+      div { class: "app", style: "display:flex;height:100vh;",
+        div {
+          class: "side-menu",
+          style: format!(
+              "display:flex;flex-direction:column;width:{};background:#2c3e50;",
+              side_menu_width,
+          ),
+          button {
+            style: "background:none;border:none;color:white;font-size:24px;cursor:pointer;margin:10px;",
+            onclick: move |_| {
+                collapsed.toggle();
+            },
+            "☰"
+          }
+          ul { style: "list-style:none;padding:0;margin:0;",
+
+            for (text , icon , href) in SIDE_ITEMS {
+              li { style: "color:white;display:flex;align-items:center;padding:10px;",
+                span { style: "font-size:20px;", "{icon}" }
+                span { style: format!("display:{};margin-left:8px;", text_display),
+                  a { href: href.unwrap_or("#"),
+                    button { disabled: href.is_none(), "{text}" }
+                  }
+                }
+              }
+            }
+          }
+        }
+        div { style: "flex:1;display:flex;flex-direction:column;",
+          div {
+            class: "top-bar",
+            style: "display:flex;align-items:center;justify-content:space-between;background:#ecf0f1;padding:10px;",
+            div { style: "display:flex;align-items:center;",
+
+              for (name , icon) in top_icons {
+                button { style: "background:none;border:none;font-size:16px;margin-right:20px;",
+                  "{icon}-{name}"
+
+                }
+              }
+            }
+            div { style: "position:relative;",
+              button {
+                style: "border:none;background:none;cursor:pointer;",
+                onclick: move |_| user_menu_open.toggle(),
+                span { style: "border-radius:50%;width:32px;height:32px;background:#777;color:white;display:flex;align-items:center;justify-content:center;",
+                  "🧑"
+                }
+              }
+              if user_menu_open() {
+                div {
+                  class: "dropdown",
+                  style: "position:absolute;right:0;top:30px;background:white;border:1px solid #ccc;box-shadow:0 2px 5px rgba(0,0,0,0.2);display:flex;flex-direction:column;",
+                  a { href: "/dashboard/profile",
+                    button { style: "background:none;border:none;text-align:left;padding:8px 12px;width:100%;",
+                      "Profile"
+                    }
+                  }
+                  button { style: "background:none;border:none;text-align:left;padding:8px 12px;width:100%;",
+                    "Settings"
+                  }
+                  button { style: "background:none;border:none;text-align:left;padding:8px 12px;width:100%;",
+                    "Logout"
+                  }
+                }
+              }
+            }
+          }
+          div { style: "flex:1;background:#bdc3c7;overflow:auto;padding:20px;", {children} }
+        }
+      }
+      script { dangerous_inner_html: API_BUTTONS_JS }
+    }
+}
+
+#[component]
+fn DashboardPage(
+    username: String,
+    domains: Vec<domains::DomainRow>,
+    error: Option<String>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Welcome, {username}!" }
+        p { "This is your Rust‑Manager dashboard." }
+        h2 { "Domains" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        domains::DomainTable { domains }
+      }
+    }
+}
+
+/// Layout with nothing but an error message.
+#[component]
+pub fn ErrorPage(message: String) -> Element {
+    rsx! {
+      Layout {
+        p { style: "color:red;", "{message}" }
+      }
+    }
+}
+
+/// Every domain as `(name, uuid)`, ordered by name – for domain pickers.
+pub fn domain_choices(conn: &Libvirt) -> Result<Vec<(String, String)>, (StatusCode, String)> {
+    let mut domains = Vec::new();
+    for dom in conn.list_all_domains(0).map_err(libvirt_error)? {
+        domains.push((
+            dom.get_name().map_err(libvirt_error)?,
+            dom.get_uuid_string().map_err(libvirt_error)?,
+        ));
+    }
+    domains.sort();
+    Ok(domains)
+}
+
+/// JSON body that switches autostart to `enabled`.
+pub fn autostart_body(enabled: bool) -> &'static str {
+    if enabled {
+        r#"{"autostart":true}"#
+    } else {
+        r#"{"autostart":false}"#
+    }
+}
+
+/// Format a byte count with a binary unit, e.g. `12.5 GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Format seconds since the unix epoch as `YYYY-MM-DD HH:MM:SS` UTC.
+pub fn format_timestamp(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Logged‑in user of a dashboard page – anonymous visitors are sent to the
+/// login form instead of getting a bare 401.
+pub struct PageUser(pub CurrentUser);
+
+impl FromRequestParts<AppState> for PageUser {
+    type Rejection = Redirect;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Redirect> {
+        CurrentUser::from_request_parts(parts, state)
+            .await
+            .map(PageUser)
+            .map_err(|_| Redirect::to("/login"))
+    }
+}
+
+/// Render a dashboard page to HTML.
+pub fn render_page(page: Element) -> Html<String> {
+    // `render_element` consumes the rsx! tree and produces an HTML string.
+    Html(render_element(page))
+}
+
+pub async fn dashboard_page(
+    PageUser(user): PageUser,
+    State(state): State<AppState>,
+) -> Html<String> {
+    let (domains, error) = match domains::load_domains(&state.config.libvirt_uri) {
+        Ok(rows) => (rows, None),
+        Err((_, message)) => (Vec::new(), Some(message)),
+    };
+    render_page(rsx!(DashboardPage {
+        username: user.username,
+        domains,
+        error
+    }))
+}
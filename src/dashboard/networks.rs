@@ -0,0 +1,152 @@
+use axum::{extract::State, http::StatusCode, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, autostart_body, render_page};
+use crate::AppState;
+use crate::api::Libvirt;
+use crate::api::networks::{NetworkInfo, ParsedNetwork, network_info, parse_network_xml};
+
+/// One network row: state from libvirt plus the parsed XML.
+#[derive(Clone, PartialEq)]
+struct NetworkRow {
+    name: String,
+    active: bool,
+    autostart: bool,
+    bridge: String,
+    forward_mode: String,
+    addresses: Vec<String>,
+    dhcp: Vec<String>,
+}
+
+impl NetworkRow {
+    fn new(info: NetworkInfo, parsed: ParsedNetwork) -> NetworkRow {
+        NetworkRow {
+            name: info.name,
+            active: info.active,
+            autostart: info.autostart,
+            bridge: info.bridge.or(parsed.bridge).unwrap_or_default(),
+            forward_mode: parsed.forward_mode.unwrap_or_else(|| "isolated".into()),
+            addresses: parsed
+                .ips
+                .iter()
+                .filter_map(|ip| {
+                    let address = ip.address.as_deref()?;
+                    Some(match (&ip.netmask, ip.prefix) {
+                        (Some(mask), _) => format!("{}/{}", address, mask),
+                        (None, Some(prefix)) => format!("{}/{}", address, prefix),
+                        (None, None) => address.to_string(),
+                    })
+                })
+                .collect(),
+            dhcp: parsed
+                .ips
+                .iter()
+                .flat_map(|ip| ip.dhcp_ranges.iter())
+                .map(|r| format!("{} – {}", r.start, r.end))
+                .collect(),
+        }
+    }
+}
+
+#[component]
+fn NetworkPage(networks: Vec<NetworkRow>, error: Option<String>) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Networks" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Name" }
+              th { "State" }
+              th { "Autostart" }
+              th { "Bridge" }
+              th { "Forward" }
+              th { "Addresses" }
+              th { "DHCP ranges" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for net in networks {
+              tr {
+                td { "{net.name}" }
+                td { if net.active { "🟢 active" } else { "⚪ inactive" } }
+                td { if net.autostart { "yes" } else { "no" } }
+                td { "{net.bridge}" }
+                td { "{net.forward_mode}" }
+                td {
+                  for address in net.addresses.iter() {
+                    div { "{address}" }
+                  }
+                }
+                td {
+                  for range in net.dhcp.iter() {
+                    div { "{range}" }
+                  }
+                }
+                td {
+                  if net.active {
+                    button {
+                      "data-api": "/api/networks/{net.name}/stop",
+                      "data-method": "POST",
+                      "Stop"
+                    }
+                  } else {
+                    button {
+                      "data-api": "/api/networks/{net.name}/start",
+                      "data-method": "POST",
+                      "Start"
+                    }
+                  }
+                  button {
+                    "data-api": "/api/networks/{net.name}/autostart",
+                    "data-method": "PUT",
+                    "data-body": autostart_body(!net.autostart),
+                    if net.autostart { "Disable autostart" } else { "Enable autostart" }
+                  }
+                  button {
+                    "data-api": "/api/networks/{net.name}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Undefine network {net.name}?",
+                    "Undefine"
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+}
+
+/// Collect every network with its parsed XML.
+fn load_networks(uri: &str) -> Result<Vec<NetworkRow>, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let networks = conn
+        .list_all_networks(0)
+        .map_err(crate::api::libvirt_error)?;
+    let mut rows = Vec::new();
+    for net in networks {
+        let info = network_info(&net)?;
+        let parsed = net
+            .get_xml_desc(0)
+            .ok()
+            .and_then(|xml| parse_network_xml(&xml).ok())
+            .unwrap_or_default();
+        rows.push(NetworkRow::new(info, parsed));
+    }
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(rows)
+}
+
+// GET /dashboard/networks
+pub async fn network_page(_user: PageUser, State(state): State<AppState>) -> Html<String> {
+    let (networks, error) = match load_networks(&state.config.libvirt_uri) {
+        Ok(rows) => (rows, None),
+        Err((_, message)) => (Vec::new(), Some(message)),
+    };
+    render_page(rsx!(NetworkPage { networks, error }))
+}
@@ -0,0 +1,223 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Html,
+};
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+use super::{Layout, PageUser, domain_choices, render_page};
+use crate::AppState;
+use crate::api::Libvirt;
+use crate::api::nodedevs::{NodeDeviceInfo, list_nodedevs};
+
+/// Capability tabs: `cap` value and label.
+const CAPABILITIES: [(&str, &str); 5] = [
+    ("pci", "PCI"),
+    ("usb_device", "USB"),
+    ("net", "Network"),
+    ("scsi", "SCSI"),
+    ("mdev", "Mediated"),
+];
+
+/// Attaching needs the chosen domain in the URL, so the form gets its own
+/// handler; it asks before taking a device another domain already uses.
+const NODEDEV_JS: &str = r#"
+document.addEventListener('submit', async (ev) => {
+  const form = ev.target;
+  if (!form.dataset.hostdev) return;
+  ev.preventDefault();
+  const assigned = form.dataset.assigned;
+  if (assigned && !confirm(form.dataset.hostdev + ' is already assigned to ' + assigned
+      + '. Only one running guest can use it at a time. Attach anyway?')) return;
+  const res = await fetch('/api/domains/' + encodeURIComponent(form.domain.value) + '/hostdevs', {
+    method: 'POST',
+    headers: { 'content-type': 'application/json' },
+    body: JSON.stringify({ device: form.dataset.hostdev, scope: form.scope.value }),
+  });
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+});
+"#;
+
+#[derive(Deserialize)]
+pub struct DevicePageQuery {
+    #[serde(default)]
+    cap: Option<String>,
+}
+
+/// One device row – assignments as `(domain name, uuid)`.
+#[derive(Clone, PartialEq)]
+struct DeviceRow {
+    name: String,
+    description: String,
+    driver: String,
+    iommu_group: String,
+    passthrough: bool,
+    pci: bool,
+    assigned: Vec<(String, String)>,
+}
+
+impl From<NodeDeviceInfo> for DeviceRow {
+    fn from(dev: NodeDeviceInfo) -> DeviceRow {
+        let p = dev.parsed;
+        let description = match (p.vendor, p.product, p.interface) {
+            (_, _, Some(iface)) => format!("{} {}", iface, p.mac.unwrap_or_default()),
+            (Some(vendor), Some(product), None) => format!("{} {}", vendor, product),
+            (vendor, product, None) => product.or(vendor).unwrap_or_default(),
+        };
+        DeviceRow {
+            pci: dev.capabilities.iter().any(|c| c == "pci"),
+            name: dev.name,
+            description,
+            driver: p.driver.unwrap_or_default(),
+            iommu_group: p.iommu_group.map(|g| g.to_string()).unwrap_or_default(),
+            passthrough: p.source.is_some(),
+            assigned: dev
+                .assigned_to
+                .into_iter()
+                .map(|a| (a.domain, a.uuid))
+                .collect(),
+        }
+    }
+}
+
+fn assigned_names(row: &DeviceRow) -> String {
+    row.assigned
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[component]
+fn DevicePage(
+    cap: String,
+    devices: Vec<DeviceRow>,
+    domains: Vec<(String, String)>,
+    error: Option<String>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Host devices" }
+        div {
+          for (value , label) in CAPABILITIES {
+            a { href: "/dashboard/devices?cap={value}",
+              button { disabled: value == cap, "{label}" }
+            }
+          }
+        }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Name" }
+              th { "Device" }
+              th { "Driver" }
+              th { "IOMMU group" }
+              th { "Assigned to" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for dev in devices {
+              tr {
+                td {
+                  code { "{dev.name}" }
+                }
+                td { "{dev.description}" }
+                td { "{dev.driver}" }
+                td { "{dev.iommu_group}" }
+                td {
+                  for (domain , uuid) in dev.assigned.iter() {
+                    div {
+                      a { href: "/dashboard/domains/{uuid}", "⚠️ {domain}" }
+                      button {
+                        "data-api": "/api/domains/{uuid}/hostdevs/{dev.name}?scope=both",
+                        "data-method": "DELETE",
+                        "data-confirm": "Remove {dev.name} from {domain}?",
+                        "Remove"
+                      }
+                    }
+                  }
+                }
+                td {
+                  if dev.pci {
+                    if dev.driver == "vfio-pci" {
+                      button {
+                        "data-api": "/api/nodedevs/{dev.name}/reattach",
+                        "data-method": "POST",
+                        "Give back to host"
+                      }
+                    } else {
+                      button {
+                        "data-api": "/api/nodedevs/{dev.name}/detach",
+                        "data-method": "POST",
+                        "data-confirm": "Unbind {dev.name} from {dev.driver}? The host loses access to it.",
+                        "Detach from host"
+                      }
+                    }
+                  }
+                  if dev.passthrough {
+                    form {
+                      "data-hostdev": "{dev.name}",
+                      "data-assigned": assigned_names(&dev),
+                      select { name: "domain",
+                        for (name , uuid) in domains.iter() {
+                          option { value: "{uuid}", "{name}" }
+                        }
+                      }
+                      select { name: "scope",
+                        option { value: "both", "live + config" }
+                        option { value: "live", "live" }
+                        option { value: "config", "config" }
+                      }
+                      button { r#type: "submit", "Attach" }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+      script { dangerous_inner_html: NODEDEV_JS }
+    }
+}
+
+/// Devices with capability `cap` and every domain as `(name, uuid)`.
+type DeviceData = (Vec<DeviceRow>, Vec<(String, String)>);
+
+fn load_devices(uri: &str, cap: &str) -> Result<DeviceData, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let devices = list_nodedevs(&conn, Some(cap))?
+        .into_iter()
+        .map(DeviceRow::from)
+        .collect();
+    let domains = domain_choices(&conn)?;
+    Ok((devices, domains))
+}
+
+// GET /dashboard/devices?cap=pci
+pub async fn device_page(
+    _user: PageUser,
+    State(state): State<AppState>,
+    Query(query): Query<DevicePageQuery>,
+) -> Html<String> {
+    let cap = query.cap.unwrap_or_else(|| "pci".into());
+    let uri = state.config.libvirt_uri.clone();
+    let lookup_cap = cap.clone();
+    let loaded = tokio::task::spawn_blocking(move || load_devices(&uri, &lookup_cap)).await;
+    let (devices, domains, error) = match loaded {
+        Ok(Ok((devices, domains))) => (devices, domains, None),
+        Ok(Err((_, message))) => (Vec::new(), Vec::new(), Some(message)),
+        Err(e) => (Vec::new(), Vec::new(), Some(e.to_string())),
+    };
+    render_page(rsx!(DevicePage {
+        cap,
+        devices,
+        domains,
+        error
+    }))
+}
@@ -0,0 +1,237 @@
+use axum::{extract::State, http::StatusCode, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, domain_choices, render_page};
+use crate::AppState;
+use crate::api::Libvirt;
+use crate::api::nwfilters::{NwFilterBindingInfo, NwFilterInfo, list_bindings, list_nwfilters};
+
+/// The interface form needs the domain and MAC in the URL, and turns the
+/// comma separated IP field into the parameter list.
+const NWFILTER_JS: &str = r#"
+document.addEventListener('submit', async (ev) => {
+  const form = ev.target;
+  if (form.dataset.kind !== 'interface-filter') return;
+  ev.preventDefault();
+  const url = '/api/domains/' + encodeURIComponent(form.domain.value)
+    + '/interfaces/' + encodeURIComponent(form.mac.value.trim()) + '/filter';
+  const ips = form.ip.value.split(',').map((ip) => ip.trim()).filter((ip) => ip);
+  const res = await fetch(url, {
+    method: 'PUT',
+    headers: { 'content-type': 'application/json' },
+    body: JSON.stringify({
+      filter: form.filter.value,
+      parameters: ips.length ? { IP: ips } : {},
+      scope: form.scope.value,
+    }),
+  });
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+});
+"#;
+
+/// One binding row with its parameters joined for display.
+#[derive(Clone, PartialEq)]
+struct BindingRow {
+    portdev: String,
+    owner: String,
+    owner_uuid: String,
+    mac: String,
+    filter: String,
+    parameters: String,
+}
+
+impl From<NwFilterBindingInfo> for BindingRow {
+    fn from(b: NwFilterBindingInfo) -> BindingRow {
+        BindingRow {
+            portdev: b.portdev,
+            owner: b.owner_name.unwrap_or_default(),
+            owner_uuid: b.owner_uuid.unwrap_or_default(),
+            mac: b.mac.unwrap_or_default(),
+            filter: b.filter,
+            parameters: b
+                .parameters
+                .iter()
+                .map(|(name, values)| format!("{}={}", name, values.join(",")))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// One filter row.
+#[derive(Clone, PartialEq)]
+struct FilterRow {
+    name: String,
+    chain: String,
+    priority: String,
+    references: String,
+    rules: usize,
+}
+
+impl From<NwFilterInfo> for FilterRow {
+    fn from(f: NwFilterInfo) -> FilterRow {
+        FilterRow {
+            name: f.name,
+            chain: f.parsed.chain.unwrap_or_default(),
+            priority: f.parsed.priority.map(|p| p.to_string()).unwrap_or_default(),
+            references: f.parsed.references.join(", "),
+            rules: f.parsed.rules,
+        }
+    }
+}
+
+#[component]
+fn NwFilterPage(
+    filters: Vec<FilterRow>,
+    bindings: Vec<BindingRow>,
+    domains: Vec<(String, String)>,
+    error: Option<String>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Network filters" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Name" }
+              th { "Chain" }
+              th { "Priority" }
+              th { "Includes" }
+              th { "Rules" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for filter in filters.iter() {
+              tr {
+                td {
+                  a { href: "/api/nwfilters/{filter.name}", "{filter.name}" }
+                }
+                td { "{filter.chain}" }
+                td { "{filter.priority}" }
+                td { "{filter.references}" }
+                td { "{filter.rules}" }
+                td {
+                  button {
+                    "data-api": "/api/nwfilters/{filter.name}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Undefine filter {filter.name}?",
+                    "Undefine"
+                  }
+                }
+              }
+            }
+          }
+        }
+        h2 { "Define filter" }
+        form { "data-api": "/api/nwfilters",
+          textarea {
+            name: "xml",
+            rows: "8",
+            cols: "80",
+            placeholder: "<filter name='my-filter' chain='root'>…</filter>",
+            required: true,
+          }
+          br {}
+          button { r#type: "submit", "Define" }
+        }
+        h2 { "Filter a guest interface" }
+        form { "data-kind": "interface-filter",
+          select { name: "domain",
+            for (name , uuid) in domains.iter() {
+              option { value: "{uuid}", "{name}" }
+            }
+          }
+          input { name: "mac", placeholder: "MAC, e.g. 52:54:00:12:34:56", required: true }
+          select { name: "filter",
+            for filter in filters.iter() {
+              option { value: "{filter.name}", selected: filter.name == "clean-traffic",
+                "{filter.name}"
+              }
+            }
+          }
+          input { name: "ip", placeholder: "IP parameter(s), comma separated" }
+          select { name: "scope",
+            option { value: "both", "live + config" }
+            option { value: "live", "live" }
+            option { value: "config", "config" }
+          }
+          button { r#type: "submit", "Apply" }
+        }
+        h2 { "Bindings" }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Port" }
+              th { "Domain" }
+              th { "MAC" }
+              th { "Filter" }
+              th { "Parameters" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for binding in bindings {
+              tr {
+                td { "{binding.portdev}" }
+                td {
+                  a { href: "/dashboard/domains/{binding.owner_uuid}", "{binding.owner}" }
+                }
+                td { "{binding.mac}" }
+                td { "{binding.filter}" }
+                td { "{binding.parameters}" }
+                td {
+                  button {
+                    "data-api": "/api/domains/{binding.owner_uuid}/interfaces/{binding.mac}/filter?scope=both",
+                    "data-method": "DELETE",
+                    "data-confirm": "Remove filter {binding.filter} from {binding.owner}?",
+                    "Remove from interface"
+                  }
+                  button {
+                    "data-api": "/api/nwfilter-bindings/{binding.portdev}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Delete binding on {binding.portdev}? The domain definition keeps its filter.",
+                    "Delete binding"
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+      script { dangerous_inner_html: NWFILTER_JS }
+    }
+}
+
+type NwFilterData = (Vec<FilterRow>, Vec<BindingRow>, Vec<(String, String)>);
+
+fn load_nwfilters(uri: &str) -> Result<NwFilterData, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let filters = list_nwfilters(&conn)?
+        .into_iter()
+        .map(FilterRow::from)
+        .collect();
+    let bindings = list_bindings(&conn)?
+        .into_iter()
+        .map(BindingRow::from)
+        .collect();
+    let domains = domain_choices(&conn)?;
+    Ok((filters, bindings, domains))
+}
+
+// GET /dashboard/nwfilters
+pub async fn nwfilter_page(_user: PageUser, State(state): State<AppState>) -> Html<String> {
+    let (filters, bindings, domains, error) = match load_nwfilters(&state.config.libvirt_uri) {
+        Ok((filters, bindings, domains)) => (filters, bindings, domains, None),
+        Err((_, message)) => (Vec::new(), Vec::new(), Vec::new(), Some(message)),
+    };
+    render_page(rsx!(NwFilterPage {
+        filters,
+        bindings,
+        domains,
+        error
+    }))
+}
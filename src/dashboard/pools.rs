@@ -0,0 +1,141 @@
+use axum::{extract::State, http::StatusCode, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, autostart_body, format_bytes, render_page};
+use crate::AppState;
+use crate::api::pools::pool_info;
+use crate::api::{Libvirt, libvirt_error};
+
+/// One pool row with sizes already formatted.
+#[derive(Clone, PartialEq)]
+struct PoolRow {
+    name: String,
+    pool_type: String,
+    state: String,
+    active: bool,
+    autostart: bool,
+    capacity: String,
+    allocation: String,
+    available: String,
+    used_percent: u64,
+}
+
+#[component]
+fn PoolPage(pools: Vec<PoolRow>, error: Option<String>) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Storage pools" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "Name" }
+              th { "Type" }
+              th { "State" }
+              th { "Autostart" }
+              th { "Capacity" }
+              th { "Allocation" }
+              th { "Available" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for pool in pools {
+              tr {
+                td { "{pool.name}" }
+                td { "{pool.pool_type}" }
+                td { "{pool.state}" }
+                td { if pool.autostart { "yes" } else { "no" } }
+                td { "{pool.capacity}" }
+                td {
+                  "{pool.allocation} "
+                  progress { max: "100", value: "{pool.used_percent}" }
+                }
+                td { "{pool.available}" }
+                td {
+                  if pool.active {
+                    button {
+                      "data-api": "/api/pools/{pool.name}/refresh",
+                      "data-method": "POST",
+                      "Refresh"
+                    }
+                    button {
+                      "data-api": "/api/pools/{pool.name}/stop",
+                      "data-method": "POST",
+                      "Stop"
+                    }
+                  } else {
+                    button {
+                      "data-api": "/api/pools/{pool.name}/start",
+                      "data-method": "POST",
+                      "Start"
+                    }
+                    button {
+                      "data-api": "/api/pools/{pool.name}/build",
+                      "data-method": "POST",
+                      "Build"
+                    }
+                    button {
+                      "data-api": "/api/pools/{pool.name}/delete",
+                      "data-method": "POST",
+                      "data-confirm": "Delete the storage behind pool {pool.name}? This cannot be undone.",
+                      "Delete storage"
+                    }
+                  }
+                  button {
+                    "data-api": "/api/pools/{pool.name}/autostart",
+                    "data-method": "PUT",
+                    "data-body": autostart_body(!pool.autostart),
+                    if pool.autostart { "Disable autostart" } else { "Enable autostart" }
+                  }
+                  button {
+                    "data-api": "/api/pools/{pool.name}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Undefine pool {pool.name}?",
+                    "Undefine"
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+}
+
+fn load_pools(uri: &str) -> Result<Vec<PoolRow>, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let pools = conn.list_all_storage_pools(0).map_err(libvirt_error)?;
+    let mut rows = Vec::new();
+    for pool in pools {
+        let info = pool_info(&pool)?;
+        rows.push(PoolRow {
+            name: info.name,
+            pool_type: info.pool_type,
+            state: info.state.to_string(),
+            active: info.active,
+            autostart: info.autostart,
+            capacity: format_bytes(info.capacity),
+            allocation: format_bytes(info.allocation),
+            available: format_bytes(info.available),
+            used_percent: info
+                .allocation
+                .saturating_mul(100)
+                .checked_div(info.capacity)
+                .unwrap_or(0),
+        });
+    }
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(rows)
+}
+
+// GET /dashboard/pools
+pub async fn pool_page(_user: PageUser, State(state): State<AppState>) -> Html<String> {
+    let (pools, error) = match load_pools(&state.config.libvirt_uri) {
+        Ok(rows) => (rows, None),
+        Err((_, message)) => (Vec::new(), Some(message)),
+    };
+    render_page(rsx!(PoolPage { pools, error }))
+}
@@ -0,0 +1,86 @@
+use axum::{extract::State, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, render_page};
+use crate::AppState;
+use crate::ssh_keys::{self, SshKey};
+
+/// Key type and comment of an `authorized_keys` line – the blob is long
+/// and says little.
+fn key_summary(public_key: &str) -> String {
+    let parts: Vec<&str> = public_key.split_whitespace().collect();
+    match parts.as_slice() {
+        [kind, _, comment @ ..] if !comment.is_empty() => {
+            format!("{} {}", kind, comment.join(" "))
+        }
+        [kind, ..] => kind.to_string(),
+        [] => String::new(),
+    }
+}
+
+#[component]
+fn ProfilePage(username: String, admin: bool, keys: Vec<SshKey>) -> Element {
+    let role = if admin { "administrator" } else { "user" };
+    rsx! {
+      Layout {
+        h1 { "Profile – {username}" }
+        p { "Signed in as {role}." }
+        h2 { "SSH keys" }
+        p { "Stored keys can be selected for cloud-init seeds of new domains." }
+        if keys.is_empty() {
+          p { "No keys yet." }
+        } else {
+          table { style: "background:white;",
+            thead {
+              tr {
+                th { "Name" }
+                th { "Key" }
+                th { "Added" }
+                th { "" }
+              }
+            }
+            tbody {
+              for key in keys.iter() {
+                tr {
+                  td { "{key.name}" }
+                  td { {key_summary(&key.public_key)} }
+                  td { "{key.created_at}" }
+                  td {
+                    button {
+                      "data-api": "/api/ssh-keys/{key.id}",
+                      "data-method": "DELETE",
+                      "data-confirm": "Delete key {key.name}?",
+                      "Delete"
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+        form { "data-api": "/api/ssh-keys",
+          input { name: "name", placeholder: "name", required: true }
+          textarea {
+            name: "public_key",
+            rows: "3",
+            cols: "80",
+            placeholder: "ssh-ed25519 AAAA… user@host",
+            required: true,
+          }
+          button { r#type: "submit", "Add key" }
+        }
+      }
+    }
+}
+
+// GET /dashboard/profile
+pub async fn profile_page(PageUser(user): PageUser, State(state): State<AppState>) -> Html<String> {
+    let keys = ssh_keys::list_for_user(&state.pool, user.id)
+        .await
+        .unwrap_or_default();
+    render_page(rsx!(ProfilePage {
+        username: user.username,
+        admin: user.admin,
+        keys
+    }))
+}
@@ -0,0 +1,150 @@
+use axum::{extract::State, http::StatusCode, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, render_page};
+use crate::AppState;
+use crate::api::Libvirt;
+use crate::api::secrets::{SecretInfo, secret_info};
+
+/// One secret row – metadata only, values are never shown.
+#[derive(Clone, PartialEq)]
+struct SecretRow {
+    uuid: String,
+    usage_type: String,
+    usage_id: String,
+    description: String,
+    ephemeral: bool,
+    private: bool,
+}
+
+impl From<SecretInfo> for SecretRow {
+    fn from(info: SecretInfo) -> SecretRow {
+        SecretRow {
+            uuid: info.uuid,
+            usage_type: info.usage_type.name().to_string(),
+            usage_id: info.usage_id.unwrap_or_default(),
+            description: info.description.unwrap_or_default(),
+            ephemeral: info.ephemeral,
+            private: info.private,
+        }
+    }
+}
+
+#[component]
+fn SecretPage(secrets: Vec<SecretRow>, admin: bool, error: Option<String>) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Secrets" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        table { style: "width:100%;background:white;",
+          thead {
+            tr {
+              th { "UUID" }
+              th { "Usage" }
+              th { "Usage ID" }
+              th { "Description" }
+              th { "Ephemeral" }
+              th { "Private" }
+              th { "Actions" }
+            }
+          }
+          tbody {
+            for secret in secrets {
+              tr {
+                td {
+                  code { "{secret.uuid}" }
+                }
+                td { "{secret.usage_type}" }
+                td { "{secret.usage_id}" }
+                td { "{secret.description}" }
+                td { if secret.ephemeral { "yes" } else { "no" } }
+                td { if secret.private { "yes" } else { "no" } }
+                td {
+                  if admin {
+                    form {
+                      "data-api": "/api/secrets/{secret.uuid}/value",
+                      "data-method": "PUT",
+                      input {
+                        name: "value",
+                        r#type: "password",
+                        placeholder: "new value",
+                        required: true,
+                      }
+                      label {
+                        input { name: "base64", r#type: "checkbox" }
+                        "base64"
+                      }
+                      button { r#type: "submit", "Set value" }
+                    }
+                  }
+                  button {
+                    "data-api": "/api/secrets/{secret.uuid}",
+                    "data-method": "DELETE",
+                    "data-confirm": "Undefine secret {secret.uuid}?",
+                    "Undefine"
+                  }
+                }
+              }
+            }
+          }
+        }
+        h2 { "New secret" }
+        form { "data-api": "/api/secrets",
+          select { name: "usage_type",
+            option { value: "ceph", "Ceph" }
+            option { value: "iscsi", "iSCSI" }
+            option { value: "volume", "Volume" }
+            option { value: "tls", "TLS" }
+            option { value: "vtpm", "vTPM" }
+            option { value: "none", "None" }
+          }
+          input {
+            name: "usage_id",
+            placeholder: "usage ID (name, target or volume path)",
+            size: "30",
+          }
+          input { name: "description", placeholder: "description" }
+          label {
+            input { name: "ephemeral", r#type: "checkbox" }
+            "ephemeral"
+          }
+          label {
+            input { name: "private", r#type: "checkbox", checked: true }
+            "private"
+          }
+          button { r#type: "submit", "Define" }
+        }
+        if !admin {
+          p { "Only administrators may set secret values." }
+        }
+      }
+    }
+}
+
+fn load_secrets(uri: &str) -> Result<Vec<SecretRow>, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let secrets = conn
+        .list_all_secrets(0)
+        .map_err(crate::api::libvirt_error)?;
+    let mut rows = secrets
+        .iter()
+        .map(|s| secret_info(s).map(SecretRow::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.sort_by(|a, b| (&a.usage_type, &a.usage_id).cmp(&(&b.usage_type, &b.usage_id)));
+    Ok(rows)
+}
+
+// GET /dashboard/secrets
+pub async fn secret_page(PageUser(user): PageUser, State(state): State<AppState>) -> Html<String> {
+    let (secrets, error) = match load_secrets(&state.config.libvirt_uri) {
+        Ok(rows) => (rows, None),
+        Err((_, message)) => (Vec::new(), Some(message)),
+    };
+    render_page(rsx!(SecretPage {
+        secrets,
+        admin: user.admin,
+        error
+    }))
+}
@@ -0,0 +1,153 @@
+use axum::{extract::State, response::Html};
+use dioxus::prelude::*;
+
+use super::cloudinit::{SEED_JS, SeedFields};
+use super::{Layout, PageUser, render_page};
+use crate::AppState;
+use crate::api::domains::lookup;
+use crate::api::pools::pool_info;
+use crate::api::{Libvirt, domain_state_name, libvirt_error};
+use crate::ssh_keys::{self, SshKey};
+use crate::templates::{self, Template};
+
+/// A template with the current state of its domain.
+#[derive(Clone, PartialEq)]
+struct TemplateRow {
+    template: Template,
+    /// Current name; the domain may have been renamed since it was marked
+    name: String,
+    /// `None` once the domain is no longer defined
+    state: Option<String>,
+}
+
+#[component]
+fn TemplatePage(
+    rows: Vec<TemplateRow>,
+    pools: Vec<String>,
+    keys: Vec<SshKey>,
+    error: Option<String>,
+) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Templates" }
+        p {
+          "Mark a shut-off domain as template on its page, then clone it here. "
+          "Clones get a new name, UUID and MAC addresses; linked clones share the "
+          "template's disks through qcow2 overlays. While any linked clone exists "
+          "the template can neither be started nor unmarked, and marking a domain "
+          "turns its autostart off."
+        }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        if rows.is_empty() {
+          p { "No templates yet." }
+        }
+        for row in rows {
+          h2 {
+            if row.state.is_some() {
+              a { href: "/dashboard/domains/{row.template.domain_uuid}", "{row.name}" }
+            } else {
+              "{row.name}"
+            }
+          }
+          if !row.template.description.is_empty() {
+            p { "{row.template.description}" }
+          }
+          p {
+            "Marked by {row.template.created_by} at {row.template.created_at}"
+          }
+          if let Some(state) = &row.state {
+            p { "State: {state}" }
+            form {
+              "data-seed": "/api/templates/{row.template.domain_uuid}/clone",
+              "data-seed-key": "cloud_init",
+              input { name: "name", placeholder: "name of the clone", required: true }
+              label {
+                input { name: "linked", r#type: "checkbox" }
+                " linked"
+              }
+              select { name: "pool",
+                option { value: "", "pool of each disk" }
+                for pool in pools.iter() {
+                  option { value: "{pool}", "{pool}" }
+                }
+              }
+              input { name: "hostname", placeholder: "cloud-init hostname (optional)" }
+              label {
+                input { name: "start", r#type: "checkbox" }
+                " start"
+              }
+              details {
+                summary { "More cloud-init settings" }
+                SeedFields { keys: keys.clone(), hostname: false }
+              }
+              button { r#type: "submit", "Clone" }
+            }
+          } else {
+            p { style: "color:red;", "The domain is no longer defined." }
+          }
+          button {
+            "data-api": "/api/domains/{row.template.domain_uuid}/template",
+            "data-method": "DELETE",
+            "data-confirm": "Turn {row.name} back into a plain domain?",
+            "Unmark"
+          }
+        }
+      }
+      script { dangerous_inner_html: SEED_JS }
+    }
+}
+
+// GET /dashboard/templates
+pub async fn template_page(
+    PageUser(user): PageUser,
+    State(state): State<AppState>,
+) -> Html<String> {
+    let all = templates::list(&state.pool).await.unwrap_or_default();
+    let keys = ssh_keys::list_for_user(&state.pool, user.id)
+        .await
+        .unwrap_or_default();
+    let loaded = Libvirt::open(&state.config.libvirt_uri).and_then(|conn| {
+        let rows: Vec<TemplateRow> = all
+            .into_iter()
+            .map(|template| {
+                let found = lookup(&conn, &template.domain_uuid).and_then(|dom| {
+                    let (dom_state, _) = dom.get_state().map_err(libvirt_error)?;
+                    Ok((
+                        dom.get_name().map_err(libvirt_error)?,
+                        domain_state_name(dom_state).to_lowercase(),
+                    ))
+                });
+                let (name, state) = match found {
+                    Ok((name, state)) => (name, Some(state)),
+                    Err(_) => (template.domain_name.clone(), None),
+                };
+                TemplateRow {
+                    template,
+                    name,
+                    state,
+                }
+            })
+            .collect();
+        let mut pools = Vec::new();
+        for pool in conn.list_all_storage_pools(0).map_err(libvirt_error)? {
+            let info = pool_info(&pool)?;
+            if info.active {
+                pools.push(info.name);
+            }
+        }
+        pools.sort();
+        Ok((rows, pools))
+    });
+    let (rows, pools, error) = match loaded {
+        Ok((rows, pools)) => (rows, pools, None),
+        Err((_, message)) => (Vec::new(), Vec::new(), Some(message)),
+    };
+    render_page(rsx!(TemplatePage {
+        rows,
+        pools,
+        keys,
+        error
+    }))
+}
@@ -0,0 +1,213 @@
+use axum::{extract::State, http::StatusCode, response::Html};
+use dioxus::prelude::*;
+
+use super::{Layout, PageUser, format_bytes, render_page};
+use crate::AppState;
+use crate::api::pools::pool_info;
+use crate::api::volumes::volume_info;
+use crate::api::{Libvirt, libvirt_error};
+
+/// Create, upload, resize and clone need user input, so they get their own
+/// handlers next to the generic `data-api` buttons.
+const VOLUME_JS: &str = r#"
+async function volumeRequest(url, opts) {
+  const res = await fetch(url, opts);
+  if (res.ok) { location.reload(); } else { alert(await res.text()); }
+  return res.ok;
+}
+function json(body) {
+  return { headers: { 'content-type': 'application/json' }, body: JSON.stringify(body) };
+}
+document.addEventListener('submit', async (ev) => {
+  const form = ev.target;
+  if (!form.dataset.pool) return;
+  ev.preventDefault();
+  const base = '/api/pools/' + encodeURIComponent(form.dataset.pool) + '/volumes';
+  if (form.dataset.kind === 'create') {
+    const capacity = Math.round(parseFloat(form.size.value) * 1024 * 1024 * 1024);
+    await volumeRequest(base, { method: 'POST',
+      ...json({ name: form.name.value, format: form.format.value, capacity }) });
+  } else if (form.dataset.kind === 'upload') {
+    const file = form.file.files[0];
+    if (!file) return;
+    const name = form.name.value || file.name;
+    // A raw volume of exactly the file's size receives the upload
+    const res = await fetch(base, { method: 'POST',
+      ...json({ name, format: 'raw', capacity: file.size }) });
+    if (!res.ok) { alert(await res.text()); return; }
+    form.querySelector('button').disabled = true;
+    await volumeRequest(base + '/' + encodeURIComponent(name) + '/content',
+      { method: 'PUT', body: file });
+  }
+});
+document.addEventListener('click', async (ev) => {
+  const btn = ev.target.closest('[data-volume]');
+  if (!btn) return;
+  const url = btn.dataset.volume;
+  if (btn.dataset.action === 'resize') {
+    const size = prompt('New size in GiB');
+    if (!size) return;
+    const capacity = Math.round(parseFloat(size) * 1024 * 1024 * 1024);
+    const shrink = capacity < parseInt(btn.dataset.capacity, 10);
+    if (shrink && !confirm('Shrinking discards data past the new end. Continue?')) return;
+    await volumeRequest(url + '/resize', { method: 'POST', ...json({ capacity, shrink }) });
+  } else if (btn.dataset.action === 'clone') {
+    const name = prompt('Name of the copy');
+    if (!name) return;
+    await volumeRequest(url + '/clone', { method: 'POST', ...json({ name }) });
+  }
+});
+"#;
+
+/// One volume row with sizes already formatted.
+#[derive(Clone, PartialEq)]
+struct VolumeRow {
+    name: String,
+    format: String,
+    path: String,
+    capacity_bytes: u64,
+    capacity: String,
+    allocation: String,
+}
+
+/// Volumes of one pool; inactive pools have no volume list.
+#[derive(Clone, PartialEq)]
+struct PoolVolumes {
+    pool: String,
+    active: bool,
+    volumes: Vec<VolumeRow>,
+}
+
+#[component]
+fn VolumePage(pools: Vec<PoolVolumes>, error: Option<String>) -> Element {
+    rsx! {
+      Layout {
+        h1 { "Storage volumes" }
+        if let Some(error) = error {
+          p { style: "color:red;", "{error}" }
+        }
+        for pool in pools {
+          h2 { "{pool.pool}" }
+          if !pool.active {
+            p { "Pool is not active – start it on the Pool page to see its volumes." }
+          } else {
+            table { style: "width:100%;background:white;",
+              thead {
+                tr {
+                  th { "Name" }
+                  th { "Format" }
+                  th { "Capacity" }
+                  th { "Allocation" }
+                  th { "Path" }
+                  th { "Actions" }
+                }
+              }
+              tbody {
+                for vol in pool.volumes.iter() {
+                  tr {
+                    td { "{vol.name}" }
+                    td { "{vol.format}" }
+                    td { "{vol.capacity}" }
+                    td { "{vol.allocation}" }
+                    td { "{vol.path}" }
+                    td {
+                      a { href: "/api/pools/{pool.pool}/volumes/{vol.name}/content",
+                        button { "Download" }
+                      }
+                      button {
+                        "data-volume": "/api/pools/{pool.pool}/volumes/{vol.name}",
+                        "data-action": "resize",
+                        "data-capacity": "{vol.capacity_bytes}",
+                        "Resize"
+                      }
+                      button {
+                        "data-volume": "/api/pools/{pool.pool}/volumes/{vol.name}",
+                        "data-action": "clone",
+                        "Clone"
+                      }
+                      button {
+                        "data-api": "/api/pools/{pool.pool}/volumes/{vol.name}/wipe",
+                        "data-method": "POST",
+                        "data-confirm": "Overwrite {vol.name} with zeroes?",
+                        "Wipe"
+                      }
+                      button {
+                        "data-api": "/api/pools/{pool.pool}/volumes/{vol.name}",
+                        "data-method": "DELETE",
+                        "data-confirm": "Delete volume {vol.name}? This cannot be undone.",
+                        "Delete"
+                      }
+                    }
+                  }
+                }
+              }
+            }
+            form { "data-pool": "{pool.pool}", "data-kind": "create",
+              "New volume: "
+              input { name: "name", placeholder: "name", required: true }
+              select { name: "format",
+                option { value: "qcow2", "qcow2" }
+                option { value: "raw", "raw" }
+              }
+              input {
+                name: "size",
+                r#type: "number",
+                min: "0.1",
+                step: "0.1",
+                placeholder: "GiB",
+                required: true,
+              }
+              button { r#type: "submit", "Create" }
+            }
+            form { "data-pool": "{pool.pool}", "data-kind": "upload",
+              "Upload image: "
+              input { name: "file", r#type: "file", required: true }
+              input { name: "name", placeholder: "volume name (default: file name)" }
+              button { r#type: "submit", "Upload" }
+            }
+          }
+        }
+      }
+      script { dangerous_inner_html: VOLUME_JS }
+    }
+}
+
+fn load_volumes(uri: &str) -> Result<Vec<PoolVolumes>, (StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    let pools = conn.list_all_storage_pools(0).map_err(libvirt_error)?;
+    let mut out = Vec::new();
+    for pool in pools {
+        let info = pool_info(&pool)?;
+        let mut volumes = Vec::new();
+        if info.active {
+            for vol in pool.list_all_volumes(0).map_err(libvirt_error)? {
+                let vol = volume_info(&vol)?;
+                volumes.push(VolumeRow {
+                    name: vol.name,
+                    format: vol.format.unwrap_or_default(),
+                    path: vol.path,
+                    capacity_bytes: vol.capacity,
+                    capacity: format_bytes(vol.capacity),
+                    allocation: format_bytes(vol.allocation),
+                });
+            }
+            volumes.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        out.push(PoolVolumes {
+            pool: info.name,
+            active: info.active,
+            volumes,
+        });
+    }
+    out.sort_by(|a, b| a.pool.cmp(&b.pool));
+    Ok(out)
+}
+
+// GET /dashboard/volumes
+pub async fn volume_page(_user: PageUser, State(state): State<AppState>) -> Html<String> {
+    let (pools, error) = match load_volumes(&state.config.libvirt_uri) {
+        Ok(pools) => (pools, None),
+        Err((_, message)) => (Vec::new(), Some(message)),
+    };
+    render_page(rsx!(VolumePage { pools, error }))
+}
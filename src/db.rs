@@ -0,0 +1,85 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// db.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::{SqlitePool, sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions};
+
+/// Schema migrations embedded from `./migrations` at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Open (creating it if needed) the SQLite database at `path`.
+pub async fn connect(path: &str) -> anyhow::Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+/// Initialise the SQLite database (pending migrations + default admin)
+pub async fn init_db(pool: &SqlitePool) -> anyhow::Result<()> {
+    MIGRATOR.run(pool).await?;
+
+    // Add a default user (`admin` / `password`) if the table is empty
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        .fetch_one(pool)
+        .await?;
+    if count.0 == 0 {
+        crate::users::add(pool, "admin", "password").await?;
+        crate::users::set_admin(pool, "admin", true).await?;
+        println!("🔑 Created default user `admin` with password `password`");
+    }
+    Ok(())
+}
+
+/// One row of `migrate status`.
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
+
+/// List every embedded migration and whether it has been applied.
+pub async fn migration_status(pool: &SqlitePool) -> anyhow::Result<Vec<MigrationStatus>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied = conn.list_applied_migrations().await?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.iter().any(|a| a.version == m.version),
+        })
+        .collect())
+}
+
+/// Revert applied migrations down to (but not including) `target`.  Without a
+/// target only the most recent migration is reverted.
+pub async fn migrate_down(pool: &SqlitePool, target: Option<i64>) -> anyhow::Result<()> {
+    let target = match target {
+        Some(target) => target,
+        None => {
+            let status = migration_status(pool).await?;
+            let applied: Vec<i64> = status
+                .iter()
+                .filter(|s| s.applied)
+                .map(|s| s.version)
+                .collect();
+            // Revert the newest applied migration only
+            match applied.len() {
+                0 => return Ok(()),
+                1 => 0,
+                n => applied[n - 2],
+            }
+        }
+    };
+    MIGRATOR.undo(pool, target).await?;
+    Ok(())
+}
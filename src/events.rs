@@ -0,0 +1,224 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// events.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use std::ffi::CStr;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use virt::{error::Error, event, sys};
+
+use crate::api::{Libvirt, domain_state_name, libvirt_error};
+use crate::scheduler::now;
+
+/// Events a slow subscriber may fall behind before it has to resync.
+const CAPACITY: usize = 256;
+
+/// Wait before reconnecting after the event connection was lost.
+const RETRY: Duration = Duration::from_secs(5);
+
+/// Event names by `virDomainEventType`, each with its detail names.
+const EVENT_NAMES: [(&str, &[&str]); 9] = [
+    ("defined", &["added", "updated", "renamed", "from_snapshot"]),
+    ("undefined", &["removed", "renamed"]),
+    (
+        "started",
+        &["booted", "migrated", "restored", "from_snapshot", "wakeup"],
+    ),
+    (
+        "suspended",
+        &[
+            "paused",
+            "migrated",
+            "ioerror",
+            "watchdog",
+            "restored",
+            "from_snapshot",
+            "api_error",
+            "postcopy",
+            "postcopy_failed",
+        ],
+    ),
+    (
+        "resumed",
+        &[
+            "unpaused",
+            "migrated",
+            "from_snapshot",
+            "postcopy",
+            "postcopy_failed",
+        ],
+    ),
+    (
+        "stopped",
+        &[
+            "shutdown",
+            "destroyed",
+            "crashed",
+            "migrated",
+            "saved",
+            "failed",
+            "from_snapshot",
+        ],
+    ),
+    ("shutdown", &["finished", "guest", "host"]),
+    ("pmsuspended", &["memory", "disk"]),
+    ("crashed", &["panicked", "crashloaded"]),
+];
+
+/// A domain lifecycle change as pushed to browsers.
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainEvent {
+    pub uuid: String,
+    pub name: String,
+    /// `started`, `stopped`, `defined`, … – see `EVENT_NAMES`
+    pub event: &'static str,
+    pub detail: &'static str,
+    /// State the domain is in after the event, when the event implies one
+    pub state: Option<&'static str>,
+    /// Seconds since the unix epoch
+    pub time: i64,
+}
+
+/// Fan-out of libvirt domain events to every subscriber.
+#[derive(Clone)]
+pub struct Events(broadcast::Sender<DomainEvent>);
+
+impl Default for Events {
+    fn default() -> Events {
+        Events(broadcast::channel(CAPACITY).0)
+    }
+}
+
+impl Events {
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.0.subscribe()
+    }
+}
+
+type LifecycleCallback = unsafe extern "C" fn(
+    sys::virConnectPtr,
+    sys::virDomainPtr,
+    libc::c_int,
+    libc::c_int,
+    *mut libc::c_void,
+) -> libc::c_int;
+type GenericCallback =
+    unsafe extern "C" fn(sys::virConnectPtr, sys::virDomainPtr, *mut libc::c_void);
+
+/// `virConnectDomainEventCallback` for `VIR_DOMAIN_EVENT_ID_LIFECYCLE`.
+unsafe extern "C" fn lifecycle(
+    _conn: sys::virConnectPtr,
+    dom: sys::virDomainPtr,
+    event: libc::c_int,
+    detail: libc::c_int,
+    opaque: *mut libc::c_void,
+) -> libc::c_int {
+    // SAFETY: `opaque` is the sender registered in `watch`; libvirt keeps it
+    // alive until `free_sender` runs.
+    let sender = unsafe { &*(opaque as *const broadcast::Sender<DomainEvent>) };
+    let mut uuid = [0 as libc::c_char; sys::VIR_UUID_STRING_BUFLEN as usize];
+    let name = unsafe {
+        sys::virDomainGetUUIDString(dom, uuid.as_mut_ptr());
+        let name = sys::virDomainGetName(dom);
+        if name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        }
+    };
+    let (event_name, details) = EVENT_NAMES
+        .get(event as usize)
+        .copied()
+        .unwrap_or(("unknown", &[]));
+    let state = match event as u32 {
+        sys::VIR_DOMAIN_EVENT_STARTED | sys::VIR_DOMAIN_EVENT_RESUMED => Some(1),
+        sys::VIR_DOMAIN_EVENT_SUSPENDED => Some(3),
+        sys::VIR_DOMAIN_EVENT_STOPPED => Some(5),
+        sys::VIR_DOMAIN_EVENT_CRASHED => Some(6),
+        sys::VIR_DOMAIN_EVENT_PMSUSPENDED => Some(7),
+        _ => None,
+    };
+    // Nobody listening is not an error
+    let _ = sender.send(DomainEvent {
+        uuid: unsafe { CStr::from_ptr(uuid.as_ptr()) }
+            .to_string_lossy()
+            .into_owned(),
+        name,
+        event: event_name,
+        detail: details.get(detail as usize).copied().unwrap_or("unknown"),
+        state: state.map(domain_state_name),
+        time: now(),
+    });
+    0
+}
+
+unsafe extern "C" fn free_sender(opaque: *mut libc::c_void) {
+    drop(unsafe { Box::from_raw(opaque as *mut broadcast::Sender<DomainEvent>) });
+}
+
+/// Forward lifecycle events of `uri` until the connection is lost.
+fn watch(
+    uri: &str,
+    sender: &broadcast::Sender<DomainEvent>,
+) -> Result<(), (axum::http::StatusCode, String)> {
+    let conn = Libvirt::open_long_lived(uri)?;
+    // Keepalives notice a dead daemon and wake the event loop regularly;
+    // local drivers without keepalive support just return an error here
+    unsafe { sys::virConnectSetKeepAlive(conn.as_ptr(), 5, 3) };
+
+    let opaque = Box::into_raw(Box::new(sender.clone())) as *mut libc::c_void;
+    // SAFETY: libvirt expects the specific callback cast to the generic type
+    // (VIR_DOMAIN_EVENT_CALLBACK in C); `free_sender` releases `opaque`.
+    let id = unsafe {
+        sys::virConnectDomainEventRegisterAny(
+            conn.as_ptr(),
+            std::ptr::null_mut(),
+            sys::VIR_DOMAIN_EVENT_ID_LIFECYCLE as libc::c_int,
+            Some(std::mem::transmute::<LifecycleCallback, GenericCallback>(
+                lifecycle,
+            )),
+            opaque,
+            Some(free_sender),
+        )
+    };
+    if id == -1 {
+        unsafe { free_sender(opaque) };
+        return Err(libvirt_error(Error::last_error()));
+    }
+
+    println!("📡 Watching domain events on {}", uri);
+    while conn.is_alive().unwrap_or(false) {
+        if let Err(e) = event::event_run_default_impl() {
+            eprintln!("⚠️  libvirt event loop failed: {}", e);
+            break;
+        }
+    }
+    unsafe { sys::virConnectDomainEventDeregisterAny(conn.as_ptr(), id) };
+    Ok(())
+}
+
+/// Register libvirt's event loop and forward domain lifecycle events from
+/// the local hypervisor to `events`, reconnecting when the daemon restarts.
+pub fn start(uri: String, events: Events) {
+    // Must happen before the connection that events are received on opens
+    if let Err(e) = event::event_register_default_impl() {
+        eprintln!("⚠️  Could not start the libvirt event loop: {}", e);
+        return;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("libvirt-events".into())
+        .spawn(move || {
+            loop {
+                match watch(&uri, &events.0) {
+                    Ok(()) => eprintln!("⚠️  Lost the libvirt event connection, reconnecting"),
+                    Err((_, message)) => {
+                        eprintln!("⚠️  Could not watch domain events: {}", message)
+                    }
+                }
+                std::thread::sleep(RETRY);
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("⚠️  Could not start the libvirt event thread: {}", e);
+    }
+}
@@ -0,0 +1,41 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// hosts.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// A registered hypervisor.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Host {
+    pub id: i64,
+    pub name: String,
+    pub uri: String,
+    pub created_at: String,
+}
+
+/// Register a hypervisor under a unique name.
+pub async fn add(pool: &SqlitePool, name: &str, uri: &str) -> anyhow::Result<()> {
+    sqlx::query("INSERT INTO hosts (name, uri) VALUES (?, ?)")
+        .bind(name)
+        .bind(uri)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// All registered hypervisors ordered by name.
+pub async fn list(pool: &SqlitePool) -> anyhow::Result<Vec<Host>> {
+    let hosts = sqlx::query_as("SELECT id, name, uri, created_at FROM hosts ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+    Ok(hosts)
+}
+
+/// The hypervisor registered under `name`.
+pub async fn find(pool: &SqlitePool, name: &str) -> anyhow::Result<Option<Host>> {
+    let host = sqlx::query_as("SELECT id, name, uri, created_at FROM hosts WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+    Ok(host)
+}
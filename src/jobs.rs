@@ -0,0 +1,214 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// jobs.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const COLUMNS: &str = "id, name, owner, schedule, method, path, body, enabled, next_run, \
+    last_run, last_status, last_outcome, created_at";
+
+/// A scheduled API call.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Job {
+    pub id: i64,
+    pub name: String,
+    /// User the call is made as
+    pub owner: String,
+    pub schedule: String,
+    pub method: String,
+    pub path: String,
+    pub body: Option<String>,
+    pub enabled: bool,
+    /// Next run as seconds since the unix epoch
+    pub next_run: Option<i64>,
+    pub last_run: Option<String>,
+    pub last_status: Option<i64>,
+    pub last_outcome: Option<String>,
+    pub created_at: String,
+}
+
+/// One execution of a job.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct JobRun {
+    pub id: i64,
+    pub job_id: i64,
+    pub job_name: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: Option<i64>,
+    pub outcome: Option<String>,
+}
+
+/// The user editable part of a job.
+pub struct JobSpec<'a> {
+    pub name: &'a str,
+    pub schedule: &'a str,
+    pub method: &'a str,
+    pub path: &'a str,
+    pub body: Option<&'a str>,
+}
+
+pub async fn add(
+    pool: &SqlitePool,
+    owner: &str,
+    spec: &JobSpec<'_>,
+    next_run: i64,
+) -> anyhow::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO jobs (name, owner, schedule, method, path, body, next_run) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(spec.name)
+    .bind(owner)
+    .bind(spec.schedule)
+    .bind(spec.method)
+    .bind(spec.path)
+    .bind(spec.body)
+    .bind(next_run)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn update(
+    pool: &SqlitePool,
+    id: i64,
+    spec: &JobSpec<'_>,
+    next_run: i64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE jobs SET name = ?, schedule = ?, method = ?, path = ?, body = ?, next_run = ? \
+         WHERE id = ?",
+    )
+    .bind(spec.name)
+    .bind(spec.schedule)
+    .bind(spec.method)
+    .bind(spec.path)
+    .bind(spec.body)
+    .bind(next_run)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn set_enabled(pool: &SqlitePool, id: i64, enabled: bool) -> anyhow::Result<()> {
+    sqlx::query("UPDATE jobs SET enabled = ? WHERE id = ?")
+        .bind(enabled)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `None` parks the job until its schedule is fixed.
+pub async fn set_next_run(pool: &SqlitePool, id: i64, next_run: Option<i64>) -> anyhow::Result<()> {
+    sqlx::query("UPDATE jobs SET next_run = ? WHERE id = ?")
+        .bind(next_run)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> anyhow::Result<Option<Job>> {
+    let job = sqlx::query_as(&format!("SELECT {} FROM jobs WHERE id = ?", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(job)
+}
+
+/// All jobs ordered by name.
+pub async fn list(pool: &SqlitePool) -> anyhow::Result<Vec<Job>> {
+    let jobs = sqlx::query_as(&format!("SELECT {} FROM jobs ORDER BY name, id", COLUMNS))
+        .fetch_all(pool)
+        .await?;
+    Ok(jobs)
+}
+
+/// Remove a job together with its run history.
+pub async fn delete(pool: &SqlitePool, id: i64) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM job_runs WHERE job_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM jobs WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Enabled jobs whose next run is at or before `now`.
+pub async fn due(pool: &SqlitePool, now: i64) -> anyhow::Result<Vec<Job>> {
+    let jobs = sqlx::query_as(&format!(
+        "SELECT {} FROM jobs WHERE enabled = 1 AND next_run <= ? ORDER BY next_run",
+        COLUMNS
+    ))
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+    Ok(jobs)
+}
+
+/// Open a run history entry; returns its id.
+pub async fn start_run(pool: &SqlitePool, job_id: i64) -> anyhow::Result<i64> {
+    let result = sqlx::query("INSERT INTO job_runs (job_id) VALUES (?)")
+        .bind(job_id)
+        .execute(pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Close a run and copy its outcome onto the job.
+pub async fn finish_run(
+    pool: &SqlitePool,
+    run_id: i64,
+    job_id: i64,
+    status: u16,
+    outcome: &str,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE job_runs SET finished_at = CURRENT_TIMESTAMP, status = ?, outcome = ? \
+         WHERE id = ?",
+    )
+    .bind(status)
+    .bind(outcome)
+    .bind(run_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE jobs SET last_run = CURRENT_TIMESTAMP, last_status = ?, last_outcome = ? \
+         WHERE id = ?",
+    )
+    .bind(status)
+    .bind(outcome)
+    .bind(job_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Run history of one job (or of all jobs with `None`), newest first.
+pub async fn runs(
+    pool: &SqlitePool,
+    job_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<JobRun>> {
+    let runs = sqlx::query_as(
+        "SELECT r.id, r.job_id, j.name AS job_name, r.started_at, r.finished_at, r.status, \
+         r.outcome FROM job_runs r JOIN jobs j ON j.id = r.job_id \
+         WHERE ? IS NULL OR r.job_id = ? ORDER BY r.id DESC LIMIT ?",
+    )
+    .bind(job_id)
+    .bind(job_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(runs)
+}
@@ -1,15 +1,34 @@
 mod api;
+mod audit;
+mod auth;
+mod backups;
+mod cli;
+mod cloudinit;
+mod config;
+mod console;
 mod dashboard;
+mod db;
+mod events;
+mod hosts;
+mod jobs;
+mod migrations;
+mod sampler;
+mod scheduler;
+mod ssh_keys;
+mod systemd;
+mod telemetry;
+mod templates;
+mod tls;
+mod users;
 
 mod wizard;
 
-use argon2::{self, Config};
 use axum::{
     Router,
-    extract::{Form, State},
+    extract::{Form, FromRef, State},
     http::StatusCode,
     response::{Html, IntoResponse},
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use axum_session::{Session, SessionConfig, SessionLayer, SessionStore};
 use axum_session_sqlx::SessionSqlitePool;
@@ -17,16 +36,28 @@ use axum_session_sqlx::SessionSqlitePool;
 use rand::RngCore;
 use rand::rngs::OsRng;
 
-use serde::{Deserialize, Serialize};
-use sqlx::{SqlitePool, sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions};
+use clap::Parser;
+use serde::Deserialize;
+use sqlx::SqlitePool;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
-/// User record – only the fields we need for authentication
-#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
-struct User {
-    id: i64,
-    username: String,
-    password_hash: String, // Argon2 hash
+/// Shared state handed to every handler
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub config: Arc<config::Config>,
+    pub metrics: sampler::Metrics,
+    pub events: events::Events,
+    pub tickets: console::Tickets,
+    pub serial: console::SerialConsoles,
+}
+
+impl FromRef<AppState> for SqlitePool {
+    fn from_ref(state: &AppState) -> SqlitePool {
+        state.pool.clone()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,100 +68,484 @@ struct LoginForm {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // 1️⃣  Connect to (and initialise) the database
-    let db_path = "data.db";
-    let options = SqliteConnectOptions::new()
-        .filename(db_path)
-        .create_if_missing(true);
-    SqlitePool::connect_with(options).await?;
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&format!("sqlite://{}", db_path))
-        .await?;
-    init_db(&pool).await?;
-
-    // Create table if not exists
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS session (
-            session_id TEXT PRIMARY KEY,
-            data BLOB NOT NULL,
-            expires_at INTEGER NOT NULL
-        );
-        "#,
-    )
-    .execute(&pool)
-    .await?;
+    let cli = cli::Cli::parse();
+    let config = config::Config::load(cli.config.as_deref())?;
+
+    // 1️⃣  Connect to the database
+    let pool = db::connect(&config.database).await?;
+
+    match cli.command.unwrap_or(cli::Command::Serve) {
+        cli::Command::Serve => {}
+        // `migrate` decides itself which migrations to apply
+        command @ cli::Command::Migrate(_) => return cli::run(command, &pool).await,
+        command => {
+            db::init_db(&pool).await?;
+            return cli::run(command, &pool).await;
+        }
+    }
 
+    // Apply pending migrations and seed the default admin
+    db::init_db(&pool).await?;
+    serve(config, pool).await
+}
+
+/// Run the web server until it is stopped.
+async fn serve(config: config::Config, pool: SqlitePool) -> anyhow::Result<()> {
     // 2️⃣  Generate a random 32‑byte key for axum_session
     let mut key_bytes = [0u8; 32];
     OsRng.fill_bytes(&mut key_bytes);
 
-    let session_config = SessionConfig::default().with_table_name("sessions_table");
+    // Session cookies must only travel over HTTPS once TLS is enabled
+    let session_config = SessionConfig::default()
+        .with_table_name("sessions_table")
+        .with_secure(config.tls.is_some());
 
     let session_store =
         SessionStore::<SessionSqlitePool>::new(Some(pool.clone().into()), session_config)
             .await
             .unwrap();
 
+    let state = AppState {
+        pool: pool.clone(),
+        config: Arc::new(config.clone()),
+        metrics: sampler::Metrics::default(),
+        events: events::Events::default(),
+        tickets: console::Tickets::default(),
+        serial: console::SerialConsoles::default(),
+    };
+    // libvirt's event loop has to be registered before other connections open
+    events::start(config.libvirt_uri.clone(), state.events.clone());
+    // Backup jobs keep running in the hypervisor across restarts
+    api::backups::resume(&state).await;
+    api::migrations::interrupt_running(&state).await;
+    sampler::start(state.clone());
+
     // 3️⃣  Build the router
     let app = Router::new()
         .route("/", get(root))
         .route("/login", get(login_page).post(login_action))
         .route("/dashboard", get(dashboard::dashboard_page))
+        .route("/dashboard/host", get(dashboard::host::host_page))
+        .route("/dashboard/devices", get(dashboard::nodedevs::device_page))
+        .route(
+            "/dashboard/networks",
+            get(dashboard::networks::network_page),
+        )
+        .route("/dashboard/secrets", get(dashboard::secrets::secret_page))
+        .route(
+            "/dashboard/nwfilters",
+            get(dashboard::nwfilters::nwfilter_page),
+        )
+        .route("/dashboard/pools", get(dashboard::pools::pool_page))
+        .route("/dashboard/volumes", get(dashboard::volumes::volume_page))
+        .route("/dashboard/jobs", get(dashboard::jobs::job_page))
+        .route("/dashboard/profile", get(dashboard::profile::profile_page))
+        .route(
+            "/dashboard/templates",
+            get(dashboard::templates::template_page),
+        )
+        .route(
+            "/dashboard/domains/{uuid}",
+            get(dashboard::domains::domain_page),
+        )
+        .route(
+            "/dashboard/domains/{uuid}/cloud-init",
+            get(dashboard::cloudinit::cloud_init_page),
+        )
+        .route(
+            "/dashboard/domains/{uuid}/console",
+            get(dashboard::console::console_page),
+        )
+        .route(
+            "/dashboard/domains/{uuid}/migrate",
+            get(dashboard::migrations::migration_page),
+        )
+        .route(
+            "/dashboard/domains/{uuid}/serial",
+            get(dashboard::console::serial_page),
+        )
         .route("/logout", post(logout))
         .route("/api/domains", get(api::get_domains))
+        .route("/api/events", get(api::events::domain_events))
+        .route(
+            "/api/domains/{uuid}/addresses",
+            get(api::domains::get_addresses),
+        )
+        .route(
+            "/api/domains/{uuid}/start",
+            post(api::domains::start_domain),
+        )
+        .route(
+            "/api/domains/{uuid}/shutdown",
+            post(api::domains::shutdown_domain),
+        )
+        .route(
+            "/api/domains/{uuid}/destroy",
+            post(api::domains::destroy_domain),
+        )
+        .route(
+            "/api/domains/{uuid}/reboot",
+            post(api::domains::reboot_domain),
+        )
+        .route(
+            "/api/domains/{uuid}/suspend",
+            post(api::domains::suspend_domain),
+        )
+        .route(
+            "/api/domains/{uuid}/resume",
+            post(api::domains::resume_domain),
+        )
+        .route("/api/domains/{uuid}/metrics", get(api::stats::get_metrics))
+        .route(
+            "/api/domains/{uuid}/console",
+            post(api::console::create_console_ticket),
+        )
+        .route(
+            "/api/domains/{uuid}/serial",
+            post(api::console::create_serial_ticket),
+        )
+        .route("/api/console/ws", get(api::console::console_socket))
+        .route(
+            "/api/domains/{uuid}/snapshots",
+            get(api::snapshots::list_snapshots).post(api::snapshots::create_snapshot),
+        )
+        .route(
+            "/api/domains/{uuid}/snapshots/{name}",
+            delete(api::snapshots::delete_snapshot),
+        )
+        .route(
+            "/api/domains/{uuid}/snapshots/{name}/revert",
+            post(api::snapshots::revert_snapshot),
+        )
+        .route(
+            "/api/domains/{uuid}/checkpoints",
+            get(api::backups::list_checkpoints).post(api::backups::create_checkpoint),
+        )
+        .route(
+            "/api/domains/{uuid}/checkpoints/{name}",
+            delete(api::backups::delete_checkpoint),
+        )
+        .route(
+            "/api/domains/{uuid}/backups",
+            get(api::backups::list_backups).post(api::backups::start_backup),
+        )
+        .route(
+            "/api/domains/{uuid}/backup-retention",
+            get(api::backups::get_retention).put(api::backups::set_retention),
+        )
+        .route(
+            "/api/domains/{uuid}/migrate",
+            post(api::migrations::start_migration),
+        )
+        .route(
+            "/api/domains/{uuid}/cloud-init",
+            post(api::cloudinit::attach_cloud_init),
+        )
+        .route(
+            "/api/domains/{uuid}/template",
+            put(api::templates::mark_template).delete(api::templates::unmark_template),
+        )
+        .route("/api/templates", get(api::templates::list_templates))
+        .route(
+            "/api/ssh-keys",
+            get(api::ssh_keys::list_keys).post(api::ssh_keys::add_key),
+        )
+        .route("/api/ssh-keys/{id}", delete(api::ssh_keys::delete_key))
+        .route(
+            "/api/templates/{uuid}/clone",
+            post(api::templates::clone_template),
+        )
+        .route("/api/migrations", get(api::migrations::list_migrations))
+        .route("/api/migrations/{id}", get(api::migrations::get_migration))
+        .route(
+            "/api/migrations/{id}/cancel",
+            post(api::migrations::cancel_migration),
+        )
+        .route("/api/backups/{id}", get(api::backups::get_backup))
+        .route(
+            "/api/backups/{id}/cancel",
+            post(api::backups::cancel_backup),
+        )
+        .route(
+            "/api/jobs",
+            get(api::jobs::list_jobs).post(api::jobs::create_job),
+        )
+        .route(
+            "/api/jobs/{id}",
+            get(api::jobs::get_job)
+                .put(api::jobs::update_job)
+                .delete(api::jobs::delete_job),
+        )
+        .route("/api/jobs/{id}/enabled", put(api::jobs::set_job_enabled))
+        .route("/api/jobs/{id}/run", post(api::jobs::run_job))
+        .route("/api/jobs/{id}/runs", get(api::jobs::list_runs))
+        .route(
+            "/api/networks",
+            get(api::networks::list_networks).post(api::networks::define_network),
+        )
+        .route(
+            "/api/networks/{name}",
+            get(api::networks::get_network).delete(api::networks::undefine_network),
+        )
+        .route(
+            "/api/networks/{name}/start",
+            post(api::networks::start_network),
+        )
+        .route(
+            "/api/networks/{name}/stop",
+            post(api::networks::stop_network),
+        )
+        .route(
+            "/api/networks/{name}/autostart",
+            put(api::networks::set_network_autostart),
+        )
+        .route(
+            "/api/pools",
+            get(api::pools::list_pools).post(api::pools::define_pool),
+        )
+        .route(
+            "/api/pools/{name}",
+            get(api::pools::get_pool).delete(api::pools::undefine_pool),
+        )
+        .route("/api/pools/{name}/build", post(api::pools::build_pool))
+        .route("/api/pools/{name}/start", post(api::pools::start_pool))
+        .route("/api/pools/{name}/stop", post(api::pools::stop_pool))
+        .route("/api/pools/{name}/refresh", post(api::pools::refresh_pool))
+        .route("/api/pools/{name}/delete", post(api::pools::delete_pool))
+        .route(
+            "/api/pools/{name}/autostart",
+            put(api::pools::set_pool_autostart),
+        )
+        .route(
+            "/api/pools/{name}/volumes",
+            get(api::volumes::list_volumes).post(api::volumes::create_volume),
+        )
+        .route(
+            "/api/pools/{name}/volumes/{vol}",
+            get(api::volumes::get_volume).delete(api::volumes::delete_volume),
+        )
+        .route(
+            "/api/pools/{name}/volumes/{vol}/clone",
+            post(api::volumes::clone_volume),
+        )
+        .route(
+            "/api/pools/{name}/volumes/{vol}/resize",
+            post(api::volumes::resize_volume),
+        )
+        .route(
+            "/api/pools/{name}/volumes/{vol}/wipe",
+            post(api::volumes::wipe_volume),
+        )
+        .route(
+            "/api/pools/{name}/volumes/{vol}/content",
+            get(api::volumes::download_volume).put(api::volumes::upload_volume),
+        )
+        .route("/api/host", get(api::host::get_host))
+        .route("/api/nodedevs", get(api::nodedevs::get_nodedevs))
+        .route("/api/nodedevs/{name}", get(api::nodedevs::get_nodedev))
+        .route(
+            "/api/nodedevs/{name}/detach",
+            post(api::nodedevs::detach_nodedev),
+        )
+        .route(
+            "/api/nodedevs/{name}/reattach",
+            post(api::nodedevs::reattach_nodedev),
+        )
+        .route(
+            "/api/domains/{uuid}/devices",
+            get(api::devices::get_devices),
+        )
+        .route("/api/domains/{uuid}/disks", post(api::devices::attach_disk))
+        .route(
+            "/api/domains/{uuid}/disks/{target}",
+            delete(api::devices::detach_disk),
+        )
+        .route(
+            "/api/domains/{uuid}/disks/{target}/media",
+            put(api::devices::change_media).delete(api::devices::eject_media),
+        )
+        .route(
+            "/api/domains/{uuid}/interfaces",
+            post(api::devices::attach_interface),
+        )
+        .route(
+            "/api/domains/{uuid}/interfaces/{mac}",
+            delete(api::devices::detach_interface),
+        )
+        .route("/api/domains/{uuid}/vcpus", put(api::devices::set_vcpus))
+        .route("/api/domains/{uuid}/memory", put(api::devices::set_memory))
+        .route(
+            "/api/domains/{uuid}/hostdevs",
+            post(api::nodedevs::attach_hostdev),
+        )
+        .route(
+            "/api/domains/{uuid}/hostdevs/{device}",
+            delete(api::nodedevs::detach_hostdev),
+        )
+        .route(
+            "/api/secrets",
+            get(api::secrets::list_secrets).post(api::secrets::define_secret),
+        )
+        .route(
+            "/api/secrets/{uuid}",
+            get(api::secrets::get_secret).delete(api::secrets::undefine_secret),
+        )
+        .route(
+            "/api/secrets/{uuid}/value",
+            put(api::secrets::set_secret_value),
+        )
+        .route(
+            "/api/nwfilters",
+            get(api::nwfilters::get_nwfilters).post(api::nwfilters::define_nwfilter),
+        )
+        .route(
+            "/api/nwfilters/{name}",
+            get(api::nwfilters::get_nwfilter).delete(api::nwfilters::undefine_nwfilter),
+        )
+        .route(
+            "/api/nwfilter-bindings",
+            get(api::nwfilters::get_bindings).post(api::nwfilters::create_binding),
+        )
+        .route(
+            "/api/nwfilter-bindings/{portdev}",
+            get(api::nwfilters::get_binding).delete(api::nwfilters::delete_binding),
+        )
+        .route(
+            "/api/domains/{uuid}/interfaces/{mac}/filter",
+            put(api::nwfilters::set_interface_filter)
+                .delete(api::nwfilters::remove_interface_filter),
+        )
+        .route("/api/networks/{name}/leases", get(api::dhcp::list_leases))
+        .route(
+            "/api/networks/{name}/hosts",
+            post(api::dhcp::add_static_host).put(api::dhcp::modify_static_host),
+        )
+        .route(
+            "/api/networks/{name}/hosts/{mac}",
+            delete(api::dhcp::delete_static_host),
+        )
+        .route(
+            "/api/networks/{name}/dns-hosts",
+            post(api::dhcp::add_dns_host),
+        )
+        .route(
+            "/api/networks/{name}/dns-hosts/{ip}",
+            delete(api::dhcp::delete_dns_host),
+        )
+        // Probes for load balancers and orchestrators – no session required
+        .route("/healthz", get(api::health::healthz))
+        .route("/readyz", get(api::health::readyz))
+        .route("/version", get(api::health::version))
+        .route("/metrics", get(api::prometheus::get_metrics))
+        .route("/assets/{*path}", get(dashboard::assets::serve_asset))
         .route(
             "/wizard/example",
             get(wizard::wizard_get).post(wizard::wizard_post),
         )
-        .with_state(pool.clone())
-        .layer(SessionLayer::new(session_store));
+        .route_layer(axum::middleware::from_fn(telemetry::track))
+        .with_state(state.clone());
+    // Scheduled jobs call the API in-process, authenticated as their owner
+    scheduler::start(state, app.clone());
+    let app = app.layer(SessionLayer::new(session_store));
     // Register the wizard routes
     // .merge(crate::wizard::add_wizard_routes(Router::new()));
 
-    // 4️⃣  Run
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3302));
-    println!("🚀 Server listening on http://{}/", addr);
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3302").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // 4️⃣  Run – on a socket handed over by systemd if there is one
+    let addr: SocketAddr = config.listen.parse()?;
+    let listener = match systemd::activated_listener()? {
+        Some(listener) => listener,
+        None => {
+            let listener = std::net::TcpListener::bind(addr)?;
+            listener.set_nonblocking(true)?;
+            listener
+        }
+    };
+    let addr = listener.local_addr()?;
+
+    // SIGTERM/SIGINT stop accepting connections and give in‑flight requests
+    // `shutdown_timeout` seconds to finish
+    let handle = axum_server::Handle::new();
+    let drain = Duration::from_secs(config.shutdown_timeout);
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown_signal().await;
+            println!(
+                "🛑 Shutting down, draining connections for up to {:?}",
+                drain
+            );
+            systemd::notify_stopping();
+            handle.graceful_shutdown(Some(drain));
+        }
+    });
+
+    let served = match &config.tls {
+        Some(tls_config) => {
+            let rustls = tls::load(tls_config).await?;
+            tokio::spawn(tls::watch(rustls.clone(), tls_config.clone()));
+
+            if let Some(redirect_from) = &tls_config.redirect_from {
+                let redirect_addr: SocketAddr = redirect_from.parse()?;
+                let redirect = tls::redirect_app(tls_config.clone(), addr);
+                let redirect_handle = handle.clone();
+                println!("↪️  Redirecting http://{}/ to HTTPS", redirect_addr);
+                tokio::spawn(async move {
+                    if let Err(e) = axum_server::bind(redirect_addr)
+                        .handle(redirect_handle)
+                        .serve(redirect.into_make_service())
+                        .await
+                    {
+                        eprintln!("⚠️  HTTP redirect listener failed: {}", e);
+                    }
+                });
+            }
+
+            println!("🚀 Server listening on https://{}/", addr);
+            systemd::notify_ready();
+            axum_server::from_tcp_rustls(listener, rustls)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await
+        }
+        None => {
+            println!("🚀 Server listening on http://{}/", addr);
+            systemd::notify_ready();
+            axum_server::from_tcp(listener)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await
+        }
+    };
+
+    // Every request has finished (or was cut off) – release the database
+    pool.close().await;
+    println!("👋 Server stopped");
+    served?;
     Ok(())
 }
 
-/// Initialise the SQLite database (users table + default admin)
-async fn init_db(pool: &SqlitePool) -> anyhow::Result<()> {
-    // Create table if missing
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            username TEXT NOT NULL UNIQUE,
-            password_hash TEXT NOT NULL
-        );
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Add a default user (`admin` / `password`) if the table is empty
-    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
-        .fetch_one(pool)
-        .await?;
-    if count.0 == 0 {
-        let password = b"password";
-        let salt = b"admin_salt";
-        let config = Config::default();
-        let password_hash = argon2::hash_encoded(password, salt, &config).unwrap();
-
-        sqlx::query("INSERT INTO users (username, password_hash) VALUES (?, ?)")
-            .bind("admin")
-            .bind(password_hash.to_string())
-            .execute(pool)
-            .await?;
-        println!("🔑 Created default user `admin` with password `password`");
+/// Resolve on SIGINT (Ctrl‑C) or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
     }
-    Ok(())
 }
 
 /// Root handler – redirects based on session
@@ -201,19 +616,18 @@ async fn login_action(
     State(pool): State<SqlitePool>,
     Form(form): Form<LoginForm>,
 ) -> impl IntoResponse {
-    // Fetch user by username
-    let user: Option<User> =
-        sqlx::query_as("SELECT id, username, password_hash FROM users WHERE username = ?")
-            .bind(&form.username)
-            .fetch_optional(&pool)
-            .await
-            .unwrap_or(None);
+    // Fetch user by username – disabled users are treated as unknown
+    let user = users::find(&pool, &form.username)
+        .await
+        .unwrap_or(None)
+        .filter(|u| !u.disabled);
 
     if let Some(user) = user {
         // Verify password
         if argon2::verify_encoded(&user.password_hash, form.password.as_bytes()).unwrap_or(false) {
             // Store user id in session
             session.set("user_id", user.id);
+            audit::record(&pool, &user.username, "login", "").await;
             return (
                 StatusCode::FOUND,
                 axum::response::AppendHeaders([("location", "/dashboard")]),
@@ -223,6 +637,8 @@ async fn login_action(
     }
 
     // Authentication failed – reload login with error
+    audit::record(&pool, &form.username, "login.failed", "").await;
+    telemetry::login_failed();
     return (
         StatusCode::OK,
         Html(format!(
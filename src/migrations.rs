@@ -0,0 +1,140 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// migrations.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const COLUMNS: &str = "id, domain_uuid, domain_name, destination, mode, options, status, \
+    bytes_total, bytes_processed, post_copy, error, started_by, started_at, finished_at";
+
+/// One migration of a domain to a registered host.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Migration {
+    pub id: i64,
+    pub domain_uuid: String,
+    pub domain_name: String,
+    /// Name of the registered destination host
+    pub destination: String,
+    /// `live`, `suspended` or `offline`
+    pub mode: String,
+    pub options: String,
+    pub status: String,
+    pub bytes_total: Option<i64>,
+    pub bytes_processed: Option<i64>,
+    /// Whether the migration switched to post-copy
+    pub post_copy: bool,
+    pub error: Option<String>,
+    pub started_by: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Everything known about a migration when it is started.
+pub struct NewMigration<'a> {
+    pub domain_uuid: &'a str,
+    pub domain_name: &'a str,
+    pub destination: &'a str,
+    pub mode: &'a str,
+    pub options: &'a str,
+    pub started_by: &'a str,
+}
+
+/// Record a migration that has just been started; returns its id.
+pub async fn insert(pool: &SqlitePool, migration: &NewMigration<'_>) -> anyhow::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO migrations (domain_uuid, domain_name, destination, mode, options, status, \
+         started_by) VALUES (?, ?, ?, ?, ?, 'running', ?)",
+    )
+    .bind(migration.domain_uuid)
+    .bind(migration.domain_name)
+    .bind(migration.destination)
+    .bind(migration.mode)
+    .bind(migration.options)
+    .bind(migration.started_by)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn update_progress(
+    pool: &SqlitePool,
+    id: i64,
+    processed: Option<u64>,
+    total: Option<u64>,
+    post_copy: bool,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE migrations SET bytes_processed = COALESCE(?, bytes_processed), \
+         bytes_total = COALESCE(?, bytes_total), post_copy = post_copy OR ? WHERE id = ?",
+    )
+    .bind(processed.map(|v| v as i64))
+    .bind(total.map(|v| v as i64))
+    .bind(post_copy)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark a migration as `completed`, `failed` or `cancelled`.
+pub async fn finish(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    error: Option<&str>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE migrations SET status = ?, error = ?, finished_at = CURRENT_TIMESTAMP \
+         WHERE id = ?",
+    )
+    .bind(status)
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> anyhow::Result<Option<Migration>> {
+    let migration = sqlx::query_as(&format!("SELECT {} FROM migrations WHERE id = ?", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(migration)
+}
+
+/// The most recent migrations of all domains, newest first.
+pub async fn list(pool: &SqlitePool, limit: i64) -> anyhow::Result<Vec<Migration>> {
+    let migrations = sqlx::query_as(&format!(
+        "SELECT {} FROM migrations ORDER BY id DESC LIMIT ?",
+        COLUMNS
+    ))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(migrations)
+}
+
+/// Migration history of a domain, newest first.
+pub async fn list_for_domain(pool: &SqlitePool, uuid: &str) -> anyhow::Result<Vec<Migration>> {
+    let migrations = sqlx::query_as(&format!(
+        "SELECT {} FROM migrations WHERE domain_uuid = ? ORDER BY id DESC",
+        COLUMNS
+    ))
+    .bind(uuid)
+    .fetch_all(pool)
+    .await?;
+    Ok(migrations)
+}
+
+/// Mark migrations still running when the server stopped as `interrupted` –
+/// a managed migration dies with the client that drove it.
+pub async fn interrupt_running(pool: &SqlitePool) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE migrations SET status = 'interrupted', finished_at = CURRENT_TIMESTAMP \
+         WHERE status = 'running'",
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,172 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// sampler.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use virt::sys;
+
+use crate::AppState;
+use crate::api::stats::{DomainStats, all_domain_stats};
+use crate::api::{Libvirt, libvirt_error};
+use crate::scheduler::now;
+
+/// Stat groups read on every tick.
+const STATS: u32 = sys::VIR_DOMAIN_STATS_CPU_TOTAL
+    | sys::VIR_DOMAIN_STATS_BALLOON
+    | sys::VIR_DOMAIN_STATS_VCPU
+    | sys::VIR_DOMAIN_STATS_INTERFACE
+    | sys::VIR_DOMAIN_STATS_BLOCK;
+
+/// Usage of one running domain over the last sampling interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    /// Seconds since the unix epoch
+    pub time: i64,
+    /// Share of the domain's vCPUs that was busy, 0–100
+    pub cpu_percent: f64,
+    /// Current balloon size in bytes
+    pub memory: Option<u64>,
+    /// Resident set size of the hypervisor process in bytes
+    pub memory_rss: Option<u64>,
+    /// Memory in use inside the guest; needs the balloon driver
+    pub memory_used: Option<u64>,
+    /// Bytes per second over all disks and interfaces
+    pub block_read: f64,
+    pub block_write: f64,
+    pub net_rx: f64,
+    pub net_tx: f64,
+}
+
+/// Cumulative counters the rates are computed from.
+struct Counters {
+    at: Instant,
+    cpu_time: u64,
+    vcpus: u64,
+    block_read: u64,
+    block_write: u64,
+    net_rx: u64,
+    net_tx: u64,
+}
+
+impl From<&DomainStats> for Counters {
+    fn from(stats: &DomainStats) -> Counters {
+        Counters {
+            at: Instant::now(),
+            cpu_time: stats.number("cpu.time").unwrap_or(0),
+            vcpus: stats.number("vcpu.current").unwrap_or(1).max(1),
+            block_read: stats.sum("block", "rd.bytes"),
+            block_write: stats.sum("block", "wr.bytes"),
+            net_rx: stats.sum("net", "rx.bytes"),
+            net_tx: stats.sum("net", "tx.bytes"),
+        }
+    }
+}
+
+struct Series {
+    last: Counters,
+    samples: VecDeque<Sample>,
+}
+
+/// Rolling window of samples per running domain, keyed by UUID.
+#[derive(Clone, Default)]
+pub struct Metrics(Arc<Mutex<HashMap<String, Series>>>);
+
+impl Metrics {
+    /// Samples of domain `uuid`, oldest first.
+    pub fn samples(&self, uuid: &str) -> Vec<Sample> {
+        let series = self.0.lock().unwrap();
+        series
+            .get(uuid)
+            .map(|s| s.samples.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Turn a fresh set of counters into samples, keeping at most `window`
+    /// per domain. Domains that are no longer running are forgotten.
+    fn record(&self, stats: Vec<DomainStats>, window: usize) {
+        let mut series = self.0.lock().unwrap();
+        series.retain(|uuid, _| stats.iter().any(|s| &s.uuid == uuid));
+        for dom in stats {
+            let current = Counters::from(&dom);
+            let Some(entry) = series.get_mut(&dom.uuid) else {
+                series.insert(
+                    dom.uuid,
+                    Series {
+                        last: current,
+                        samples: VecDeque::new(),
+                    },
+                );
+                continue;
+            };
+            let elapsed = current.at.duration_since(entry.last.at).as_secs_f64();
+            if elapsed > 0.0 {
+                // Counters start again from zero when the guest is restarted
+                let rate = |new: u64, old: u64| new.saturating_sub(old) as f64 / elapsed;
+                let last = &entry.last;
+                let kib = |field: &str| dom.number(field).map(|k| k * 1024);
+                entry.samples.push_back(Sample {
+                    time: now(),
+                    cpu_percent: (rate(current.cpu_time, last.cpu_time)
+                        / 1e7
+                        / current.vcpus as f64)
+                        .min(100.0),
+                    memory: kib("balloon.current"),
+                    memory_rss: kib("balloon.rss"),
+                    memory_used: kib("balloon.available")
+                        .zip(kib("balloon.unused"))
+                        .map(|(available, unused)| available.saturating_sub(unused)),
+                    block_read: rate(current.block_read, last.block_read),
+                    block_write: rate(current.block_write, last.block_write),
+                    net_rx: rate(current.net_rx, last.net_rx),
+                    net_tx: rate(current.net_tx, last.net_tx),
+                });
+                while entry.samples.len() > window {
+                    entry.samples.pop_front();
+                }
+            }
+            entry.last = current;
+        }
+    }
+}
+
+fn collect(uri: &str) -> Result<Vec<DomainStats>, (axum::http::StatusCode, String)> {
+    let conn = Libvirt::open(uri)?;
+    all_domain_stats(&conn, STATS, sys::VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE)
+        .map_err(libvirt_error)
+}
+
+/// Start sampling every `metrics_interval` seconds; 0 turns sampling off.
+pub fn start(state: AppState) {
+    let every = state.config.metrics_interval;
+    if every == 0 {
+        println!("📉 Metrics sampling disabled");
+        return;
+    }
+    let window = (state.config.metrics_window / every).max(1) as usize;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(every));
+        let mut failing = false;
+        loop {
+            interval.tick().await;
+            let uri = state.config.libvirt_uri.clone();
+            match tokio::task::spawn_blocking(move || collect(&uri)).await {
+                Ok(Ok(stats)) => {
+                    state.metrics.record(stats, window);
+                    failing = false;
+                }
+                // Only report the first of a run of failures
+                Ok(Err((_, message))) if !failing => {
+                    eprintln!("⚠️  Could not sample domain metrics: {}", message);
+                    failing = true;
+                }
+                Err(e) if !failing => {
+                    eprintln!("⚠️  Could not sample domain metrics: {}", e);
+                    failing = true;
+                }
+                _ => {}
+            }
+        }
+    });
+}
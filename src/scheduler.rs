@@ -0,0 +1,158 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// scheduler.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, header},
+};
+use chrono::{Local, TimeZone};
+use croner::Cron;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower::ServiceExt;
+
+use crate::auth::RunAs;
+use crate::{AppState, jobs, users};
+
+/// How often the job table is checked for due jobs.
+const TICK: Duration = Duration::from_secs(15);
+
+/// Longest response body kept as a run's outcome.
+const OUTCOME_LIMIT: usize = 500;
+
+/// API prefixes a job may call – domain and pool actions.
+const ALLOWED_PREFIXES: [&str; 2] = ["/api/domains/", "/api/pools/"];
+
+/// Seconds since the unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// First time after `after` (unix seconds) that the cron expression
+/// `schedule` matches, in the server's local time zone.
+pub fn next_run(schedule: &str, after: i64) -> Result<i64, String> {
+    let cron = Cron::new(schedule)
+        .parse()
+        .map_err(|e| format!("invalid schedule: {}", e))?;
+    let after = Local
+        .timestamp_opt(after, 0)
+        .single()
+        .ok_or("invalid time")?;
+    cron.find_next_occurrence(&after, false)
+        .map(|next| next.timestamp())
+        .map_err(|e| format!("schedule never matches: {}", e))
+}
+
+/// Check that a job calls an action the scheduler may run.
+pub fn validate_call(method: &str, path: &str) -> Result<(), String> {
+    if !matches!(method, "POST" | "PUT" | "DELETE") {
+        return Err("method must be POST, PUT or DELETE".into());
+    }
+    if !ALLOWED_PREFIXES.iter().any(|p| path.starts_with(p)) {
+        return Err(format!(
+            "path must start with {}",
+            ALLOWED_PREFIXES.join(" or ")
+        ));
+    }
+    if path.contains("..") || path.contains('#') {
+        return Err("invalid path".into());
+    }
+    Ok(())
+}
+
+/// Start the scheduler loop. Jobs are dispatched to `app` – the API router
+/// without the session layer – as their owner.
+pub fn start(state: AppState, app: Router) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            run_due(&state, &app).await;
+        }
+    });
+}
+
+async fn run_due(state: &AppState, app: &Router) {
+    let now = now();
+    let due = match jobs::due(&state.pool, now).await {
+        Ok(due) => due,
+        Err(e) => {
+            eprintln!("⚠️  Could not load due jobs: {}", e);
+            return;
+        }
+    };
+    for job in due {
+        // Schedule the following run first so a slow job is never started twice
+        let next = next_run(&job.schedule, now).ok();
+        if let Err(e) = jobs::set_next_run(&state.pool, job.id, next).await {
+            eprintln!("⚠️  Could not reschedule job {}: {}", job.id, e);
+            continue;
+        }
+        let state = state.clone();
+        let app = app.clone();
+        tokio::spawn(async move { run(&state, app, &job).await });
+    }
+}
+
+/// Execute one job and record the outcome in its history.
+async fn run(state: &AppState, app: Router, job: &jobs::Job) {
+    let run_id = match jobs::start_run(&state.pool, job.id).await {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("⚠️  Could not record a run of job {}: {}", job.id, e);
+            return;
+        }
+    };
+    let (status, outcome) = dispatch(state, app, job).await;
+    println!(
+        "⏰ Job {} ({} {}) finished with {}",
+        job.name, job.method, job.path, status
+    );
+    if let Err(e) = jobs::finish_run(&state.pool, run_id, job.id, status, &outcome).await {
+        eprintln!("⚠️  Could not record the outcome of job {}: {}", job.id, e);
+    }
+}
+
+async fn dispatch(state: &AppState, app: Router, job: &jobs::Job) -> (u16, String) {
+    let owner = match users::find(&state.pool, &job.owner).await {
+        Ok(Some(user)) if !user.disabled => user,
+        Ok(_) => return (403, format!("owner {} is missing or disabled", job.owner)),
+        Err(e) => return (500, e.to_string()),
+    };
+    let method = match Method::from_bytes(job.method.as_bytes()) {
+        Ok(method) => method,
+        Err(e) => return (400, e.to_string()),
+    };
+    let request = Request::builder()
+        .method(method)
+        .uri(&job.path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .extension(RunAs(owner.id))
+        .body(Body::from(job.body.clone().unwrap_or_default()));
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => return (400, e.to_string()),
+    };
+
+    let response = match app.oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    };
+    let status = response.status().as_u16();
+    let body = axum::body::to_bytes(response.into_body(), 64 * 1024)
+        .await
+        .unwrap_or_default();
+    let mut outcome = String::from_utf8_lossy(&body).into_owned();
+    if outcome.len() > OUTCOME_LIMIT {
+        let mut end = OUTCOME_LIMIT;
+        while !outcome.is_char_boundary(end) {
+            end -= 1;
+        }
+        outcome.truncate(end);
+        outcome.push('…');
+    }
+    (status, outcome)
+}
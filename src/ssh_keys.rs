@@ -0,0 +1,99 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// ssh_keys.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const COLUMNS: &str = "id, user_id, name, public_key, created_at";
+
+/// Key types cloud-init images accept in `authorized_keys`.
+const KEY_TYPES: [&str; 7] = [
+    "ssh-ed25519",
+    "ssh-rsa",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "sk-ssh-ed25519@openssh.com",
+    "sk-ecdsa-sha2-nistp256@openssh.com",
+];
+
+/// A public key a user stored for cloud-init seeds.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct SshKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    /// One `authorized_keys` line: type, base64 blob and optional comment
+    pub public_key: String,
+    pub created_at: String,
+}
+
+/// Check an OpenSSH public key line and strip surrounding whitespace;
+/// `None` when it is not one.
+pub fn normalize(public_key: &str) -> Option<String> {
+    let line = public_key.trim();
+    if line.contains(['\n', '\r']) {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let kind = parts.next()?;
+    let blob = BASE64.decode(parts.next()?).ok()?;
+    // The blob starts with the key type as a length-prefixed string
+    let len = u32::from_be_bytes(blob.get(..4)?.try_into().ok()?) as usize;
+    if !KEY_TYPES.contains(&kind) || blob.get(4..4 + len)? != kind.as_bytes() {
+        return None;
+    }
+    Some(line.to_string())
+}
+
+/// Store a key for a user; names are unique per user. Returns the new id.
+pub async fn add(
+    pool: &SqlitePool,
+    user_id: i64,
+    name: &str,
+    public_key: &str,
+) -> anyhow::Result<i64> {
+    let result = sqlx::query("INSERT INTO ssh_keys (user_id, name, public_key) VALUES (?, ?, ?)")
+        .bind(user_id)
+        .bind(name)
+        .bind(public_key)
+        .execute(pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Remove one of a user's keys; false when the user has no such key.
+pub async fn delete(pool: &SqlitePool, user_id: i64, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM ssh_keys WHERE id = ? AND user_id = ?")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A user's keys ordered by name.
+pub async fn list_for_user(pool: &SqlitePool, user_id: i64) -> anyhow::Result<Vec<SshKey>> {
+    let keys = sqlx::query_as(&format!(
+        "SELECT {} FROM ssh_keys WHERE user_id = ? ORDER BY name",
+        COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(keys)
+}
+
+/// One of a user's keys.
+pub async fn get(pool: &SqlitePool, user_id: i64, id: i64) -> anyhow::Result<Option<SshKey>> {
+    let key = sqlx::query_as(&format!(
+        "SELECT {} FROM ssh_keys WHERE id = ? AND user_id = ?",
+        COLUMNS
+    ))
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(key)
+}
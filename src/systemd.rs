@@ -0,0 +1,45 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// systemd.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use sd_notify::NotifyState;
+use std::time::Duration;
+
+/// Listening socket handed over by systemd socket activation (LISTEN_FDS), if
+/// any.  Only the first socket is used.
+pub fn activated_listener() -> anyhow::Result<Option<std::net::TcpListener>> {
+    let Some(fd) = sd_notify::listen_fds()?.next() else {
+        return Ok(None);
+    };
+    // SAFETY: systemd passes ownership of the descriptor to this process and
+    // `listen_fds` marks it close‑on‑exec; nothing else in the process uses it.
+    let listener = unsafe {
+        use std::os::fd::FromRawFd;
+        std::net::TcpListener::from_raw_fd(fd)
+    };
+    listener.set_nonblocking(true)?;
+    Ok(Some(listener))
+}
+
+/// Tell systemd the service is ready and start pinging the watchdog when
+/// `WatchdogSec=` is configured.  Harmless when not started by systemd.
+pub fn notify_ready() {
+    let _ = sd_notify::notify(false, &[NotifyState::Ready]);
+
+    let mut usec = 0u64;
+    if sd_notify::watchdog_enabled(false, &mut usec) {
+        // Ping at half the configured interval as recommended by sd_watchdog_enabled(3)
+        let period = Duration::from_micros(usec / 2);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let _ = sd_notify::notify(false, &[NotifyState::Watchdog]);
+            }
+        });
+    }
+}
+
+/// Tell systemd the service has started shutting down.
+pub fn notify_stopping() {
+    let _ = sd_notify::notify(false, &[NotifyState::Stopping]);
+}
@@ -0,0 +1,221 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// telemetry.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Upper bounds (seconds) of the latency histogram buckets.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Latency histogram with cumulative counts per bucket.
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    const EMPTY: Histogram = Histogram {
+        buckets: [0; BUCKETS.len()],
+        sum: 0.0,
+        count: 0,
+    };
+
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bucket, le) in self.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= le {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// Requests by `(method, route, status)`.
+static HTTP_REQUESTS: Mutex<BTreeMap<(String, String, u16), u64>> = Mutex::new(BTreeMap::new());
+/// Request latency by `(method, route)`.
+static HTTP_LATENCY: Mutex<BTreeMap<(String, String), Histogram>> = Mutex::new(BTreeMap::new());
+static LOGIN_FAILURES: AtomicU64 = AtomicU64::new(0);
+/// Time from opening a libvirt connection to closing it again – request
+/// scoped connections only, see `Libvirt::open_long_lived`.
+static LIBVIRT_CONNECTIONS: Mutex<Histogram> = Mutex::new(Histogram::EMPTY);
+/// libvirt errors by error code.
+static LIBVIRT_ERRORS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Middleware counting requests and their latency per matched route.
+pub async fn track(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    // Label by route template so `/api/domains/{uuid}` is one series
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = started.elapsed();
+    *HTTP_REQUESTS
+        .lock()
+        .unwrap()
+        .entry((method.clone(), route.clone(), response.status().as_u16()))
+        .or_default() += 1;
+    HTTP_LATENCY
+        .lock()
+        .unwrap()
+        .entry((method, route))
+        .or_default()
+        .observe(elapsed);
+    response
+}
+
+pub fn login_failed() {
+    LOGIN_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn libvirt_connection(elapsed: Duration) {
+    LIBVIRT_CONNECTIONS.lock().unwrap().observe(elapsed);
+}
+
+pub fn libvirt_error(code: &str) {
+    *LIBVIRT_ERRORS
+        .lock()
+        .unwrap()
+        .entry(code.to_string())
+        .or_default() += 1;
+}
+
+// ---------------------------------------------------------------------
+// Prometheus text exposition format
+// ---------------------------------------------------------------------
+#[derive(Default)]
+pub struct Text(String);
+
+impl Text {
+    /// Start a metric family – `kind` is `counter`, `gauge` or `histogram`.
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.0, "# HELP {} {}", name, help);
+        let _ = writeln!(self.0, "# TYPE {} {}", name, kind);
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.0.push_str(name);
+        if !labels.is_empty() {
+            self.0.push('{');
+            for (i, (label, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.0.push(',');
+                }
+                let _ = write!(self.0, "{}=\"{}\"", label, escape_label(value));
+            }
+            self.0.push('}');
+        }
+        let _ = writeln!(self.0, " {}", value);
+    }
+
+    fn histogram(&mut self, name: &str, labels: &[(&str, &str)], histogram: &Histogram) {
+        for (count, le) in histogram.buckets.iter().zip(BUCKETS) {
+            let le = le.to_string();
+            let mut with_le = labels.to_vec();
+            with_le.push(("le", le.as_str()));
+            self.sample(&format!("{}_bucket", name), &with_le, *count as f64);
+        }
+        let mut with_le = labels.to_vec();
+        with_le.push(("le", "+Inf"));
+        self.sample(
+            &format!("{}_bucket", name),
+            &with_le,
+            histogram.count as f64,
+        );
+        self.sample(&format!("{}_sum", name), labels, histogram.sum);
+        self.sample(&format!("{}_count", name), labels, histogram.count as f64);
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Append the application's own metrics.
+pub fn render(out: &mut Text) {
+    out.family(
+        "rust_manager_http_requests_total",
+        "counter",
+        "HTTP requests by method, route and status.",
+    );
+    for ((method, route, status), count) in HTTP_REQUESTS.lock().unwrap().iter() {
+        out.sample(
+            "rust_manager_http_requests_total",
+            &[
+                ("method", method.as_str()),
+                ("route", route.as_str()),
+                ("status", status.to_string().as_str()),
+            ],
+            *count as f64,
+        );
+    }
+    out.family(
+        "rust_manager_http_request_duration_seconds",
+        "histogram",
+        "HTTP request latency by method and route.",
+    );
+    for ((method, route), histogram) in HTTP_LATENCY.lock().unwrap().iter() {
+        out.histogram(
+            "rust_manager_http_request_duration_seconds",
+            &[("method", method.as_str()), ("route", route.as_str())],
+            histogram,
+        );
+    }
+    out.family(
+        "rust_manager_login_failures_total",
+        "counter",
+        "Failed login attempts.",
+    );
+    out.sample(
+        "rust_manager_login_failures_total",
+        &[],
+        LOGIN_FAILURES.load(Ordering::Relaxed) as f64,
+    );
+    out.family(
+        "rust_manager_libvirt_connection_lifetime_seconds",
+        "histogram",
+        "Time request scoped libvirt connections were held open; streams, consoles, \
+         migrations and event loops are not counted.",
+    );
+    out.histogram(
+        "rust_manager_libvirt_connection_lifetime_seconds",
+        &[],
+        &LIBVIRT_CONNECTIONS.lock().unwrap(),
+    );
+    out.family(
+        "rust_manager_libvirt_errors_total",
+        "counter",
+        "libvirt errors by error code.",
+    );
+    for (code, count) in LIBVIRT_ERRORS.lock().unwrap().iter() {
+        out.sample(
+            "rust_manager_libvirt_errors_total",
+            &[("code", code.as_str())],
+            *count as f64,
+        );
+    }
+}
@@ -0,0 +1,71 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// templates.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const COLUMNS: &str = "domain_uuid, domain_name, description, created_by, created_at";
+
+/// A domain new domains are cloned from.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Template {
+    pub domain_uuid: String,
+    /// Name when the domain was marked
+    pub domain_name: String,
+    pub description: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// Mark a domain as template, or update the description of one.
+pub async fn mark(
+    pool: &SqlitePool,
+    uuid: &str,
+    name: &str,
+    description: &str,
+    created_by: &str,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO templates (domain_uuid, domain_name, description, created_by) \
+         VALUES (?, ?, ?, ?) ON CONFLICT (domain_uuid) DO UPDATE SET \
+         domain_name = excluded.domain_name, description = excluded.description",
+    )
+    .bind(uuid)
+    .bind(name)
+    .bind(description)
+    .bind(created_by)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Turn a template back into a plain domain; false when it was none.
+pub async fn unmark(pool: &SqlitePool, uuid: &str) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM templates WHERE domain_uuid = ?")
+        .bind(uuid)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get(pool: &SqlitePool, uuid: &str) -> anyhow::Result<Option<Template>> {
+    let template = sqlx::query_as(&format!(
+        "SELECT {} FROM templates WHERE domain_uuid = ?",
+        COLUMNS
+    ))
+    .bind(uuid)
+    .fetch_optional(pool)
+    .await?;
+    Ok(template)
+}
+
+/// All templates ordered by name.
+pub async fn list(pool: &SqlitePool) -> anyhow::Result<Vec<Template>> {
+    let templates = sqlx::query_as(&format!(
+        "SELECT {} FROM templates ORDER BY domain_name",
+        COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(templates)
+}
@@ -0,0 +1,168 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// tls.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use axum::{
+    Router,
+    http::{HeaderMap, Uri, header},
+    response::{IntoResponse, Redirect},
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls_pki_types::{CertificateDer, ServerName, pem::PemObject};
+use std::net::{IpAddr, SocketAddr};
+use std::{path::Path, time::Duration, time::SystemTime};
+
+use crate::config::TlsConfig;
+
+/// Build the rustls config from the certificate/key pair in `tls`.  When the
+/// files are missing and `self_signed` is enabled a certificate is generated
+/// first (handy for first‑run and development setups).
+pub async fn load(tls: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    if !tls.cert.exists() || !tls.key.exists() {
+        if !tls.self_signed {
+            anyhow::bail!(
+                "TLS certificate {} or key {} not found",
+                tls.cert.display(),
+                tls.key.display()
+            );
+        }
+        generate_self_signed(&tls.cert, &tls.key, &tls.self_signed_names)?;
+        println!(
+            "🔏 Generated self-signed certificate {} for {:?}",
+            tls.cert.display(),
+            tls.self_signed_names
+        );
+    }
+    let config = RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
+    Ok(config)
+}
+
+/// Write a fresh self‑signed certificate and private key as PEM files.
+pub fn generate_self_signed(cert: &Path, key: &Path, names: &[String]) -> anyhow::Result<()> {
+    let generated = rcgen::generate_simple_self_signed(names.to_vec())?;
+    for path in [cert, key] {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+    std::fs::write(cert, generated.cert.pem())?;
+    std::fs::write(key, generated.key_pair.serialize_pem())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(key, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// Reload the certificates on SIGHUP or whenever one of the files changes on
+/// disk.  Runs forever – spawn it next to the server.
+pub async fn watch(config: RustlsConfig, tls: TlsConfig) {
+    let mut last = modified(&tls);
+    let mut interval = tokio::time::interval(Duration::from_secs(tls.reload_interval.max(1)));
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+
+    loop {
+        #[cfg(unix)]
+        let by_signal = tokio::select! {
+            _ = interval.tick() => false,
+            Some(_) = async {
+                match hangup.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => true,
+        };
+        #[cfg(not(unix))]
+        let by_signal = {
+            interval.tick().await;
+            false
+        };
+
+        let current = modified(&tls);
+        if !by_signal && current == last {
+            continue;
+        }
+        last = current;
+
+        // Keep serving the old certificate when the new one is broken
+        match config.reload_from_pem_file(&tls.cert, &tls.key).await {
+            Ok(()) => println!("🔁 Reloaded TLS certificate {}", tls.cert.display()),
+            Err(e) => eprintln!("⚠️  TLS reload failed, keeping old certificate: {}", e),
+        }
+    }
+}
+
+/// Latest modification time of the certificate and key files.
+fn modified(tls: &TlsConfig) -> Option<SystemTime> {
+    let cert = std::fs::metadata(&tls.cert).and_then(|m| m.modified()).ok();
+    let key = std::fs::metadata(&tls.key).and_then(|m| m.modified()).ok();
+    cert.max(key)
+}
+
+/// Whether the leaf certificate in the PEM file `cert` is valid for `host`.
+/// The file is read each time so a reloaded certificate counts at once.
+fn certificate_covers(cert: &Path, host: &str) -> bool {
+    let Ok(der) = CertificateDer::from_pem_file(cert) else {
+        return false;
+    };
+    let Ok(cert) = webpki::EndEntityCert::try_from(&der) else {
+        return false;
+    };
+    ServerName::try_from(host)
+        .is_ok_and(|name| cert.verify_is_valid_for_subject_name(&name).is_ok())
+}
+
+/// Host for the redirect URL: the request's own host when the certificate
+/// is valid for it or it is the listen address, otherwise the listen
+/// address or the first configured certificate name. Trusting any Host
+/// header would make the listener an open redirect.
+fn redirect_host(requested: &str, tls: &TlsConfig, listen: SocketAddr) -> String {
+    // Strip a port from the Host header; [v6]:port keeps its brackets
+    let host = match requested.find(']') {
+        Some(end) if requested.starts_with('[') => &requested[..=end],
+        _ => requested.split(':').next().unwrap_or_default(),
+    };
+    let bare = host.trim_start_matches('[').trim_end_matches(']');
+    let is_listen_ip = bare.parse().is_ok_and(|ip: IpAddr| ip == listen.ip());
+    if !bare.is_empty() && (is_listen_ip || certificate_covers(&tls.cert, bare)) {
+        return host.to_string();
+    }
+    let fallback = match listen.ip() {
+        ip if ip.is_unspecified() => tls
+            .self_signed_names
+            .first()
+            .cloned()
+            .unwrap_or_else(|| "localhost".into()),
+        ip => ip.to_string(),
+    };
+    match fallback.parse() {
+        Ok(IpAddr::V6(ip)) => format!("[{}]", ip),
+        _ => fallback,
+    }
+}
+
+/// Router for the plain HTTP listener – every request is redirected to the
+/// same path on the HTTPS listener at `listen`.
+pub fn redirect_app(tls: TlsConfig, listen: SocketAddr) -> Router {
+    let https_port = listen.port();
+    Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+        let requested = headers
+            .get(header::HOST)
+            .and_then(|h| h.to_str().ok())
+            .or(uri.host())
+            .unwrap_or_default();
+        let host = redirect_host(requested, &tls, listen);
+        let path = uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/")
+            .to_string();
+        let target = if https_port == 443 {
+            format!("https://{}{}", host, path)
+        } else {
+            format!("https://{}:{}{}", host, https_port, path)
+        };
+        Redirect::permanent(&target).into_response()
+    })
+}
@@ -0,0 +1,110 @@
+// ──────────────────────────────────────────────────────────────────────────────
+// users.rs
+// ──────────────────────────────────────────────────────────────────────────────
+use argon2::{self, Config};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// User record – only the fields we need for authentication
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub password_hash: String, // Argon2 hash
+    pub disabled: bool,
+    /// Administrators may set secret values
+    pub admin: bool,
+}
+
+/// Hash a password with Argon2 and a random 16‑byte salt.
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let hash = argon2::hash_encoded(password.as_bytes(), &salt, &Config::default())?;
+    Ok(hash)
+}
+
+/// Fetch a user by name (disabled users included).
+pub async fn find(pool: &SqlitePool, username: &str) -> anyhow::Result<Option<User>> {
+    let user = sqlx::query_as(
+        "SELECT id, username, password_hash, disabled, admin FROM users WHERE username = ?",
+    )
+    .bind(username)
+    .fetch_optional(pool)
+    .await?;
+    Ok(user)
+}
+
+/// Fetch a user by id (disabled users included).
+pub async fn find_by_id(pool: &SqlitePool, id: i64) -> anyhow::Result<Option<User>> {
+    let user = sqlx::query_as(
+        "SELECT id, username, password_hash, disabled, admin FROM users WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(user)
+}
+
+/// All users ordered by name.
+pub async fn list(pool: &SqlitePool) -> anyhow::Result<Vec<User>> {
+    let users = sqlx::query_as(
+        "SELECT id, username, password_hash, disabled, admin FROM users ORDER BY username",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(users)
+}
+
+/// Create a new, enabled user.
+pub async fn add(pool: &SqlitePool, username: &str, password: &str) -> anyhow::Result<()> {
+    let hash = hash_password(password)?;
+    sqlx::query("INSERT INTO users (username, password_hash) VALUES (?, ?)")
+        .bind(username)
+        .bind(hash)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Replace a user's password.
+pub async fn set_password(pool: &SqlitePool, username: &str, password: &str) -> anyhow::Result<()> {
+    let hash = hash_password(password)?;
+    let result = sqlx::query("UPDATE users SET password_hash = ? WHERE username = ?")
+        .bind(hash)
+        .bind(username)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        anyhow::bail!("no such user `{}`", username);
+    }
+    Ok(())
+}
+
+/// Disable (or re‑enable) a user.  Disabled users cannot log in.
+pub async fn set_disabled(pool: &SqlitePool, username: &str, disabled: bool) -> anyhow::Result<()> {
+    let result = sqlx::query("UPDATE users SET disabled = ? WHERE username = ?")
+        .bind(disabled)
+        .bind(username)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        anyhow::bail!("no such user `{}`", username);
+    }
+    Ok(())
+}
+
+/// Grant (or revoke) administrator rights.
+pub async fn set_admin(pool: &SqlitePool, username: &str, admin: bool) -> anyhow::Result<()> {
+    let result = sqlx::query("UPDATE users SET admin = ? WHERE username = ?")
+        .bind(admin)
+        .bind(username)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        anyhow::bail!("no such user `{}`", username);
+    }
+    Ok(())
+}